@@ -0,0 +1,224 @@
+use crate::nes::{
+    parse_addr_range, Breakpoint, Filter, NtscFilter, NtscSettings, Palette, RamPattern, Region,
+    SyncMode, TraceCondition, TraceFormat, Tracer,
+};
+
+#[cfg(test)]
+mod test;
+
+// file where --trace-ring dumps the last traced instructions after a crash
+const TRACE_CRASH_DUMP: &str = "trace-crash.log";
+
+#[derive(Default, Debug, PartialEq)]
+pub enum Command {
+    // opens the window and runs the game
+    #[default]
+    Run,
+    // runs headless,driven by the monitor over stdin/stdout
+    Monitor,
+    // runs headless until the frame is complete and saves it as PNG
+    Screenshot {
+        frame: usize,
+        path: String,
+    },
+}
+
+#[derive(Default)]
+pub struct Options {
+    pub command: Command,
+    // iNES file to run,the built-in snake game runs when there is none
+    pub rom: Option<String>,
+    pub tracer: Option<Tracer>,
+    pub breakpoints: Vec<Breakpoint>,
+    pub gdb_port: Option<u16>,
+    pub unlimited_sprites: bool,
+    // palette from --palette or --ntsc,the built-in one is used when there is none
+    pub palette: Option<Palette>,
+    // forces the timing of a console,otherwise it comes from the ROM header
+    pub region: Option<Region>,
+    pub filter: Filter,
+    // Y4M file the frames are recorded into,the audio goes next to it as WAV
+    pub record: Option<String>,
+    pub sync: SyncMode,
+    // times the speed of the console while fast forwarding,None is as fast as possible
+    pub fast_forward: Option<u32>,
+    // what the RAM,CHR-RAM and OAM hold at power on
+    pub ram_pattern: RamPattern,
+}
+
+#[derive(Default)]
+struct TraceOptions {
+    file: Option<String>,
+    ring: Option<usize>,
+    format: Option<TraceFormat>,
+    start: Option<TraceCondition>,
+    stop: Option<TraceCondition>,
+}
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(args: I) -> Result<Options, String> {
+        let mut args = args.peekable();
+        let command = match args.next_if(|arg| arg == "monitor") {
+            Some(_) => Command::Monitor,
+            None => Command::Run,
+        };
+        let mut trace = TraceOptions::default();
+        let mut breakpoints = Vec::new();
+        let mut gdb_port = None;
+        let mut rom = None;
+        let mut unlimited_sprites = false;
+        let mut palette_file = None;
+        let mut ntsc = None;
+        let mut region = None;
+        let mut filter = None;
+        let mut screenshot = None;
+        let mut record = None;
+        let mut sync = SyncMode::default();
+        let mut fast_forward = None;
+        let mut ram_pattern = RamPattern::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+            match arg.as_str() {
+                "--rom" => rom = Some(value()?),
+                "--trace" => trace.file = Some(value()?),
+                "--trace-ring" => trace.ring = Some(parse_number(&value()?)?),
+                "--trace-format" => trace.format = Some(parse_trace_format(&value()?)?),
+                "--trace-start-pc" => {
+                    let range = parse_addr_range(&value()?)?;
+                    trace.start = Some(TraceCondition::ProgramCounter(range));
+                }
+                "--trace-stop-pc" => {
+                    let range = parse_addr_range(&value()?)?;
+                    trace.stop = Some(TraceCondition::ProgramCounter(range));
+                }
+                "--trace-start-frame" => {
+                    trace.start = Some(TraceCondition::Frame(parse_number(&value()?)?));
+                }
+                "--trace-stop-frame" => {
+                    trace.stop = Some(TraceCondition::Frame(parse_number(&value()?)?));
+                }
+                "--screenshot-at-frame" => {
+                    let frame = parse_number(&value()?)?;
+                    screenshot = Some((frame, value()?));
+                }
+                "--record" => record = Some(value()?),
+                "--sync" => sync = value()?.parse()?,
+                "--fast-forward" => fast_forward = parse_fast_forward(&value()?)?,
+                "--ram-pattern" => ram_pattern = value()?.parse()?,
+                "--no-sprite-limit" => unlimited_sprites = true,
+                "--palette" => palette_file = Some(value()?),
+                "--filter" => filter = Some(value()?.parse::<Filter>()?),
+                "--region" => region = Some(value()?.parse()?),
+                "--ntsc" => ntsc = Some(value()?.parse::<NtscSettings>()?),
+                "--break" => breakpoints.push(value()?.parse()?),
+                "--gdb" => {
+                    let port = value()?;
+                    gdb_port = Some(port.parse().map_err(|_| format!("Invalid port {}", port))?);
+                }
+                _ => return Err(format!("Unknown argument {}", arg)),
+            }
+        }
+
+        if command == Command::Monitor && record.is_some() {
+            return Err(String::from("--record can't be used with the monitor"));
+        }
+        let command = match (command, screenshot) {
+            (Command::Monitor, Some(_)) => {
+                return Err(String::from(
+                    "--screenshot-at-frame can't be used with the monitor",
+                ))
+            }
+            (_, Some((frame, path))) => Command::Screenshot { frame, path },
+            (command, None) => command,
+        };
+
+        Ok(Options {
+            command,
+            rom,
+            tracer: trace.build()?,
+            breakpoints,
+            gdb_port,
+            unlimited_sprites,
+            palette: build_palette(palette_file, ntsc)?,
+            region,
+            filter: match (filter, ntsc) {
+                // the NTSC filter uses the TV settings of --ntsc too
+                (Some(Filter::Ntsc(_)), Some(settings)) => Filter::Ntsc(NtscFilter::new(settings)),
+                (filter, _) => filter.unwrap_or_default(),
+            },
+            record,
+            sync,
+            fast_forward,
+            ram_pattern,
+        })
+    }
+}
+
+fn build_palette(
+    file: Option<String>,
+    ntsc: Option<NtscSettings>,
+) -> Result<Option<Palette>, String> {
+    match (file, ntsc) {
+        (Some(_), Some(_)) => Err(String::from("--palette and --ntsc can't be used together")),
+        (Some(file), None) => Palette::load(&file).map(Some),
+        (None, Some(settings)) => Ok(Some(Palette::generate(&settings))),
+        (None, None) => Ok(None),
+    }
+}
+
+impl TraceOptions {
+    fn build(self) -> Result<Option<Tracer>, String> {
+        let tracer = match (self.file, self.ring) {
+            (Some(_), Some(_)) => {
+                return Err(String::from(
+                    "--trace and --trace-ring can't be used together",
+                ))
+            }
+            (Some(file), None) => Tracer::to_file(&file)
+                .map_err(|err| format!("Could not create {}: {}", file, err))?,
+            (None, Some(capacity)) => Tracer::ring_buffer(capacity, TRACE_CRASH_DUMP),
+            (None, None) => return Ok(None),
+        };
+
+        let mut tracer = tracer;
+        if let Some(format) = self.format {
+            tracer = tracer.with_format(format);
+        }
+        if let Some(start) = self.start {
+            tracer = tracer.start_when(start);
+        }
+        if let Some(stop) = self.stop {
+            tracer = tracer.stop_when(stop);
+        }
+        Ok(Some(tracer))
+    }
+}
+
+fn parse_number(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid number {}", value))
+}
+
+// "max" or a speed of 2x or more
+fn parse_fast_forward(value: &str) -> Result<Option<u32>, String> {
+    match value {
+        "max" => Ok(None),
+        _ => match value.trim_end_matches('x').parse::<u32>() {
+            Ok(speed) if speed >= 2 => Ok(Some(speed)),
+            _ => Err(format!(
+                "Invalid fast forward {},expected max or 2x or more",
+                value
+            )),
+        },
+    }
+}
+
+fn parse_trace_format(value: &str) -> Result<TraceFormat, String> {
+    match value {
+        "nintendulator" => Ok(TraceFormat::Nintendulator),
+        "mesen" => Ok(TraceFormat::Mesen),
+        _ => Err(format!("Unknown trace format {}", value)),
+    }
+}
@@ -0,0 +1,176 @@
+use crate::cli::{Command, Options};
+use crate::nes::{Filter, Palette, RamPattern, Region, SyncMode};
+
+fn args(values: &[&str]) -> impl Iterator<Item = String> {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .into_iter()
+}
+
+#[test]
+fn test_parse_without_arguments() {
+    let options = Options::parse(args(&[])).unwrap();
+    assert_eq!(options.command, Command::Run);
+    assert!(options.tracer.is_none());
+}
+
+#[test]
+fn test_parse_trace_ring() {
+    let options =
+        Options::parse(args(&["--trace-ring", "5000", "--trace-start-frame", "2"])).unwrap();
+    assert!(options.tracer.is_some());
+}
+
+#[test]
+fn test_parse_trace_and_trace_ring_are_exclusive() {
+    let result = Options::parse(args(&["--trace", "a.log", "--trace-ring", "10"]));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_missing_value() {
+    let result = Options::parse(args(&["--trace-ring"]));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_unknown_argument() {
+    let result = Options::parse(args(&["--turbo"]));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_breakpoints() {
+    let options = Options::parse(args(&["--break", "C000", "--break", "write:4014"])).unwrap();
+    assert_eq!(options.breakpoints.len(), 2);
+}
+
+#[test]
+fn test_parse_invalid_breakpoint() {
+    let result = Options::parse(args(&["--break", "exec:zz"]));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_gdb_port() {
+    let options = Options::parse(args(&["--gdb", "1234"])).unwrap();
+    assert_eq!(options.gdb_port, Some(1234));
+    assert!(Options::parse(args(&["--gdb", "99999"])).is_err());
+}
+
+#[test]
+fn test_parse_monitor_subcommand() {
+    let options = Options::parse(args(&["monitor", "--break", "C000"])).unwrap();
+    assert_eq!(options.command, Command::Monitor);
+    assert_eq!(options.breakpoints.len(), 1);
+}
+
+#[test]
+fn test_parse_rom() {
+    let options = Options::parse(args(&["--rom", "game.nes"])).unwrap();
+    assert_eq!(options.rom, Some(String::from("game.nes")));
+}
+
+#[test]
+fn test_parse_no_sprite_limit() {
+    let options = Options::parse(args(&["--no-sprite-limit"])).unwrap();
+    assert!(options.unlimited_sprites);
+    assert!(!Options::parse(args(&[])).unwrap().unlimited_sprites);
+}
+
+#[test]
+fn test_parse_palette() {
+    let path = std::env::temp_dir().join("cli-test-palette.pal");
+    std::fs::write(&path, vec![0x11; 192]).unwrap();
+    let options = Options::parse(args(&["--palette", path.to_str().unwrap()])).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(options.palette.unwrap().color(0x20), (0x11, 0x11, 0x11));
+    assert!(Options::parse(args(&[])).unwrap().palette.is_none());
+    assert!(Options::parse(args(&["--palette", "/missing/file.pal"])).is_err());
+}
+
+#[test]
+fn test_parse_ntsc_palette() {
+    let options = Options::parse(args(&["--ntsc", "hue=5,saturation=1.2"])).unwrap();
+    assert_ne!(options.palette.unwrap(), Palette::default());
+
+    assert!(Options::parse(args(&["--ntsc", "tint=5"])).is_err());
+    assert!(Options::parse(args(&["--ntsc", "", "--palette", "a.pal"])).is_err());
+}
+
+#[test]
+fn test_parse_region() {
+    let options = Options::parse(args(&["--region", "pal"])).unwrap();
+    assert_eq!(options.region, Some(Region::Pal));
+    assert!(Options::parse(args(&[])).unwrap().region.is_none());
+    assert!(Options::parse(args(&["--region", "secam"])).is_err());
+}
+
+#[test]
+fn test_parse_filter() {
+    let options = Options::parse(args(&["--filter", "ntsc", "--ntsc", "hue=10"])).unwrap();
+    assert!(matches!(options.filter, Filter::Ntsc(_)));
+    assert!(matches!(
+        Options::parse(args(&[])).unwrap().filter,
+        Filter::None
+    ));
+    assert!(Options::parse(args(&["--filter", "blur"])).is_err());
+}
+
+#[test]
+fn test_parse_screenshot() {
+    let options = Options::parse(args(&["--screenshot-at-frame", "60", "out.png"])).unwrap();
+    assert_eq!(
+        options.command,
+        Command::Screenshot {
+            frame: 60,
+            path: String::from("out.png")
+        }
+    );
+    assert!(Options::parse(args(&["--screenshot-at-frame", "60"])).is_err());
+    assert!(Options::parse(args(&["--screenshot-at-frame", "x", "out.png"])).is_err());
+    assert!(Options::parse(args(&["monitor", "--screenshot-at-frame", "1", "a.png"])).is_err());
+}
+
+#[test]
+fn test_parse_record() {
+    let options = Options::parse(args(&["--record", "bug.y4m"])).unwrap();
+    assert_eq!(options.record, Some(String::from("bug.y4m")));
+    assert!(Options::parse(args(&[])).unwrap().record.is_none());
+    assert!(Options::parse(args(&["monitor", "--record", "bug.y4m"])).is_err());
+}
+
+#[test]
+fn test_parse_sync() {
+    let options = Options::parse(args(&["--sync", "display"])).unwrap();
+    assert_eq!(options.sync, SyncMode::Display);
+    assert_eq!(Options::parse(args(&[])).unwrap().sync, SyncMode::Timer);
+    assert!(Options::parse(args(&["--sync", "vsync"])).is_err());
+}
+
+#[test]
+fn test_parse_fast_forward() {
+    assert_eq!(Options::parse(args(&[])).unwrap().fast_forward, None);
+    let options = Options::parse(args(&["--fast-forward", "4x"])).unwrap();
+    assert_eq!(options.fast_forward, Some(4));
+    let options = Options::parse(args(&["--fast-forward", "3"])).unwrap();
+    assert_eq!(options.fast_forward, Some(3));
+    let options = Options::parse(args(&["--fast-forward", "max"])).unwrap();
+    assert_eq!(options.fast_forward, None);
+    assert!(Options::parse(args(&["--fast-forward", "1x"])).is_err());
+    assert!(Options::parse(args(&["--fast-forward", "fast"])).is_err());
+}
+
+#[test]
+fn test_parse_ram_pattern() {
+    assert_eq!(
+        Options::parse(args(&[])).unwrap().ram_pattern,
+        RamPattern::Zeros
+    );
+    let options = Options::parse(args(&["--ram-pattern", "random:42"])).unwrap();
+    assert_eq!(options.ram_pattern, RamPattern::Random(42));
+    assert!(Options::parse(args(&["--ram-pattern", "noise"])).is_err());
+}
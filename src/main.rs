@@ -1,9 +1,17 @@
+mod cli;
 mod nes;
-use nes::NES;
+use cli::{Command, Options};
+use nes::{RamPattern, NES};
+use std::path::Path;
 
 fn main() {
-    // init sdl2
-    let sdl_context = sdl2::init().unwrap();
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
 
     let game_code = vec![
         0x20, 0x06, 0x06, 0x20, 0x38, 0x06, 0x20, 0x0d, 0x06, 0x20, 0x2a, 0x06, 0x60, 0xa9, 0x02,
@@ -29,7 +37,80 @@ fn main() {
         0x60, 0xa6, 0xff, 0xea, 0xea, 0xca, 0xd0, 0xfb, 0x60,
     ];
 
-    let mut nes = NES::new(game_code, sdl_context);
+    let mut nes = match options.rom {
+        Some(path) => {
+            let raw = std::fs::read(&path).unwrap_or_else(|err| {
+                eprintln!("Could not read {}: {}", path, err);
+                std::process::exit(1);
+            });
+            let mut nes = NES::from_rom(&raw).unwrap_or_else(|err| {
+                eprintln!("Could not load {}: {}", path, err);
+                std::process::exit(1);
+            });
+            if let Some(name) = Path::new(&path).file_name() {
+                nes.set_rom_name(&name.to_string_lossy());
+            }
+            nes
+        }
+        None => NES::new(game_code),
+    };
+    if options.ram_pattern != RamPattern::Zeros {
+        // the seed of a random pattern is printed so the run can be repeated with random:SEED
+        println!("RAM pattern: {}", options.ram_pattern);
+        nes.set_ram_pattern(options.ram_pattern);
+    }
+    if let Some(region) = options.region {
+        nes.set_region(region);
+    }
+    nes.set_filter(options.filter);
+    nes.set_sync(options.sync);
+    nes.controls_mut()
+        .set_fast_forward_speed(options.fast_forward);
+    nes.set_tracer(options.tracer);
+    nes.set_unlimited_sprites(options.unlimited_sprites);
+    if let Some(palette) = options.palette {
+        nes.set_palette(palette);
+    }
+    for breakpoint in options.breakpoints {
+        nes.add_breakpoint(breakpoint);
+    }
+    if let Some(path) = options.record {
+        if let Err(err) = nes.start_recording(&path) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+    if let Some(port) = options.gdb_port {
+        match nes.listen_gdb(port) {
+            Ok(addr) => println!("Waiting for gdb on {}", addr),
+            Err(err) => {
+                eprintln!("Could not listen for gdb on port {}: {}", port, err);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    nes.run();
+    match options.command {
+        Command::Run => nes.run(),
+        Command::Monitor => {
+            let stdin = std::io::stdin();
+            if let Err(err) = nes.run_monitor(stdin.lock(), std::io::stdout()) {
+                eprintln!("Monitor stopped: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Command::Screenshot { frame, path } => {
+            let result = nes
+                .run_until_frame(frame)
+                .and_then(|_| nes.screenshot(&path))
+                .and_then(|_| nes.stop_recording());
+            match result {
+                Ok(()) => println!("Frame {} saved to {}", frame, path),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod test;
+
+// speeds slow motion goes through,the last one goes back to normal
+const SLOW_MOTION_SPEEDS: [f64; 3] = [0.5, 0.25, 1.0];
+
+/*
+  how the run loop moves the emulation forward: paused,one frame at a time,faster or slower than the console.
+  pausing always waits for the frame being emulated to finish,so frame advance runs exactly one frame
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Controls {
+    paused: bool,
+    // the frame being emulated has to finish before stopping
+    finishing_frame: bool,
+    fast_forward: bool,
+    // times the speed of the console when fast forwarding,None is as fast as the host can go
+    fast_forward_speed: Option<u32>,
+    slow_motion: f64,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Controls {
+            paused: false,
+            finishing_frame: false,
+            fast_forward: false,
+            fast_forward_speed: None,
+            slow_motion: 1.0,
+        }
+    }
+}
+
+impl Controls {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // false while the emulation has to wait for the user
+    pub fn is_running(&self) -> bool {
+        !self.paused || self.finishing_frame
+    }
+
+    pub fn pause(&mut self) {
+        if !self.paused {
+            self.paused = true;
+            self.finishing_frame = true;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.finishing_frame = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    // runs one more frame and pauses again,it pauses a running emulation at the end of the frame
+    pub fn advance_frame(&mut self) {
+        self.paused = true;
+        self.finishing_frame = true;
+    }
+
+    // called by the run loop every time a frame is complete
+    pub fn end_frame(&mut self) {
+        self.finishing_frame = false;
+    }
+
+    pub fn set_fast_forward(&mut self, fast_forward: bool) {
+        self.fast_forward = fast_forward;
+    }
+
+    pub fn set_fast_forward_speed(&mut self, speed: Option<u32>) {
+        self.fast_forward_speed = speed;
+    }
+
+    // 100% -> 50% -> 25% -> 100%,returns the new speed
+    pub fn next_slow_motion(&mut self) -> f64 {
+        let current = SLOW_MOTION_SPEEDS
+            .iter()
+            .position(|speed| *speed == self.slow_motion)
+            .unwrap_or(SLOW_MOTION_SPEEDS.len() - 1);
+        self.slow_motion = SLOW_MOTION_SPEEDS[(current + 1) % SLOW_MOTION_SPEEDS.len()];
+        self.slow_motion
+    }
+
+    // times the speed of the console,None is uncapped.fast forward wins over slow motion
+    pub fn speed(&self) -> Option<f64> {
+        if self.fast_forward {
+            self.fast_forward_speed.map(|speed| speed as f64)
+        } else {
+            Some(self.slow_motion)
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use crate::nes::controls::Controls;
+
+#[test]
+fn test_pause_waits_for_the_frame() {
+    let mut controls = Controls::default();
+    assert!(controls.is_running());
+
+    controls.pause();
+    assert!(controls.is_paused());
+    assert!(controls.is_running());
+    controls.end_frame();
+    assert!(!controls.is_running());
+
+    controls.toggle_pause();
+    assert!(!controls.is_paused());
+    assert!(controls.is_running());
+}
+
+#[test]
+fn test_frame_advance_runs_one_frame() {
+    let mut controls = Controls::default();
+    controls.pause();
+    controls.end_frame();
+
+    controls.advance_frame();
+    assert!(controls.is_running());
+    controls.end_frame();
+    assert!(!controls.is_running());
+    assert!(controls.is_paused());
+}
+
+#[test]
+fn test_fast_forward() {
+    let mut controls = Controls::default();
+    assert_eq!(controls.speed(), Some(1.0));
+
+    controls.set_fast_forward(true);
+    assert_eq!(controls.speed(), None);
+    controls.set_fast_forward_speed(Some(3));
+    assert_eq!(controls.speed(), Some(3.0));
+
+    controls.set_fast_forward(false);
+    assert_eq!(controls.speed(), Some(1.0));
+}
+
+#[test]
+fn test_slow_motion() {
+    let mut controls = Controls::default();
+    assert_eq!(controls.next_slow_motion(), 0.5);
+    assert_eq!(controls.speed(), Some(0.5));
+    assert_eq!(controls.next_slow_motion(), 0.25);
+    assert_eq!(controls.next_slow_motion(), 1.0);
+
+    // holding fast forward during slow motion
+    controls.next_slow_motion();
+    controls.set_fast_forward(true);
+    assert_eq!(controls.speed(), None);
+}
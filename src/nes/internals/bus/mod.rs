@@ -1,4 +1,11 @@
-use super::memory::Memory;
+use super::{
+    cartridge::{Cartridge, Chr, Mirroring, CHR_RAM_SIZE, PRG_ROM_PAGE_SIZE},
+    events::{Event, EventKind, EventLog},
+    memory::Memory,
+    ppu::{OAMDATA, PPU},
+    ram_pattern::RamPattern,
+    region::Region,
+};
 
 pub const RAM_START: u16 = 0x0000;
 pub const RAM_END: u16 = 0x0800;
@@ -7,17 +14,66 @@ pub const RAM_MIRRORS_END: u16 = 0x1FFF;
 
 pub const PG_ROOM_START: u16 = 0x8000;
 pub const PG_ROOM_END: u16 = 0xFFFF;
-pub const PG_ROOM_SIZE: u16 = PG_ROOM_END - PG_ROOM_START;
+pub const PG_ROOM_SIZE: u16 = PG_ROOM_END - PG_ROOM_START + 1;
 
 pub const PPU_REGISTERS: u16 = 0x2000;
 pub const PPU_REGISTERS_MIRRORS_END: u16 = 0x3FFF;
+// the 8 PPU registers repeat every 8 bytes until $3FFF
+const PPU_REGISTERS_MIRROR_MASK: u16 = 0b00100000_00000111;
+
+pub const APU_IO_REGISTERS: u16 = 0x4000;
+pub const APU_IO_REGISTERS_END: u16 = 0x4017;
+pub const OAM_DMA: u16 = 0x4014;
+pub const APU_STATUS: u16 = 0x4015;
+pub const JOYPAD_1: u16 = 0x4016;
+pub const JOYPAD_2: u16 = 0x4017;
+
+// $4015 only drives bit 5 from the bus, every other bit is an APU status flag
+const APU_STATUS_OPEN_BUS_MASK: u8 = 0b0010_0000;
+// the controller ports only drive bits 0-4, bits 5-7 keep whatever was on the bus
+const JOYPAD_OPEN_BUS_MASK: u8 = 0b1110_0000;
 
 #[cfg(test)]
 pub mod test;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Access {
+    Read { addr: u16, value: u8 },
+    Write { addr: u16, value: u8 },
+}
+
 pub struct Bus {
     cpu_ram: [u8; RAM_SIZE as usize],
     pg_room: [u8; PG_ROOM_SIZE as usize],
+    ppu: PPU,
+    /*
+      the last value driven on the CPU data bus.
+      when nothing answers a read the data lines keep their previous charge,so the CPU reads back this value
+    */
+    open_bus: u8,
+    // last values written to $4000-$4017,most of them are write only so this is the only way to inspect them
+    apu_io_registers: [u8; (APU_IO_REGISTERS_END - APU_IO_REGISTERS + 1) as usize],
+    // amount of CPU cycles executed since power on
+    cycles: usize,
+    /*
+      the CPU only ticks the bus after an instruction,but a PPU register access must see the PPU as it is
+      in the middle of the instruction.
+      every access takes 1 cycle,so before touching the PPU it is run for the accesses done so far
+    */
+    instruction_accesses: u8,
+    // CPU cycles of the current instruction the PPU already ran
+    ppu_synced_cycles: u8,
+    // on PAL the PPU runs 16 dots every 5 CPU cycles,this is the fraction of a dot left for the next cycles
+    ppu_dots_remainder: usize,
+    // page written to $4014,the copy starts once the instruction that wrote it is over
+    oam_dma_page: Option<u8>,
+    // when set every access is recorded so the debugger can check its watchpoints
+    watching: bool,
+    accesses: Vec<Access>,
+    // register writes and interrupts with the dot they happened in
+    events: EventLog,
+    // what the memories hold when the console is switched on
+    ram_pattern: RamPattern,
 }
 
 impl Bus {
@@ -25,48 +81,293 @@ impl Bus {
         Bus {
             cpu_ram: [0; RAM_SIZE as usize],
             pg_room: [0; PG_ROOM_SIZE as usize],
+            // without a cartridge the PPU gets the CHR-RAM a blank board would have
+            ppu: PPU::new(Chr::Ram(vec![0; CHR_RAM_SIZE]), Mirroring::Horizontal),
+            open_bus: 0,
+            apu_io_registers: [0; (APU_IO_REGISTERS_END - APU_IO_REGISTERS + 1) as usize],
+            cycles: 0,
+            instruction_accesses: 0,
+            ppu_synced_cycles: 0,
+            ppu_dots_remainder: 0,
+            oam_dma_page: None,
+            watching: false,
+            accesses: Vec::new(),
+            events: EventLog::new(),
+            ram_pattern: RamPattern::default(),
+        }
+    }
+
+    pub fn with_cartridge(cartridge: Cartridge) -> Result<Self, String> {
+        if cartridge.mapper != 0 {
+            return Err(format!("Mapper {} is not supported yet", cartridge.mapper));
+        }
+        let prg_rom = &cartridge.prg_rom;
+        if prg_rom.len() != PRG_ROM_PAGE_SIZE && prg_rom.len() != PG_ROOM_SIZE as usize {
+            return Err(format!("Invalid PRG ROM size {}", prg_rom.len()));
+        }
+
+        let mut bus = Bus::new();
+        // a 16KB PRG ROM shows up at both $8000 and $C000
+        for (i, byte) in bus.pg_room.iter_mut().enumerate() {
+            *byte = prg_rom[i % prg_rom.len()];
+        }
+        bus.ppu = PPU::new(cartridge.chr, cartridge.mirroring);
+        bus.set_region(cartridge.region);
+        Ok(bus)
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.ppu.set_region(region);
+        self.ppu_dots_remainder = 0;
+    }
+
+    pub fn set_watching(&mut self, watching: bool) {
+        self.watching = watching;
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watching
+    }
+
+    pub fn accesses(&self) -> &[Access] {
+        &self.accesses
+    }
+
+    pub fn clear_accesses(&mut self) {
+        self.accesses.clear();
+    }
+
+    pub fn events(&self) -> &EventLog {
+        &self.events
+    }
+
+    pub fn events_mut(&mut self) -> &mut EventLog {
+        &mut self.events
+    }
+
+    // logs an event that isn't a write,like the CPU taking an interrupt
+    pub fn record_event(&mut self, kind: EventKind) {
+        self.record_event_at(kind, None, self.ppu.position());
+    }
+
+    fn record_event_at(
+        &mut self,
+        kind: EventKind,
+        write: Option<(u16, u8)>,
+        (scanline, dot): (usize, usize),
+    ) {
+        self.events.record(Event {
+            scanline,
+            dot,
+            kind,
+            write,
+        });
+    }
+
+    // the reset button,the PPU clears some registers and the APU goes silent,the RAM is kept
+    pub fn reset(&mut self) {
+        self.ppu.reset();
+        self.apu_io_registers[(APU_STATUS - APU_IO_REGISTERS) as usize] = 0;
+        self.oam_dma_page = None;
+    }
+
+    // the RAM,CHR-RAM and OAM hold at the next power on
+    pub fn set_ram_pattern(&mut self, pattern: RamPattern) {
+        self.ram_pattern = pattern;
+    }
+
+    pub fn ram_pattern(&self) -> RamPattern {
+        self.ram_pattern
+    }
+
+    // switching the console off and on,everything but the cartridge starts over
+    pub fn power_on(&mut self) {
+        let mut filler = self.ram_pattern.filler();
+        filler.fill(&mut self.cpu_ram);
+        self.ppu.power_on(&mut filler);
+        self.open_bus = 0;
+        self.apu_io_registers = [0; (APU_IO_REGISTERS_END - APU_IO_REGISTERS + 1) as usize];
+        self.cycles = 0;
+        self.start_instruction();
+        self.ppu_dots_remainder = 0;
+        self.oam_dma_page = None;
+        self.accesses.clear();
+    }
+
+    pub fn tick(&mut self, cycles: u8) {
+        self.cycles += cycles as usize;
+        let remaining = cycles.saturating_sub(self.ppu_synced_cycles);
+        self.run_ppu(remaining);
+        self.start_instruction();
+    }
+
+    pub fn start_instruction(&mut self) {
+        self.instruction_accesses = 0;
+        self.ppu_synced_cycles = 0;
+    }
+
+    pub fn poll_nmi(&mut self) -> bool {
+        self.ppu.poll_nmi()
+    }
+
+    /*
+      copies the page written to $4014 into OAM,the CPU is halted while it happens.
+      it takes 1 cycle to halt the CPU,1 more if it has to wait for a read cycle,then 256 reads and 256 writes.
+      returns the amount of cycles the CPU was stalled
+    */
+    pub fn run_oam_dma(&mut self) -> usize {
+        let page = match self.oam_dma_page.take() {
+            Some(page) => page,
+            None => return 0,
+        };
+
+        let start = self.cycles;
+        let alignment = if self.cycles.is_multiple_of(2) { 1 } else { 2 };
+        self.tick(alignment);
+        for offset in 0..=0xFF {
+            let value = self.read_from_memory((page as u16) << 8 | offset);
+            self.tick(1);
+            self.ppu.write_register(OAMDATA, value);
+            self.tick(1);
+        }
+        self.cycles - start
+    }
+
+    fn sync_ppu(&mut self) {
+        if self.instruction_accesses > self.ppu_synced_cycles {
+            let cycles = self.instruction_accesses - self.ppu_synced_cycles;
+            self.run_ppu(cycles);
+            self.ppu_synced_cycles = self.instruction_accesses;
+        }
+    }
+
+    // the PPU runs 3 dots for every CPU cycle,3.2 on PAL
+    fn run_ppu(&mut self, cycles: u8) {
+        let (dots, per_cycles) = self.ppu.region().ppu_dots_per_cpu_cycle();
+        let total = cycles as usize * dots + self.ppu_dots_remainder;
+        self.ppu_dots_remainder = total % per_cycles;
+        self.ppu.tick(total / per_cycles);
+        if let Some(position) = self.ppu.take_sprite_zero_hit() {
+            self.record_event_at(EventKind::SpriteZeroHit, None, position);
+        }
+        self.events.set_frame(self.ppu.frame_count());
+    }
+
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    pub fn apu_io_registers(&self) -> &[u8] {
+        &self.apu_io_registers
+    }
+
+    pub fn ppu(&self) -> &PPU {
+        &self.ppu
+    }
+
+    pub fn ppu_mut(&mut self) -> &mut PPU {
+        &mut self.ppu
+    }
+
+    pub fn ppu_position(&self) -> (usize, usize) {
+        self.ppu.position()
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.ppu.frame_count()
+    }
+
+    // reads a value the same way the CPU would,but without driving the data bus
+    pub fn peek_memory(&self, addr: u16) -> u8 {
+        match addr {
+            RAM_START..=RAM_MIRRORS_END => self.cpu_ram[(addr & 0x7FF) as usize],
+            PPU_REGISTERS..=PPU_REGISTERS_MIRRORS_END => {
+                self.ppu.peek_register(addr & PPU_REGISTERS_MIRROR_MASK)
+            }
+            APU_STATUS => self.open_bus & APU_STATUS_OPEN_BUS_MASK,
+            JOYPAD_1 | JOYPAD_2 => self.open_bus & JOYPAD_OPEN_BUS_MASK,
+            PG_ROOM_START..=PG_ROOM_END => self.read_from_pg_rom(addr),
+            _ => self.open_bus,
+        }
+    }
+
+    // writes a value for debugging purposes,unlike the CPU it is allowed to patch the program rom
+    pub fn poke_memory(&mut self, addr: u16, data: u8) {
+        match addr {
+            PG_ROOM_START..=PG_ROOM_END => self.pg_room[(addr - PG_ROOM_START) as usize] = data,
+            _ => self.write_to_memory(addr, data),
         }
     }
 }
 
 impl Memory for Bus {
-    fn read_from_memory(&self, addr: u16) -> u8 {
-        match addr {
+    fn read_from_memory(&mut self, addr: u16) -> u8 {
+        let value = match addr {
             RAM_START..=RAM_MIRRORS_END => {
                 let mirror = addr & 0x7FF; // 0x7FF = 0b11111111111 we need to reduce the address to 11 bits;
-                return self.cpu_ram[mirror as usize];
+                self.cpu_ram[mirror as usize]
             }
             PPU_REGISTERS..=PPU_REGISTERS_MIRRORS_END => {
-                let _mirror_down_addr = addr & 0b00100000_00000111;
-                todo!("PPU is not supported yet")
+                self.sync_ppu();
+                let mirror_down_addr = addr & PPU_REGISTERS_MIRROR_MASK;
+                self.ppu.read_register(mirror_down_addr)
             }
-            PG_ROOM_START..=PG_ROOM_END => {
-                return self.read_from_pg_rom(addr);
+            APU_STATUS => {
+                // no APU channel is active yet,so all the status bits read as 0
+                self.open_bus & APU_STATUS_OPEN_BUS_MASK
             }
-            _ => {
-                println!("Read Address: {:X} ignored", addr);
-                return 0;
+            JOYPAD_1 | JOYPAD_2 => {
+                // no controller is plugged yet,so the serial data bits read as 0
+                self.open_bus & JOYPAD_OPEN_BUS_MASK
             }
+            PG_ROOM_START..=PG_ROOM_END => self.read_from_pg_rom(addr),
+            _ => self.open_bus,
+        };
+        // $4015 is inside the CPU,reading it doesn't drive the external data bus
+        if addr != APU_STATUS {
+            self.open_bus = value;
         }
+        self.instruction_accesses = self.instruction_accesses.saturating_add(1);
+        if self.watching {
+            self.accesses.push(Access::Read { addr, value });
+        }
+        value
     }
 
     fn write_to_memory(&mut self, addr: u16, data: u8) {
+        // the CPU always drives the bus on writes,even if nobody is listening
+        self.open_bus = data;
+        if self.watching {
+            self.accesses.push(Access::Write { addr, value: data });
+        }
+        if self.events.is_enabled() {
+            if let Some(kind) = EventKind::of_write(addr) {
+                // the position has to be the one in the middle of the instruction
+                self.sync_ppu();
+                self.record_event_at(kind, Some((addr, data)), self.ppu.position());
+            }
+        }
         match addr {
             RAM_START..=RAM_MIRRORS_END => {
                 let mirror = addr & 0x7FF; // 0x7FF = 0b11111111111 we need to reduce the address to 11 bits;
                 self.cpu_ram[mirror as usize] = data;
             }
             PPU_REGISTERS..=PPU_REGISTERS_MIRRORS_END => {
-                let _mirror_down_addr = addr & 0b00100000_00000111;
-                todo!("PPU is not supported yet")
-            }
-            PG_ROOM_START..=PG_ROOM_END => {
-                panic!("Write to ROM is not supported yet");
+                self.sync_ppu();
+                let mirror_down_addr = addr & PPU_REGISTERS_MIRROR_MASK;
+                self.ppu.write_register(mirror_down_addr, data);
             }
-            _ => {
-                println!("Write Address: {:X} ignored", addr);
+            APU_IO_REGISTERS..=APU_IO_REGISTERS_END => {
+                self.apu_io_registers[(addr - APU_IO_REGISTERS) as usize] = data;
+                if addr == OAM_DMA {
+                    self.oam_dma_page = Some(data);
+                }
             }
+            // NROM has no registers,the write only drives the data bus
+            PG_ROOM_START..=PG_ROOM_END => {}
+            _ => { /* unmapped,only the data bus sees the value */ }
         }
+        self.instruction_accesses = self.instruction_accesses.saturating_add(1);
     }
 }
 
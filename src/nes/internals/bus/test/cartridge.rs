@@ -0,0 +1,55 @@
+use crate::nes::internals::{
+    bus::Bus,
+    cartridge::{Cartridge, Chr, Mirroring},
+    memory::Memory,
+    region::Region,
+};
+
+fn cartridge(prg_rom: Vec<u8>, mapper: u8) -> Cartridge {
+    Cartridge {
+        prg_rom,
+        chr: Chr::Ram(vec![0; 0x2000]),
+        mapper,
+        mirroring: Mirroring::Vertical,
+        region: Region::Ntsc,
+    }
+}
+
+#[test]
+fn test_16kb_prg_rom_is_mirrored() {
+    let mut prg_rom = vec![0; 0x4000];
+    prg_rom[0x0010] = 0x42;
+    let mut bus = Bus::with_cartridge(cartridge(prg_rom, 0)).unwrap();
+
+    assert_eq!(bus.read_from_memory(0x8010), 0x42);
+    assert_eq!(bus.read_from_memory(0xC010), 0x42);
+    assert_eq!(bus.ppu().mirroring(), Mirroring::Vertical);
+}
+
+#[test]
+fn test_write_to_prg_rom_is_ignored() {
+    let mut prg_rom = vec![0; 0x4000];
+    prg_rom[0x0000] = 0x42;
+    let mut bus = Bus::with_cartridge(cartridge(prg_rom, 0)).unwrap();
+    bus.write_to_memory(0x8000, 0x99);
+
+    assert_eq!(bus.read_from_memory(0x8000), 0x42);
+    // the value written is still on the data bus
+    bus.write_to_memory(0x8000, 0x77);
+    assert_eq!(bus.read_from_memory(0x5000), 0x77);
+}
+
+#[test]
+fn test_unsupported_mapper() {
+    assert!(Bus::with_cartridge(cartridge(vec![0; 0x4000], 1)).is_err());
+    assert!(Bus::with_cartridge(cartridge(vec![0; 0x1000], 0)).is_err());
+}
+
+#[test]
+fn test_cartridge_region_sets_the_timing() {
+    let mut pal = cartridge(vec![0; 0x4000], 0);
+    pal.region = Region::Pal;
+    let bus = Bus::with_cartridge(pal).unwrap();
+
+    assert_eq!(bus.ppu().region(), Region::Pal);
+}
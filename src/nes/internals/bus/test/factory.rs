@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::nes::internals::bus::Bus;
+use crate::nes::internals::{bus::Bus, cpu::CPU};
 
 macro_rules! build_bus {
     ($($prop:ident[$addr:expr] = $value:expr ),*) => {
@@ -47,5 +47,13 @@ pub fn read_bus_cpu_ram(bus: &Bus, addr: u16) -> u8 {
     return bus.cpu_ram[addr as usize];
 }
 
+// a CPU without cartridge with the program loaded at $0600
+pub fn cpu_with_program(program: Vec<u8>) -> CPU {
+    let bus = Bus::new();
+    let mut cpu = CPU::new(bus);
+    cpu.load(program);
+    cpu
+}
+
 pub(crate) use build_bus;
 pub(crate) use check_prop;
@@ -1 +1,5 @@
+mod cartridge;
 pub mod factory;
+mod oam_dma;
+mod open_bus;
+mod reset;
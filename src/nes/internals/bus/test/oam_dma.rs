@@ -0,0 +1,87 @@
+use crate::nes::internals::{bus::Bus, cpu::CPU, memory::Memory};
+
+fn cpu_with_page_2() -> CPU {
+    let mut bus = Bus::new();
+    for offset in 0..=0xFF {
+        bus.write_to_memory(0x0200 + offset, offset as u8);
+    }
+    CPU::new(bus)
+}
+
+#[test]
+fn test_oam_dma_copies_the_page_to_oam() {
+    let mut cpu = cpu_with_page_2();
+    /*
+    LDA #$02
+    STA $4014
+    BRK
+     */
+    cpu.load(vec![0xa9, 0x02, 0x8d, 0x14, 0x40, 0x00]);
+    cpu.run();
+
+    cpu.write_to_memory(0x2003, 0x05);
+    assert_eq!(cpu.read_from_memory(0x2004), 0x05);
+    cpu.write_to_memory(0x2003, 0xFF);
+    assert_eq!(cpu.read_from_memory(0x2004), 0xFF);
+}
+
+#[test]
+fn test_oam_dma_starts_at_oamaddr() {
+    let mut cpu = cpu_with_page_2();
+    /*
+    LDA #$10
+    STA $2003
+    LDA #$02
+    STA $4014
+    BRK
+     */
+    cpu.load(vec![
+        0xa9, 0x10, 0x8d, 0x03, 0x20, 0xa9, 0x02, 0x8d, 0x14, 0x40, 0x00,
+    ]);
+    cpu.run();
+
+    cpu.write_to_memory(0x2003, 0x10);
+    assert_eq!(cpu.read_from_memory(0x2004), 0x00);
+    // the copy wraps around OAM
+    cpu.write_to_memory(0x2003, 0x0F);
+    assert_eq!(cpu.read_from_memory(0x2004), 0xFF);
+}
+
+#[test]
+fn test_oam_dma_stalls_513_cycles_on_even_cycle() {
+    let mut cpu = cpu_with_page_2();
+    /*
+    LDA #$02
+    STA $4014
+    BRK
+     */
+    cpu.load(vec![0xa9, 0x02, 0x8d, 0x14, 0x40, 0x00]);
+    cpu.run();
+
+    assert_eq!(cpu.bus().cycles(), 2 + 4 + 513);
+}
+
+#[test]
+fn test_oam_dma_stalls_514_cycles_on_odd_cycle() {
+    let mut cpu = cpu_with_page_2();
+    /*
+    LDA $00
+    LDA #$02
+    STA $4014
+    BRK
+     */
+    cpu.load(vec![0xa5, 0x00, 0xa9, 0x02, 0x8d, 0x14, 0x40, 0x00]);
+    cpu.run();
+
+    assert_eq!(cpu.bus().cycles(), 3 + 2 + 4 + 514);
+}
+
+#[test]
+fn test_no_dma_without_write() {
+    let mut bus = Bus::new();
+    assert_eq!(bus.run_oam_dma(), 0);
+
+    bus.write_to_memory(0x4014, 0x02);
+    assert_eq!(bus.run_oam_dma(), 513);
+    assert_eq!(bus.run_oam_dma(), 0);
+}
@@ -0,0 +1,76 @@
+use crate::nes::internals::{bus::Bus, cpu::CPU, memory::Memory};
+
+#[test]
+fn test_unmapped_read_returns_last_written_value() {
+    let mut bus = Bus::new();
+    bus.write_to_memory(0x5000, 0x42);
+
+    assert_eq!(bus.read_from_memory(0x5000), 0x42);
+    assert_eq!(bus.read_from_memory(0x6123), 0x42);
+}
+
+#[test]
+fn test_unmapped_read_returns_last_read_value() {
+    let mut bus = Bus::new();
+    bus.write_to_memory(0x0010, 0x99);
+    bus.write_to_memory(0x5000, 0x00);
+
+    assert_eq!(bus.read_from_memory(0x0010), 0x99);
+    assert_eq!(bus.read_from_memory(0x4020), 0x99);
+}
+
+#[test]
+fn test_joypad_read_keeps_upper_bits_from_bus() {
+    let mut bus = Bus::new();
+    bus.write_to_memory(0x0010, 0xFF);
+    bus.read_from_memory(0x0010);
+
+    assert_eq!(bus.read_from_memory(0x4016), 0xE0);
+    assert_eq!(bus.read_from_memory(0x4017), 0xE0);
+}
+
+#[test]
+fn test_apu_status_read_keeps_bit_5_from_bus() {
+    let mut bus = Bus::new();
+    bus.write_to_memory(0x4000, 0xFF);
+
+    assert_eq!(bus.read_from_memory(0x4015), 0x20);
+}
+
+#[test]
+fn test_apu_status_read_does_not_drive_the_bus() {
+    let mut bus = Bus::new();
+    bus.write_to_memory(0x4000, 0xFF);
+    bus.read_from_memory(0x4015);
+
+    assert_eq!(bus.read_from_memory(0x5000), 0xFF);
+}
+
+#[test]
+fn test_lda_joypad_reads_high_byte_of_operand() {
+    let bus = Bus::new();
+    let mut cpu = CPU::new(bus);
+    /*
+    LDA $4016
+    STA $10
+    BRK
+     */
+    cpu.load(vec![0xad, 0x16, 0x40, 0x85, 0x10, 0x00]);
+    cpu.run();
+
+    assert_eq!(cpu.read_from_memory(0x0010), 0x40);
+}
+
+#[test]
+fn test_ppu_registers_are_mirrored() {
+    let mut bus = Bus::new();
+    bus.write_to_memory(0x3456, 0x21);
+    bus.write_to_memory(0x2006, 0x08);
+    bus.write_to_memory(0x3FFF, 0x42);
+
+    assert_eq!(bus.ppu().vram_addr(), 0x2109);
+    bus.write_to_memory(0x2E06, 0x21);
+    bus.write_to_memory(0x2E06, 0x08);
+    bus.read_from_memory(0x2007);
+    assert_eq!(bus.read_from_memory(0x200F), 0x42);
+}
@@ -0,0 +1,39 @@
+use crate::nes::internals::{bus::Bus, memory::Memory, ram_pattern::RamPattern};
+
+#[test]
+fn test_reset_keeps_ram() {
+    let mut bus = Bus::new();
+    bus.write_to_memory(0x0010, 0x99);
+    bus.write_to_memory(0x4015, 0x0F);
+
+    bus.reset();
+    assert_eq!(bus.read_from_memory(0x0010), 0x99);
+    assert_eq!(bus.apu_io_registers[0x15], 0x00);
+}
+
+#[test]
+fn test_power_on_clears_ram() {
+    let mut bus = Bus::new();
+    bus.write_to_memory(0x0010, 0x99);
+    bus.tick(10);
+
+    bus.power_on();
+    assert_eq!(bus.read_from_memory(0x0010), 0x00);
+    assert_eq!(bus.cycles(), 0);
+}
+
+#[test]
+fn test_power_on_fills_ram_with_the_pattern() {
+    let mut bus = Bus::new();
+    bus.set_ram_pattern(RamPattern::Alternating);
+    bus.power_on();
+    assert_eq!(bus.read_from_memory(0x0003), 0x00);
+    assert_eq!(bus.read_from_memory(0x0004), 0xFF);
+
+    bus.set_ram_pattern(RamPattern::Random(3));
+    bus.power_on();
+    let ram = bus.cpu_ram;
+    bus.write_to_memory(0x0010, !ram[0x10]);
+    bus.power_on();
+    assert_eq!(bus.cpu_ram, ram);
+}
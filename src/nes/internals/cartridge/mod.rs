@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod test;
+
+use super::region::Region;
+
+const NES_TAG: [u8; 4] = [b'N', b'E', b'S', 0x1A];
+const HEADER_SIZE: usize = 16;
+const TRAINER_SIZE: usize = 512;
+pub const PRG_ROM_PAGE_SIZE: usize = 0x4000;
+pub const CHR_ROM_PAGE_SIZE: usize = 0x2000;
+// boards without CHR-ROM have 8KB of CHR-RAM instead
+pub const CHR_RAM_SIZE: usize = 0x2000;
+
+/*
+  how the 4 logical nametables at $2000,$2400,$2800 and $2C00 are wired to the 2KB of CIRAM.
+  the cartridge controls it,some mappers can even change it while the game runs
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mirroring {
+    // $2000 = $2400 and $2800 = $2C00,used by vertical scrolling games
+    Horizontal,
+    // $2000 = $2800 and $2400 = $2C00,used by horizontal scrolling games
+    Vertical,
+    // every nametable is the first 1KB of CIRAM.
+    // only mappers can select the single screen modes,the iNES header can't
+    #[allow(dead_code)]
+    SingleScreenLower,
+    // every nametable is the second 1KB of CIRAM
+    #[allow(dead_code)]
+    SingleScreenUpper,
+    // the cartridge brings another 2KB,so every nametable is unique
+    FourScreen,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Chr {
+    Rom(Vec<u8>),
+    Ram(Vec<u8>),
+}
+
+impl Chr {
+    pub fn read(&self, addr: u16) -> u8 {
+        match self {
+            Chr::Rom(data) | Chr::Ram(data) => data[addr as usize % data.len()],
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, data: u8) {
+        match self {
+            Chr::Ram(ram) => {
+                let len = ram.len();
+                ram[addr as usize % len] = data;
+            }
+            Chr::Rom(_) => { /* the PPU can't write to a rom,the value is lost */ }
+        }
+    }
+}
+
+pub struct Cartridge {
+    pub prg_rom: Vec<u8>,
+    pub chr: Chr,
+    pub mapper: u8,
+    pub mirroring: Mirroring,
+    // console the game was made for,taken from the header
+    pub region: Region,
+}
+
+impl Cartridge {
+    // parses an iNES file
+    pub fn new(raw: &[u8]) -> Result<Cartridge, String> {
+        if raw.len() < HEADER_SIZE || raw[0..4] != NES_TAG {
+            return Err(String::from("File is not in iNES file format"));
+        }
+
+        let mapper = (raw[7] & 0b1111_0000) | (raw[6] >> 4);
+
+        let four_screen = raw[6] & 0b1000 != 0;
+        let vertical_mirroring = raw[6] & 0b1 != 0;
+        let mirroring = match (four_screen, vertical_mirroring) {
+            (true, _) => Mirroring::FourScreen,
+            (false, true) => Mirroring::Vertical,
+            (false, false) => Mirroring::Horizontal,
+        };
+
+        let region = parse_region(raw);
+
+        let prg_rom_size = raw[4] as usize * PRG_ROM_PAGE_SIZE;
+        let chr_rom_size = raw[5] as usize * CHR_ROM_PAGE_SIZE;
+
+        let skip_trainer = raw[6] & 0b100 != 0;
+        let prg_rom_start = HEADER_SIZE + if skip_trainer { TRAINER_SIZE } else { 0 };
+        let chr_rom_start = prg_rom_start + prg_rom_size;
+
+        if raw.len() < chr_rom_start + chr_rom_size {
+            return Err(String::from("File is smaller than its header says"));
+        }
+
+        let chr = if chr_rom_size == 0 {
+            Chr::Ram(vec![0; CHR_RAM_SIZE])
+        } else {
+            Chr::Rom(raw[chr_rom_start..(chr_rom_start + chr_rom_size)].to_vec())
+        };
+
+        Ok(Cartridge {
+            prg_rom: raw[prg_rom_start..(prg_rom_start + prg_rom_size)].to_vec(),
+            chr,
+            mapper,
+            mirroring,
+            region,
+        })
+    }
+
+    // CRC32 of the PRG and CHR ROM without the header,the checksum ROM databases identify a dump with
+    pub fn crc32(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&self.prg_rom);
+        if let Chr::Rom(chr_rom) = &self.chr {
+            hasher.update(chr_rom);
+        }
+        hasher.finalize()
+    }
+}
+
+/*
+  NES 2.0 headers have the timing in bits 0-1 of byte 12: 0 = NTSC,1 = PAL,2 = both,3 = Dendy.
+  plain iNES only has the PAL flag in bit 0 of byte 9,and old dumps filled bytes 7-15 with garbage
+  like "DiskDude!",so it is only trusted when the padding at bytes 12-15 is clean
+*/
+fn parse_region(raw: &[u8]) -> Region {
+    let nes2 = raw[7] & 0b1100 == 0b1000;
+    if nes2 {
+        return match raw[12] & 0b11 {
+            1 => Region::Pal,
+            3 => Region::Dendy,
+            // games that run on both are played as NTSC
+            _ => Region::Ntsc,
+        };
+    }
+    if raw[12..HEADER_SIZE].iter().all(|byte| *byte == 0) && raw[9] & 0b1 != 0 {
+        Region::Pal
+    } else {
+        Region::Ntsc
+    }
+}
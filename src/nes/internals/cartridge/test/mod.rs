@@ -0,0 +1,107 @@
+use crate::nes::internals::{
+    cartridge::{Cartridge, Chr, Mirroring},
+    region::Region,
+};
+
+fn ines(flags_6: u8, flags_7: u8, prg_pages: u8, chr_pages: u8) -> Vec<u8> {
+    let mut raw = vec![
+        b'N', b'E', b'S', 0x1A, prg_pages, chr_pages, flags_6, flags_7,
+    ];
+    raw.resize(16, 0);
+    if flags_6 & 0b100 != 0 {
+        raw.resize(raw.len() + 512, 0xEE);
+    }
+    raw.resize(raw.len() + prg_pages as usize * 0x4000, 0x11);
+    raw.resize(raw.len() + chr_pages as usize * 0x2000, 0x22);
+    raw
+}
+
+#[test]
+fn test_parse_ines() {
+    let cartridge = Cartridge::new(&ines(0b0001_0001, 0b0100_0000, 2, 1)).unwrap();
+
+    assert_eq!(cartridge.mapper, 0x41);
+    assert_eq!(cartridge.mirroring, Mirroring::Vertical);
+    assert_eq!(cartridge.prg_rom.len(), 0x8000);
+    assert!(cartridge.prg_rom.iter().all(|byte| *byte == 0x11));
+    assert_eq!(cartridge.chr, Chr::Rom(vec![0x22; 0x2000]));
+}
+
+#[test]
+fn test_parse_ines_skips_trainer() {
+    let cartridge = Cartridge::new(&ines(0b0000_0100, 0, 1, 1)).unwrap();
+
+    assert_eq!(cartridge.mirroring, Mirroring::Horizontal);
+    assert_eq!(cartridge.prg_rom[0], 0x11);
+}
+
+#[test]
+fn test_parse_ines_four_screen() {
+    let cartridge = Cartridge::new(&ines(0b0000_1001, 0, 1, 1)).unwrap();
+    assert_eq!(cartridge.mirroring, Mirroring::FourScreen);
+}
+
+#[test]
+fn test_parse_ines_without_chr_rom_has_chr_ram() {
+    let cartridge = Cartridge::new(&ines(0, 0, 1, 0)).unwrap();
+    assert_eq!(cartridge.chr, Chr::Ram(vec![0; 0x2000]));
+}
+
+#[test]
+fn test_parse_invalid_file() {
+    assert!(Cartridge::new(b"NOT A ROM").is_err());
+
+    let mut raw = ines(0, 0, 2, 1);
+    raw.truncate(0x4000);
+    assert!(Cartridge::new(&raw).is_err());
+}
+
+#[test]
+fn test_chr_rom_ignores_writes() {
+    let mut chr = Chr::Rom(vec![0x22; 0x2000]);
+    chr.write(0x0010, 0x99);
+    assert_eq!(chr.read(0x0010), 0x22);
+
+    let mut chr = Chr::Ram(vec![0; 0x2000]);
+    chr.write(0x0010, 0x99);
+    assert_eq!(chr.read(0x0010), 0x99);
+}
+
+#[test]
+fn test_nes2_region() {
+    let mut raw = ines(0, 0b0000_1000, 1, 1);
+    assert_eq!(Cartridge::new(&raw).unwrap().region, Region::Ntsc);
+    raw[12] = 1;
+    assert_eq!(Cartridge::new(&raw).unwrap().region, Region::Pal);
+    raw[12] = 2;
+    assert_eq!(Cartridge::new(&raw).unwrap().region, Region::Ntsc);
+    raw[12] = 3;
+    assert_eq!(Cartridge::new(&raw).unwrap().region, Region::Dendy);
+}
+
+#[test]
+fn test_ines_pal_flag() {
+    let mut raw = ines(0, 0, 1, 1);
+    raw[9] = 1;
+    assert_eq!(Cartridge::new(&raw).unwrap().region, Region::Pal);
+
+    // the flag can't be trusted in headers with garbage in the padding
+    raw[12..16].copy_from_slice(b"ude!");
+    assert_eq!(Cartridge::new(&raw).unwrap().region, Region::Ntsc);
+}
+
+#[test]
+fn test_crc32_skips_header_and_trainer() {
+    let cartridge = Cartridge {
+        prg_rom: b"12345".to_vec(),
+        chr: Chr::Rom(b"6789".to_vec()),
+        mapper: 0,
+        mirroring: Mirroring::Horizontal,
+        region: Region::Ntsc,
+    };
+    assert_eq!(cartridge.crc32(), 0xCBF43926);
+
+    let plain = Cartridge::new(&ines(0, 0, 1, 1)).unwrap();
+    let trainer = Cartridge::new(&ines(0b0000_0100, 0, 1, 1)).unwrap();
+    assert_eq!(plain.crc32(), trainer.crc32());
+}
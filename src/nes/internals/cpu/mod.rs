@@ -7,8 +7,11 @@ use bitflags::bitflags;
 
 use super::{
     bus::Bus,
+    debugger::{Debugger, StopReason},
+    events::EventKind,
     memory::Memory,
-    opcodes::{AddressingMode, Opcodes},
+    opcodes::{AddressingMode, Opcodes, CYCLES},
+    trace::Tracer,
 };
 
 bitflags!(
@@ -28,6 +31,31 @@ bitflags!(
 const STACK_BASE: u16 = 0x0100;
 const STACK_SIZE: u8 = 0x00FF;
 
+// the reset sequence takes 7 cycles before the first instruction is fetched
+const RESET_CYCLES: u8 = 7;
+
+const NMI_VECTOR: u16 = 0xFFFA;
+pub const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE;
+// pushing the return address and the status plus reading the vector takes 7 cycles
+const INTERRUPT_CYCLES: u8 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interrupt {
+    NMI,
+    IRQ,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Registers {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub status: u8,
+    pub stack_pointer: u8,
+    pub program_counter: u16,
+}
+
 pub struct CPU {
     register_a: u8,
     register_x: u8,
@@ -41,10 +69,20 @@ pub struct CPU {
     program_counter: u16,
     stack_pointer: u8,
     bus: Bus,
+    // set when the last indexed address calculation crossed a page boundary
+    page_crossed: bool,
+    // checked between instructions,NMI has priority over IRQ
+    pending_interrupt: Option<Interrupt>,
+    tracer: Option<Tracer>,
+    debugger: Debugger,
+    // where the last execution breakpoint stopped
+    breakpoint_pc: Option<u16>,
+    // the snake and the test programs end with a BRK,a cartridge takes it as an interrupt
+    stop_on_brk: bool,
 }
 
 impl Memory for CPU {
-    fn read_from_memory(&self, addr: u16) -> u8 {
+    fn read_from_memory(&mut self, addr: u16) -> u8 {
         self.bus.read_from_memory(addr)
     }
 
@@ -63,9 +101,78 @@ impl CPU {
             program_counter: 0,
             stack_pointer: STACK_SIZE, //0x0100 - 0x01ff is used for the stack
             bus,
+            page_crossed: false,
+            pending_interrupt: None,
+            tracer: None,
+            debugger: Debugger::new(),
+            breakpoint_pc: None,
+            stop_on_brk: true,
         }
     }
 
+    pub fn registers(&self) -> Registers {
+        Registers {
+            a: self.register_a,
+            x: self.register_x,
+            y: self.register_y,
+            status: self.status.bits(),
+            stack_pointer: self.stack_pointer,
+            program_counter: self.program_counter,
+        }
+    }
+
+    pub fn set_registers(&mut self, registers: Registers) {
+        self.register_a = registers.a;
+        self.register_x = registers.x;
+        self.register_y = registers.y;
+        self.status = StatusFlags::from_bits_truncate(registers.status);
+        self.stack_pointer = registers.stack_pointer;
+        self.program_counter = registers.program_counter;
+    }
+
+    pub fn bus(&self) -> &Bus {
+        &self.bus
+    }
+
+    pub fn bus_mut(&mut self) -> &mut Bus {
+        &mut self.bus
+    }
+
+    pub fn peek_memory(&self, addr: u16) -> u8 {
+        self.bus.peek_memory(addr)
+    }
+
+    pub fn poke_memory(&mut self, addr: u16, data: u8) {
+        self.bus.poke_memory(addr, data);
+    }
+
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.tracer = tracer;
+    }
+
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    pub fn set_stop_on_brk(&mut self, stop: bool) {
+        self.stop_on_brk = stop;
+    }
+
+    pub fn request_interrupt(&mut self, interrupt: Interrupt) {
+        if self.pending_interrupt != Some(Interrupt::NMI) {
+            self.pending_interrupt = Some(interrupt);
+        }
+    }
+
+    #[cfg(test)]
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
     pub fn load(&mut self, program: Vec<u8>) {
         //loads the program into ram from 0x600 addr until the len of the program
         for i in 0..(program.len() as u16) {
@@ -77,379 +184,507 @@ impl CPU {
         self.program_counter = 0x0600;
     }
 
-    pub fn reset(&mut self) {
+    /*
+      switches the console off and on.
+      the registers start cleared and the reset sequence runs right away,so the stack pointer ends at $FD
+    */
+    pub fn power_on(&mut self) {
+        self.bus.power_on();
         self.register_a = 0;
         self.register_x = 0;
         self.register_y = 0;
         self.status = StatusFlags::from_bits_truncate(0b100100);
-        self.stack_pointer = STACK_SIZE;
+        self.stack_pointer = 0;
+        self.reset();
+    }
+
+    /*
+      the reset button,A X Y and the RAM are kept.
+      the CPU goes through an interrupt sequence with the writes to the stack turned into reads,
+      so the stack pointer goes down 3 and I is set
+    */
+    pub fn reset(&mut self) {
+        self.bus.reset();
+        self.stack_pointer = self.stack_pointer.wrapping_sub(3);
+        self.status.insert(StatusFlags::INTERRUPT_DISABLE);
+        self.pending_interrupt = None;
 
         //reads the addr of the beginning of the loaded program
-        self.program_counter = self.read_from_memory_le(0xFFFC);
+        self.program_counter = self.read_from_memory_le(RESET_VECTOR);
+        self.bus.tick(RESET_CYCLES);
     }
 
     pub fn load_and_run(&mut self, program: Vec<u8>) {
         self.load(program);
         self.reset();
+        // the program starts with the whole stack
+        self.stack_pointer = STACK_SIZE;
         self.program_counter = 0x0600;
         self.run();
     }
 
-    pub fn run(&mut self) {
-        self.run_with_callback(|_| {});
+    // only the test programs run until their BRK,the emulator always needs a way to interrupt it
+    #[allow(dead_code)]
+    pub fn run(&mut self) -> StopReason {
+        self.run_with_callback(|_| {})
+    }
+
+    #[allow(dead_code)]
+    pub fn run_with_callback<F: FnMut(&mut CPU)>(&mut self, mut callback: F) -> StopReason {
+        let reason = self.run_while(|cpu| {
+            callback(cpu);
+            true
+        });
+        reason.expect("Callback never interrupts the emulation")
     }
 
-    pub fn run_with_callback<F: FnMut(&mut CPU)>(&mut self, mut callback: F) {
+    // runs until a stop reason or until the callback returns false,in which case there is no reason to return
+    pub fn run_while<F: FnMut(&mut CPU) -> bool>(&mut self, mut callback: F) -> Option<StopReason> {
         loop {
-            let opcode_value = self.read_from_memory(self.program_counter);
-            self.program_counter += 1;
-            let opcode = Opcodes::from_u8(opcode_value).expect("Valid opcode");
-            match opcode {
-                Opcodes::ADC(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    self.add_to_register_a(value);
-                }
-                Opcodes::AND(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    let result = self.register_a & value;
-                    self.set_register_a(result);
-                }
-                Opcodes::ASL(addr_mode) => {
-                    /*
-                        "bit 7 is placed in the carry flag"
-                        the logic for that is really interesting,any number with the 7th bit set will overflow after shifting left
-                    */
-                    self.status.set(
-                        StatusFlags::CARRY,
-                        self.status.contains(StatusFlags::NEGATIVE),
-                    );
-
-                    if addr_mode == AddressingMode::ACCUMULATOR {
-                        let value = self.register_a;
-                        let result = value << 1;
-                        self.set_register_a(result);
-                    } else {
-                        let addr = self.get_memory_addr(&addr_mode);
-                        let value = self.get_value_from_memory(addr_mode);
-                        let result = value << 1;
-                        self.write_to_memory(addr, result);
-                        self.update_negative_flag(result);
-                        self.update_zero_flag(result);
-                    }
-                }
-                Opcodes::BCC => {
-                    self.branch(!self.status.contains(StatusFlags::CARRY));
-                }
-                Opcodes::BCS => {
-                    self.branch(self.status.contains(StatusFlags::CARRY));
+            // resuming from a breakpoint runs the instruction it stopped on,otherwise it would hit again
+            let resuming = self.breakpoint_pc.take() == Some(self.program_counter);
+            if !resuming {
+                if let Some((id, hit)) = self.debugger.check_instruction(self) {
+                    self.breakpoint_pc = Some(self.program_counter);
+                    return Some(StopReason::Breakpoint(id, hit));
                 }
-                Opcodes::BEQ => {
-                    self.branch(self.status.contains(StatusFlags::ZERO));
-                }
-                Opcodes::BIT(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    let result = self.register_a & value;
-                    self.update_negative_flag(value);
-                    self.update_overflow_flag(value);
+            }
 
-                    self.update_zero_flag(result);
-                }
-                Opcodes::BMI => {
-                    self.branch(self.status.contains(StatusFlags::NEGATIVE));
-                }
-                Opcodes::BNE => {
-                    self.branch(!self.status.contains(StatusFlags::ZERO));
-                }
-                Opcodes::BPL => {
-                    self.branch(!self.status.contains(StatusFlags::NEGATIVE));
-                }
-                Opcodes::BVC => {
-                    self.branch(!self.status.contains(StatusFlags::OVERFLOW));
-                }
-                Opcodes::BVS => {
-                    self.branch(self.status.contains(StatusFlags::OVERFLOW));
-                }
-                Opcodes::CLC => {
-                    self.status.remove(StatusFlags::CARRY);
-                }
-                Opcodes::CLD => {
-                    self.status.remove(StatusFlags::DECIMAL_MODE);
-                }
-                Opcodes::CLI => {
-                    self.status.remove(StatusFlags::INTERRUPT_DISABLE);
-                }
-                Opcodes::CLV => {
-                    self.status.remove(StatusFlags::OVERFLOW);
-                }
-                Opcodes::CMP(addr_mode) => self.compare(addr_mode, self.register_a),
-                Opcodes::CPX(addr_mode) => self.compare(addr_mode, self.register_x),
-                Opcodes::CPY(addr_mode) => self.compare(addr_mode, self.register_y),
-                Opcodes::DEC(addr_mode) => {
-                    let addr = self.get_memory_addr(&addr_mode);
-                    let value = self.get_value_from_memory(addr_mode);
-                    let result = value.wrapping_sub(1);
-                    self.write_to_memory(addr, result);
-                    self.update_negative_flag(result);
-                    self.update_zero_flag(result);
-                }
-                Opcodes::DEX => {
-                    let value = self.register_x;
-                    let result = value.wrapping_sub(1);
-                    self.set_register_x(result);
-                }
-                Opcodes::DEY => {
-                    let value = self.register_y;
-                    let result = value.wrapping_sub(1);
-                    self.set_register_y(result);
+            if let Some(reason) = self.step() {
+                return Some(reason);
+            }
+            if !callback(self) {
+                return None;
+            }
+        }
+    }
+
+    // executes a single instruction (and the interrupt before it,if any) ignoring execution breakpoints
+    pub fn step(&mut self) -> Option<StopReason> {
+        self.breakpoint_pc = None;
+        if let Some(interrupt) = self.pending_interrupt {
+            if self.interrupt(interrupt) {
+                self.pending_interrupt = None;
+                if let Some((id, hit)) = self.debugger.check_interrupt(interrupt) {
+                    return Some(StopReason::Breakpoint(id, hit));
                 }
-                Opcodes::EOR(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    let result = self.register_a ^ value;
+            }
+        }
+
+        if let Some(mut tracer) = self.tracer.take() {
+            tracer.trace(self);
+            self.tracer = Some(tracer);
+        }
+
+        self.bus.set_watching(self.debugger.has_watchpoints());
+        self.bus.start_instruction();
+        self.page_crossed = false;
+        let opcode_value = self.read_from_memory(self.program_counter);
+        // the pc stays on the opcode,so the monitor and gdb show where it is
+        let Ok(opcode) = Opcodes::from_u8(opcode_value) else {
+            return Some(StopReason::InvalidOpcode {
+                addr: self.program_counter,
+                opcode: opcode_value,
+            });
+        };
+        self.program_counter += 1;
+        let mut cycles = CYCLES[opcode_value as usize];
+        let page_cross_penalty = opcode.has_page_cross_penalty();
+        match opcode {
+            Opcodes::ADC(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                self.add_to_register_a(value);
+            }
+            Opcodes::AND(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                let result = self.register_a & value;
+                self.set_register_a(result);
+            }
+            Opcodes::ASL(addr_mode) => {
+                /*
+                    "bit 7 is placed in the carry flag"
+                    the logic for that is really interesting,any number with the 7th bit set will overflow after shifting left
+                */
+                self.status.set(
+                    StatusFlags::CARRY,
+                    self.status.contains(StatusFlags::NEGATIVE),
+                );
+
+                if addr_mode == AddressingMode::ACCUMULATOR {
+                    let value = self.register_a;
+                    let result = value << 1;
                     self.set_register_a(result);
-                }
-                Opcodes::INC(addr_mode) => {
-                    let addr = self.get_memory_addr(&addr_mode);
-                    let value = self.get_value_from_memory(addr_mode);
-                    let result = value.wrapping_add(1);
+                } else {
+                    let addr = self.get_operand_addr(addr_mode);
+                    let value = self.read_from_memory(addr);
+                    let result = value << 1;
                     self.write_to_memory(addr, result);
                     self.update_negative_flag(result);
                     self.update_zero_flag(result);
                 }
-                Opcodes::INX => {
-                    let value = self.register_x;
-                    let result = value.wrapping_add(1);
-                    self.set_register_x(result);
-                }
-                Opcodes::INY => {
-                    let result = self.register_y.wrapping_add(1);
-                    self.register_y = result;
-                    self.update_negative_flag(result);
-                    self.update_zero_flag(result);
-                }
-                Opcodes::JMP(addr_mode) => {
-                    if addr_mode == AddressingMode::ABSOLUTE {
-                        let addr = self.get_memory_addr(&addr_mode);
-                        self.program_counter = addr;
-                    } else {
-                        let mem_address = self.read_from_memory_le(self.program_counter);
-                        //6502 bug mode with with page boundary:
-                        //  if address $3000 contains $40, $30FF contains $80, and $3100 contains $50,
-                        // the result of JMP ($30FF) will be a transfer of control to $4080 rather than $5080 as you intended
-                        // i.e. the 6502 took the low byte of the address from $30FF and the high byte from $3000
-
-                        let indirect_ref;
-
-                        // any address ending in 0xFF will be affected by the bug
-                        if mem_address & 0x00FF == 0x00FF {
-                            /*
-                                the bug is that the 6502 takes the low byte from the correct address.
-                                However, it takes the high byte from 0x**00 (where * is any value) instead of 0x**ff + 1
-                            */
-                            let lo = self.read_from_memory(mem_address);
-                            let hi = self.read_from_memory(mem_address & 0xFF00);
-                            indirect_ref = (hi as u16) << 8 | (lo as u16);
-                        } else {
-                            indirect_ref = self.read_from_memory_le(mem_address);
-                        };
-
-                        self.program_counter = indirect_ref;
-                    }
-                }
-                Opcodes::JSR(addr_mode) => {
-                    let mode_increment = addr_mode.get_counter_increment();
+            }
+            Opcodes::BCC => {
+                self.branch(!self.status.contains(StatusFlags::CARRY));
+            }
+            Opcodes::BCS => {
+                self.branch(self.status.contains(StatusFlags::CARRY));
+            }
+            Opcodes::BEQ => {
+                self.branch(self.status.contains(StatusFlags::ZERO));
+            }
+            Opcodes::BIT(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                let result = self.register_a & value;
+                self.update_negative_flag(value);
+                self.update_overflow_flag(value);
+
+                self.update_zero_flag(result);
+            }
+            Opcodes::BMI => {
+                self.branch(self.status.contains(StatusFlags::NEGATIVE));
+            }
+            Opcodes::BNE => {
+                self.branch(!self.status.contains(StatusFlags::ZERO));
+            }
+            Opcodes::BPL => {
+                self.branch(!self.status.contains(StatusFlags::NEGATIVE));
+            }
+            Opcodes::BVC => {
+                self.branch(!self.status.contains(StatusFlags::OVERFLOW));
+            }
+            Opcodes::BVS => {
+                self.branch(self.status.contains(StatusFlags::OVERFLOW));
+            }
+            Opcodes::CLC => {
+                self.status.remove(StatusFlags::CARRY);
+            }
+            Opcodes::CLD => {
+                self.status.remove(StatusFlags::DECIMAL_MODE);
+            }
+            Opcodes::CLI => {
+                self.status.remove(StatusFlags::INTERRUPT_DISABLE);
+            }
+            Opcodes::CLV => {
+                self.status.remove(StatusFlags::OVERFLOW);
+            }
+            Opcodes::CMP(addr_mode) => self.compare(addr_mode, self.register_a),
+            Opcodes::CPX(addr_mode) => self.compare(addr_mode, self.register_x),
+            Opcodes::CPY(addr_mode) => self.compare(addr_mode, self.register_y),
+            Opcodes::DEC(addr_mode) => {
+                let addr = self.get_operand_addr(addr_mode);
+                let value = self.read_from_memory(addr);
+                let result = value.wrapping_sub(1);
+                self.write_to_memory(addr, result);
+                self.update_negative_flag(result);
+                self.update_zero_flag(result);
+            }
+            Opcodes::DEX => {
+                let value = self.register_x;
+                let result = value.wrapping_sub(1);
+                self.set_register_x(result);
+            }
+            Opcodes::DEY => {
+                let value = self.register_y;
+                let result = value.wrapping_sub(1);
+                self.set_register_y(result);
+            }
+            Opcodes::EOR(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                let result = self.register_a ^ value;
+                self.set_register_a(result);
+            }
+            Opcodes::INC(addr_mode) => {
+                let addr = self.get_operand_addr(addr_mode);
+                let value = self.read_from_memory(addr);
+                let result = value.wrapping_add(1);
+                self.write_to_memory(addr, result);
+                self.update_negative_flag(result);
+                self.update_zero_flag(result);
+            }
+            Opcodes::INX => {
+                let value = self.register_x;
+                let result = value.wrapping_add(1);
+                self.set_register_x(result);
+            }
+            Opcodes::INY => {
+                let result = self.register_y.wrapping_add(1);
+                self.register_y = result;
+                self.update_negative_flag(result);
+                self.update_zero_flag(result);
+            }
+            Opcodes::JMP(addr_mode) => {
+                if addr_mode == AddressingMode::ABSOLUTE {
                     let addr = self.get_memory_addr(&addr_mode);
-                    let return_addr = self.program_counter + mode_increment - 1;
-                    self.stack_push_le(return_addr);
                     self.program_counter = addr;
-                }
-                Opcodes::LDA(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    self.set_register_a(value);
-                }
-                Opcodes::LDX(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    self.set_register_x(value);
-                }
-                Opcodes::LDY(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    self.set_register_y(value);
-                }
-                Opcodes::LSR(addr_mode) => {
-                    let value: u8;
-                    if addr_mode == AddressingMode::ACCUMULATOR {
-                        value = self.register_a;
-                        let result = value >> 1;
-                        self.set_register_a(result);
+                } else {
+                    let mem_address = self.read_from_memory_le(self.program_counter);
+                    //6502 bug mode with with page boundary:
+                    //  if address $3000 contains $40, $30FF contains $80, and $3100 contains $50,
+                    // the result of JMP ($30FF) will be a transfer of control to $4080 rather than $5080 as you intended
+                    // i.e. the 6502 took the low byte of the address from $30FF and the high byte from $3000
+
+                    let indirect_ref;
+
+                    // any address ending in 0xFF will be affected by the bug
+                    if mem_address & 0x00FF == 0x00FF {
+                        /*
+                            the bug is that the 6502 takes the low byte from the correct address.
+                            However, it takes the high byte from 0x**00 (where * is any value) instead of 0x**ff + 1
+                        */
+                        let lo = self.read_from_memory(mem_address);
+                        let hi = self.read_from_memory(mem_address & 0xFF00);
+                        indirect_ref = (hi as u16) << 8 | (lo as u16);
                     } else {
-                        let addr = self.get_memory_addr(&addr_mode);
-                        value = self.get_value_from_memory(addr_mode);
-                        let result = value >> 1;
-                        self.write_to_memory(addr, result);
-                        self.update_negative_flag(result);
-                        self.update_zero_flag(result);
-                    }
-                    if value & 0x01 == 0x01 {
-                        self.status.insert(StatusFlags::CARRY);
-                    } else {
-                        self.status.remove(StatusFlags::CARRY);
-                    }
+                        indirect_ref = self.read_from_memory_le(mem_address);
+                    };
+
+                    self.program_counter = indirect_ref;
                 }
-                Opcodes::NOP => {}
-                Opcodes::ORA(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    let result = self.register_a | value;
+            }
+            Opcodes::JSR(addr_mode) => {
+                let mode_increment = addr_mode.get_counter_increment();
+                let addr = self.get_memory_addr(&addr_mode);
+                let return_addr = self.program_counter + mode_increment - 1;
+                self.stack_push_le(return_addr);
+                self.program_counter = addr;
+            }
+            Opcodes::LDA(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                self.set_register_a(value);
+            }
+            Opcodes::LDX(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                self.set_register_x(value);
+            }
+            Opcodes::LDY(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                self.set_register_y(value);
+            }
+            Opcodes::LSR(addr_mode) => {
+                let value: u8;
+                if addr_mode == AddressingMode::ACCUMULATOR {
+                    value = self.register_a;
+                    let result = value >> 1;
                     self.set_register_a(result);
-                }
-                Opcodes::PHA => {
-                    let value = self.register_a;
-                    self.stack_push(value);
-                }
-                Opcodes::PHP => {
-                    //http://wiki.nesdev.com/w/index.php/CPU_status_flag_behavior
-                    let mut flags = self.status.clone();
-                    flags.insert(StatusFlags::BREAK);
-                    flags.insert(StatusFlags::BREAK2);
-                    let value = flags.bits();
-                    self.stack_push(value);
-                }
-                Opcodes::PLA => {
-                    let value = self.stack_pop();
-                    self.set_register_a(value);
-                }
-                Opcodes::PLP => {
-                    let value = self.stack_pop();
-                    self.status = StatusFlags::from_bits_truncate(value);
-                    self.status.remove(StatusFlags::BREAK);
-                    self.status.insert(StatusFlags::BREAK2);
-                }
-                Opcodes::ROL(addr_mode) => {
-                    let value: u8;
-                    // true turns into 0x1, false turns into 0x0
-                    let carry_bit = self.status.contains(StatusFlags::CARRY) as u8;
-                    if addr_mode == AddressingMode::ACCUMULATOR {
-                        value = self.register_a;
-                        // a shift left always leaves a 0 on the 0 bit, so we can just OR it with the carry bit
-                        let result = (value << 1) | carry_bit;
-                        self.set_register_a(result);
-                    } else {
-                        let addr = self.get_memory_addr(&addr_mode);
-                        value = self.get_value_from_memory(addr_mode);
-                        let result = (value << 1) | carry_bit;
-                        self.write_to_memory(addr, result);
-                        self.update_negative_flag(result);
-                        self.update_zero_flag(result);
-                    }
-                    if value & 0x80 == 0x80 {
-                        self.status.insert(StatusFlags::CARRY);
-                    } else {
-                        self.status.remove(StatusFlags::CARRY);
-                    }
-                }
-                Opcodes::ROR(addr_mode) => {
-                    let value: u8;
-                    // true turns into 0x1, false turns into 0x0,so we shift it to the 7 bit to get the carry bit on the right spot
-                    let carry_bit = (self.status.contains(StatusFlags::CARRY) as u8) << 7;
-                    if addr_mode == AddressingMode::ACCUMULATOR {
-                        value = self.register_a;
-                        // a shift right always leaves a 0 on the 7 bit, so we can just OR it with the carry bit
-                        let result = (value >> 1) | carry_bit;
-                        self.set_register_a(result);
-                    } else {
-                        let addr = self.get_memory_addr(&addr_mode);
-                        value = self.get_value_from_memory(addr_mode);
-                        let result = (value >> 1) | carry_bit;
-                        self.write_to_memory(addr, result);
-                        self.update_negative_flag(result);
-                        self.update_zero_flag(result);
-                    }
-                    if value & 0x01 == 0x01 {
-                        self.status.insert(StatusFlags::CARRY);
-                    } else {
-                        self.status.remove(StatusFlags::CARRY);
-                    }
-                }
-                Opcodes::RTI => {
-                    let status = self.stack_pop();
-                    let addr = self.stack_pop_le();
-                    self.status = StatusFlags::from_bits_truncate(status);
-                    self.status.remove(StatusFlags::BREAK);
-                    self.status.insert(StatusFlags::BREAK2);
-                    self.program_counter = addr;
-                }
-                Opcodes::RTS => {
-                    let addr = self.stack_pop_le();
-                    self.program_counter = addr + 1;
-                }
-                Opcodes::SBC(addr_mode) => {
-                    let value = self.get_value_from_memory(addr_mode);
-                    let sub_value = (value as i8).wrapping_neg().wrapping_sub(1);
-                    self.add_to_register_a(sub_value as u8);
-                }
-                Opcodes::SEC => {
-                    self.status.insert(StatusFlags::CARRY);
-                }
-                Opcodes::SED => {
-                    self.status.insert(StatusFlags::DECIMAL_MODE);
-                }
-                Opcodes::SEI => {
-                    self.status.insert(StatusFlags::INTERRUPT_DISABLE);
-                }
-                Opcodes::STA(addr_mode) => {
-                    let mode_increment = addr_mode.get_counter_increment();
-                    let addr = self.get_memory_addr(addr_mode);
-                    self.write_to_memory(addr, self.register_a);
-                    self.program_counter += mode_increment;
-                }
-                Opcodes::STX(addr_mode) => {
-                    let mode_increment = addr_mode.get_counter_increment();
-                    let addr = self.get_memory_addr(addr_mode);
-                    self.write_to_memory(addr, self.register_x);
-                    self.program_counter += mode_increment;
-                }
-                Opcodes::STY(addr_mode) => {
-                    let mode_increment = addr_mode.get_counter_increment();
-                    let addr = self.get_memory_addr(addr_mode);
-                    self.write_to_memory(addr, self.register_y);
-                    self.program_counter += mode_increment;
-                }
-                Opcodes::TAX => {
-                    let result = self.register_a;
-                    self.register_x = result;
-                    self.update_negative_flag(result);
-                    self.update_zero_flag(result);
-                }
-                Opcodes::TAY => {
-                    let result = self.register_a;
-                    self.register_y = result;
+                } else {
+                    let addr = self.get_operand_addr(addr_mode);
+                    value = self.read_from_memory(addr);
+                    let result = value >> 1;
+                    self.write_to_memory(addr, result);
                     self.update_negative_flag(result);
                     self.update_zero_flag(result);
                 }
-                Opcodes::TSX => {
-                    let result = self.stack_pointer;
-                    self.set_register_x(result);
+                if value & 0x01 == 0x01 {
+                    self.status.insert(StatusFlags::CARRY);
+                } else {
+                    self.status.remove(StatusFlags::CARRY);
                 }
-                Opcodes::TXA => {
-                    let result = self.register_x;
-                    self.register_a = result;
+            }
+            Opcodes::NOP => {}
+            Opcodes::ORA(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                let result = self.register_a | value;
+                self.set_register_a(result);
+            }
+            Opcodes::PHA => {
+                let value = self.register_a;
+                self.stack_push(value);
+            }
+            Opcodes::PHP => {
+                //http://wiki.nesdev.com/w/index.php/CPU_status_flag_behavior
+                let mut flags = self.status.clone();
+                flags.insert(StatusFlags::BREAK);
+                flags.insert(StatusFlags::BREAK2);
+                let value = flags.bits();
+                self.stack_push(value);
+            }
+            Opcodes::PLA => {
+                let value = self.stack_pop();
+                self.set_register_a(value);
+            }
+            Opcodes::PLP => {
+                let value = self.stack_pop();
+                self.status = StatusFlags::from_bits_truncate(value);
+                self.status.remove(StatusFlags::BREAK);
+                self.status.insert(StatusFlags::BREAK2);
+            }
+            Opcodes::ROL(addr_mode) => {
+                let value: u8;
+                // true turns into 0x1, false turns into 0x0
+                let carry_bit = self.status.contains(StatusFlags::CARRY) as u8;
+                if addr_mode == AddressingMode::ACCUMULATOR {
+                    value = self.register_a;
+                    // a shift left always leaves a 0 on the 0 bit, so we can just OR it with the carry bit
+                    let result = (value << 1) | carry_bit;
+                    self.set_register_a(result);
+                } else {
+                    let addr = self.get_operand_addr(addr_mode);
+                    value = self.read_from_memory(addr);
+                    let result = (value << 1) | carry_bit;
+                    self.write_to_memory(addr, result);
                     self.update_negative_flag(result);
                     self.update_zero_flag(result);
                 }
-                Opcodes::TXS => {
-                    let result = self.register_x;
-                    self.stack_pointer = result;
+                if value & 0x80 == 0x80 {
+                    self.status.insert(StatusFlags::CARRY);
+                } else {
+                    self.status.remove(StatusFlags::CARRY);
                 }
-                Opcodes::TYA => {
-                    let result = self.register_y;
-                    self.register_a = result;
+            }
+            Opcodes::ROR(addr_mode) => {
+                let value: u8;
+                // true turns into 0x1, false turns into 0x0,so we shift it to the 7 bit to get the carry bit on the right spot
+                let carry_bit = (self.status.contains(StatusFlags::CARRY) as u8) << 7;
+                if addr_mode == AddressingMode::ACCUMULATOR {
+                    value = self.register_a;
+                    // a shift right always leaves a 0 on the 7 bit, so we can just OR it with the carry bit
+                    let result = (value >> 1) | carry_bit;
+                    self.set_register_a(result);
+                } else {
+                    let addr = self.get_operand_addr(addr_mode);
+                    value = self.read_from_memory(addr);
+                    let result = (value >> 1) | carry_bit;
+                    self.write_to_memory(addr, result);
                     self.update_negative_flag(result);
                     self.update_zero_flag(result);
                 }
-                Opcodes::BRK => {
-                    break;
+                if value & 0x01 == 0x01 {
+                    self.status.insert(StatusFlags::CARRY);
+                } else {
+                    self.status.remove(StatusFlags::CARRY);
                 }
             }
-            callback(self);
+            Opcodes::RTI => {
+                let status = self.stack_pop();
+                let addr = self.stack_pop_le();
+                self.status = StatusFlags::from_bits_truncate(status);
+                self.status.remove(StatusFlags::BREAK);
+                self.status.insert(StatusFlags::BREAK2);
+                self.program_counter = addr;
+            }
+            Opcodes::RTS => {
+                let addr = self.stack_pop_le();
+                self.program_counter = addr + 1;
+            }
+            Opcodes::SBC(addr_mode) => {
+                let value = self.get_value_from_memory(addr_mode);
+                let sub_value = (value as i8).wrapping_neg().wrapping_sub(1);
+                self.add_to_register_a(sub_value as u8);
+            }
+            Opcodes::SEC => {
+                self.status.insert(StatusFlags::CARRY);
+            }
+            Opcodes::SED => {
+                self.status.insert(StatusFlags::DECIMAL_MODE);
+            }
+            Opcodes::SEI => {
+                self.status.insert(StatusFlags::INTERRUPT_DISABLE);
+            }
+            Opcodes::STA(addr_mode) => {
+                let mode_increment = addr_mode.get_counter_increment();
+                let addr = self.get_memory_addr(addr_mode);
+                self.write_to_memory(addr, self.register_a);
+                self.program_counter += mode_increment;
+            }
+            Opcodes::STX(addr_mode) => {
+                let mode_increment = addr_mode.get_counter_increment();
+                let addr = self.get_memory_addr(addr_mode);
+                self.write_to_memory(addr, self.register_x);
+                self.program_counter += mode_increment;
+            }
+            Opcodes::STY(addr_mode) => {
+                let mode_increment = addr_mode.get_counter_increment();
+                let addr = self.get_memory_addr(addr_mode);
+                self.write_to_memory(addr, self.register_y);
+                self.program_counter += mode_increment;
+            }
+            Opcodes::TAX => {
+                let result = self.register_a;
+                self.register_x = result;
+                self.update_negative_flag(result);
+                self.update_zero_flag(result);
+            }
+            Opcodes::TAY => {
+                let result = self.register_a;
+                self.register_y = result;
+                self.update_negative_flag(result);
+                self.update_zero_flag(result);
+            }
+            Opcodes::TSX => {
+                let result = self.stack_pointer;
+                self.set_register_x(result);
+            }
+            Opcodes::TXA => {
+                let result = self.register_x;
+                self.register_a = result;
+                self.update_negative_flag(result);
+                self.update_zero_flag(result);
+            }
+            Opcodes::TXS => {
+                let result = self.register_x;
+                self.stack_pointer = result;
+            }
+            Opcodes::TYA => {
+                let result = self.register_y;
+                self.register_a = result;
+                self.update_negative_flag(result);
+                self.update_zero_flag(result);
+            }
+            Opcodes::BRK => {
+                if self.stop_on_brk {
+                    return Some(StopReason::Brk);
+                }
+                // the byte after BRK is padding,the return address skips it
+                self.stack_push_le(self.program_counter.wrapping_add(1));
+                let mut flags = self.status.clone();
+                flags.insert(StatusFlags::BREAK | StatusFlags::BREAK2);
+                self.stack_push(flags.bits());
+                self.status.insert(StatusFlags::INTERRUPT_DISABLE);
+                self.program_counter = self.read_from_memory_le(IRQ_VECTOR);
+            }
+        }
+        if page_cross_penalty && self.page_crossed {
+            cycles += 1;
+        }
+        self.bus.tick(cycles);
+        self.bus.run_oam_dma();
+        if self.bus.poll_nmi() {
+            self.request_interrupt(Interrupt::NMI);
+        }
+
+        if self.bus.is_watching() {
+            let hit = self.debugger.check_accesses(self.bus.accesses());
+            self.bus.clear_accesses();
+            if let Some((id, hit)) = hit {
+                return Some(StopReason::Breakpoint(id, hit));
+            }
         }
+        None
+    }
+
+    // returns false when the interrupt is masked by the I flag
+    fn interrupt(&mut self, interrupt: Interrupt) -> bool {
+        let vector = match interrupt {
+            Interrupt::NMI => NMI_VECTOR,
+            Interrupt::IRQ if self.status.contains(StatusFlags::INTERRUPT_DISABLE) => return false,
+            Interrupt::IRQ => IRQ_VECTOR,
+        };
+        self.bus.record_event(match interrupt {
+            Interrupt::NMI => EventKind::Nmi,
+            Interrupt::IRQ => EventKind::Irq,
+        });
+
+        self.stack_push_le(self.program_counter);
+        //http://wiki.nesdev.com/w/index.php/CPU_status_flag_behavior
+        let mut flags = self.status.clone();
+        flags.remove(StatusFlags::BREAK);
+        flags.insert(StatusFlags::BREAK2);
+        self.stack_push(flags.bits());
+        self.status.insert(StatusFlags::INTERRUPT_DISABLE);
+
+        self.bus.tick(INTERRUPT_CYCLES);
+        self.program_counter = self.read_from_memory_le(vector);
+        true
     }
 
     fn set_register_a(&mut self, value: u8) {
@@ -470,7 +705,7 @@ impl CPU {
         self.update_zero_flag(value);
     }
 
-    fn get_memory_addr<T: Borrow<AddressingMode>>(&self, mode: T) -> u16 {
+    fn get_memory_addr<T: Borrow<AddressingMode>>(&mut self, mode: T) -> u16 {
         match mode.borrow() {
             AddressingMode::IMMEDIATE => self.program_counter,
 
@@ -492,11 +727,13 @@ impl CPU {
             AddressingMode::ABSOLUTE_X => {
                 let base = self.read_from_memory_le(self.program_counter);
                 let addr = base.wrapping_add(self.register_x as u16);
+                self.page_crossed = Self::crosses_page(base, addr);
                 return addr;
             }
             AddressingMode::ABSOLUTE_Y => {
                 let base = self.read_from_memory_le(self.program_counter);
                 let addr = base.wrapping_add(self.register_y as u16);
+                self.page_crossed = Self::crosses_page(base, addr);
                 return addr;
             }
 
@@ -517,6 +754,7 @@ impl CPU {
                 let hi = self.read_from_memory(base.wrapping_add(1) as u16);
                 let deref_base = (hi as u16) << 8 | (lo as u16);
                 let addr = deref_base.wrapping_add(self.register_y as u16);
+                self.page_crossed = Self::crosses_page(deref_base, addr);
                 return addr;
             }
             _ => panic!("Invalid addressing mode"),
@@ -524,21 +762,34 @@ impl CPU {
     }
 
     fn get_value_from_memory(&mut self, addr_mode: AddressingMode) -> u8 {
+        let addr = self.get_operand_addr(addr_mode);
+        self.read_from_memory(addr)
+    }
+
+    // resolves the address of the operand and moves the pc past it,the pointers are only read once
+    fn get_operand_addr(&mut self, addr_mode: AddressingMode) -> u16 {
         let mode_increment = addr_mode.get_counter_increment();
         let addr = self.get_memory_addr(addr_mode);
-        let param = self.read_from_memory(addr);
         self.program_counter += mode_increment;
-        return param;
+        addr
     }
 
     fn branch(&mut self, condition: bool) {
         let offset = self.read_from_memory(self.program_counter) as i8;
         self.program_counter = self.program_counter.wrapping_add(1);
         if condition {
-            self.program_counter = self.program_counter.wrapping_add(offset as u16);
+            let target = self.program_counter.wrapping_add(offset as u16);
+            // a taken branch costs 1 extra cycle,and another one if it lands on a different page
+            let extra_cycles = 1 + Self::crosses_page(self.program_counter, target) as u8;
+            self.bus.tick(extra_cycles);
+            self.program_counter = target;
         }
     }
 
+    fn crosses_page(base: u16, addr: u16) -> bool {
+        base & 0xFF00 != addr & 0xFF00
+    }
+
     fn compare(&mut self, mode: AddressingMode, value: u8) {
         let mem_value = self.get_value_from_memory(mode);
         let result = value.wrapping_sub(mem_value);
@@ -602,7 +853,7 @@ impl CPU {
         let (pointer, overflowed) = self.stack_pointer.overflowing_sub(1);
         self.stack_pointer = pointer;
         if overflowed {
-            println!("Warning: Stack overflow on next push");
+            eprintln!("Warning: Stack overflow on next push");
         }
     }
 
@@ -624,7 +875,7 @@ impl CPU {
         let (pointer, overflowed) = self.stack_pointer.overflowing_add(1);
         self.stack_pointer = pointer;
         if overflowed {
-            println!("Warning: Stack underflow on next pop");
+            eprintln!("Warning: Stack underflow on next pop");
         }
 
         let addr = STACK_BASE + (self.stack_pointer) as u16;
@@ -1,9 +1,11 @@
 use crate::nes::internals::{
     bus::{
-        test::factory::{build_bus, read_bus_cpu_ram},
+        test::factory::{build_bus, cpu_with_program, read_bus_cpu_ram},
         Bus, PG_ROOM_START,
     },
     cpu::{StatusFlags, CPU, STACK_SIZE},
+    debugger::StopReason,
+    memory::Memory,
 };
 
 const PROGRAM_COUNTER: u16 = 0x0600;
@@ -298,32 +300,32 @@ fn test_0x4c() {
     let bus = Bus::new();
     let mut cpu = CPU::new(bus);
     cpu.program_counter = PROGRAM_COUNTER;
-    cpu.load(vec![0x4c, 0x00, 0x70, 0x00]);
+    cpu.load(vec![0x4c, 0x00, 0x07, 0x00]);
     cpu.run();
-    assert_eq!(cpu.program_counter, 0x7001);
+    assert_eq!(cpu.program_counter, 0x0701);
 }
 
 #[test]
 fn test_0x6c_with_page_boundary_crossing() {
     let bus = build_bus!(
         pg_room[room_addr(0x80ff)] = 0xff,
-        pg_room[room_addr(0x8000)] = 0x71
+        pg_room[room_addr(0x8000)] = 0x07
     );
     let mut cpu = CPU::new(bus);
 
     cpu.program_counter = PROGRAM_COUNTER;
 
-    // the cpu will read from 0x7000 because of the page boundary crossing bug, the correct value is 0x7100
+    // the cpu will read the high byte from 0x8000 because of the page boundary crossing bug, the correct addr is 0x8100
     cpu.load(vec![0x6c, 0xff, 0x80, 0x00]);
     cpu.run();
-    assert_eq!(cpu.program_counter, 0x7200); // 0x71ff + 1 because of the read on the brk instruction
+    assert_eq!(cpu.program_counter, 0x0800); // 0x07ff + 1 because of the read on the brk instruction
 }
 
 #[test]
 fn test_0x6c_without_page_boundary_crossing() {
     let bus = build_bus!(
         pg_room[room_addr(0x84fd)] = 0xff,
-        pg_room[room_addr(0x84fe)] = 0x71
+        pg_room[room_addr(0x84fe)] = 0x07
     );
     let mut cpu = CPU::new(bus);
     cpu.program_counter = PROGRAM_COUNTER;
@@ -331,7 +333,7 @@ fn test_0x6c_without_page_boundary_crossing() {
     // cpu reading from the correct value because the page boundary is not crossed
     cpu.load(vec![0x6c, 0xfd, 0x84, 0x00]);
     cpu.run();
-    assert_eq!(cpu.program_counter, 0x7200); // 0x71ff + 1 because of the read on the brk instruction
+    assert_eq!(cpu.program_counter, 0x0800); // 0x07ff + 1 because of the read on the brk instruction
 }
 
 #[test]
@@ -357,19 +359,48 @@ fn test_5_ops_working_together() {
 }
 
 #[test]
-fn test_reset_should_reset_cpu() {
+fn test_power_on_should_reset_cpu() {
     let bus = Bus::new();
     let mut cpu = CPU::new(bus);
     cpu.register_a = 10;
     cpu.register_x = 11;
     cpu.register_y = 12;
     cpu.program_counter = 0xaab;
-    cpu.load(vec![0x00]);
-    cpu.reset();
+    cpu.load(vec![0xe8]);
+    cpu.power_on();
     assert_eq!(cpu.register_a, 0);
     assert_eq!(cpu.register_x, 0);
     assert_eq!(cpu.register_y, 0);
     assert_eq!(cpu.program_counter, 0);
+    assert_eq!(cpu.stack_pointer, 0xFD);
+    assert_eq!(cpu.status.bits(), 0b0010_0100);
+    // the RAM starts cleared
+    assert_eq!(cpu.read_from_memory(0x0600), 0x00);
+}
+
+#[test]
+fn test_reset_keeps_registers_and_ram() {
+    let mut bus = Bus::new();
+    bus.poke_memory(0xFFFC, 0x00);
+    bus.poke_memory(0xFFFD, 0x07);
+    let mut cpu = CPU::new(bus);
+    cpu.register_a = 10;
+    cpu.register_x = 11;
+    cpu.register_y = 12;
+    cpu.stack_pointer = 0xF0;
+    cpu.status = StatusFlags::CARRY;
+    cpu.load(vec![0xe8]);
+    cpu.reset();
+    assert_eq!(cpu.register_a, 10);
+    assert_eq!(cpu.register_x, 11);
+    assert_eq!(cpu.register_y, 12);
+    assert_eq!(cpu.stack_pointer, 0xED);
+    assert_eq!(
+        cpu.status.bits(),
+        (StatusFlags::CARRY | StatusFlags::INTERRUPT_DISABLE).bits()
+    );
+    assert_eq!(cpu.program_counter, 0x0700);
+    assert_eq!(cpu.read_from_memory(0x0600), 0xe8);
 }
 
 #[test]
@@ -3286,3 +3317,57 @@ fn test_tya() {
     assert!(cpu.register_a == 0x42);
     assert!(cpu.program_counter == PROGRAM_COUNTER + 2);
 }
+
+#[test]
+fn test_read_modify_write_reads_the_operand_once() {
+    // INC $2000,JMP $0603
+    let mut cpu = cpu_with_program(vec![0xee, 0x00, 0x20, 0x4c, 0x03, 0x06]);
+    cpu.bus_mut().events_mut().set_enabled(true);
+    let (scanline, dot) = cpu.bus().ppu_position();
+    cpu.run_while(|cpu| cpu.bus().frame_count() < 1);
+    // the write is the 5th access:the opcode,2 address bytes and the read come before it
+    let event = &cpu.bus().events().events()[0];
+    assert_eq!(event.write.map(|(addr, _)| addr), Some(0x2000));
+    assert_eq!((event.scanline, event.dot), (scanline, dot + 4 * 3));
+}
+
+#[test]
+fn test_brk_jumps_through_the_irq_vector() {
+    let mut cpu = cpu_with_program(vec![0x00, 0xea]);
+    cpu.set_stop_on_brk(false);
+    cpu.poke_memory(0xfffe, 0x10);
+    cpu.poke_memory(0xffff, 0x86);
+    cpu.status = StatusFlags::from_bits_truncate(0b0000_0001);
+    let cycles = cpu.bus().cycles();
+
+    assert_eq!(cpu.step(), None);
+    assert_eq!(cpu.program_counter, 0x8610);
+    assert_eq!(cpu.stack_pointer, STACK_SIZE - 3);
+    // PC+2 and the status with both break bits set
+    assert_eq!(read_bus_cpu_ram(cpu.bus(), 0x01ff), 0x06);
+    assert_eq!(read_bus_cpu_ram(cpu.bus(), 0x01fe), 0x02);
+    assert_eq!(read_bus_cpu_ram(cpu.bus(), 0x01fd), 0b0011_0001);
+    assert!(cpu.status.contains(StatusFlags::INTERRUPT_DISABLE));
+    assert_eq!(cpu.bus().cycles() - cycles, 7);
+}
+
+#[test]
+fn test_rti_returns_after_the_brk_padding() {
+    let mut cpu = cpu_with_program(vec![0x00, 0xea]);
+    cpu.set_stop_on_brk(false);
+    cpu.poke_memory(0xfffe, 0x10);
+    cpu.poke_memory(0xffff, 0x06);
+    cpu.poke_memory(0x0610, 0x40);
+
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.program_counter, 0x0602);
+    assert!(!cpu.status.contains(StatusFlags::BREAK));
+}
+
+#[test]
+fn test_brk_stops_the_test_programs() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    assert_eq!(cpu.step(), Some(StopReason::Brk));
+    assert_eq!(cpu.program_counter, 0x0601);
+}
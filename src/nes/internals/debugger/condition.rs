@@ -0,0 +1,262 @@
+use std::{fmt, iter::Peekable, str::Chars};
+
+use crate::nes::internals::cpu::CPU;
+
+/*
+  expressions evaluated against the cpu before each instruction,e.g. "A == $10 && [$0200] != 0".
+  registers: A X Y P SP PC
+  numbers: $10 (hex),0x10 (hex) or 16 (decimal)
+  memory: [addr] reads a byte without side effects
+  operators from lowest to highest precedence: || && (== != < <= > >=) &
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Number(u16),
+    Register(Register),
+    Memory(Box<Condition>),
+    BitAnd(Box<Condition>, Box<Condition>),
+    Compare(Box<Condition>, Comparison, Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Register {
+    A,
+    X,
+    Y,
+    P,
+    SP,
+    PC,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Condition, String> {
+        let mut parser = Parser {
+            chars: source.chars().peekable(),
+        };
+        let condition = parser.parse_or()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(condition),
+            Some(c) => Err(format!("Unexpected '{}' in condition", c)),
+        }
+    }
+
+    pub fn is_true(&self, cpu: &CPU) -> bool {
+        self.evaluate(cpu) != 0
+    }
+
+    fn evaluate(&self, cpu: &CPU) -> u16 {
+        let registers = cpu.registers();
+        match self {
+            Condition::Number(value) => *value,
+            Condition::Register(register) => match register {
+                Register::A => registers.a as u16,
+                Register::X => registers.x as u16,
+                Register::Y => registers.y as u16,
+                Register::P => registers.status as u16,
+                Register::SP => registers.stack_pointer as u16,
+                Register::PC => registers.program_counter,
+            },
+            Condition::Memory(addr) => cpu.peek_memory(addr.evaluate(cpu)) as u16,
+            Condition::BitAnd(left, right) => left.evaluate(cpu) & right.evaluate(cpu),
+            Condition::Compare(left, comparison, right) => {
+                let left = left.evaluate(cpu);
+                let right = right.evaluate(cpu);
+                let result = match comparison {
+                    Comparison::Equal => left == right,
+                    Comparison::NotEqual => left != right,
+                    Comparison::Less => left < right,
+                    Comparison::LessOrEqual => left <= right,
+                    Comparison::Greater => left > right,
+                    Comparison::GreaterOrEqual => left >= right,
+                };
+                result as u16
+            }
+            Condition::And(left, right) => (left.is_true(cpu) && right.is_true(cpu)) as u16,
+            Condition::Or(left, right) => (left.is_true(cpu) || right.is_true(cpu)) as u16,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Number(value) => write!(f, "${:X}", value),
+            Condition::Register(register) => write!(f, "{:?}", register),
+            Condition::Memory(addr) => write!(f, "[{}]", addr),
+            Condition::BitAnd(left, right) => write!(f, "{} & {}", left, right),
+            Condition::Compare(left, comparison, right) => {
+                let operator = match comparison {
+                    Comparison::Equal => "==",
+                    Comparison::NotEqual => "!=",
+                    Comparison::Less => "<",
+                    Comparison::LessOrEqual => "<=",
+                    Comparison::Greater => ">",
+                    Comparison::GreaterOrEqual => ">=",
+                };
+                write!(f, "{} {} {}", left, operator, right)
+            }
+            Condition::And(left, right) => {
+                // || has a lower precedence,so it needs parentheses to stay inside the &&
+                let wrap = |condition: &Condition| match condition {
+                    Condition::Or(_, _) => format!("({})", condition),
+                    _ => condition.to_string(),
+                };
+                write!(f, "{} && {}", wrap(left), wrap(right))
+            }
+            Condition::Or(left, right) => write!(f, "{} || {}", left, right),
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    // consumes the token if it is the next thing on the input
+    fn accept(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let mut lookahead = self.chars.clone();
+        for expected in token.chars() {
+            if lookahead.next() != Some(expected) {
+                return false;
+            }
+        }
+        self.chars = lookahead;
+        true
+    }
+
+    fn parse_or(&mut self) -> Result<Condition, String> {
+        let mut left = self.parse_and()?;
+        while self.accept("||") {
+            let right = self.parse_and()?;
+            left = Condition::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut left = self.parse_comparison()?;
+        while self.accept("&&") {
+            let right = self.parse_comparison()?;
+            left = Condition::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Condition, String> {
+        let left = self.parse_bit_and()?;
+        // the two character operators must be tried first,otherwise "<=" would be read as "<"
+        let comparison = if self.accept("==") {
+            Comparison::Equal
+        } else if self.accept("!=") {
+            Comparison::NotEqual
+        } else if self.accept("<=") {
+            Comparison::LessOrEqual
+        } else if self.accept(">=") {
+            Comparison::GreaterOrEqual
+        } else if self.accept("<") {
+            Comparison::Less
+        } else if self.accept(">") {
+            Comparison::Greater
+        } else {
+            return Ok(left);
+        };
+        let right = self.parse_bit_and()?;
+        Ok(Condition::Compare(
+            Box::new(left),
+            comparison,
+            Box::new(right),
+        ))
+    }
+
+    fn parse_bit_and(&mut self) -> Result<Condition, String> {
+        let mut left = self.parse_value()?;
+        loop {
+            // "&&" is the logical and,so a single '&' can only be taken if it is not followed by another one
+            self.skip_whitespace();
+            let mut lookahead = self.chars.clone();
+            lookahead.next();
+            if self.chars.peek() == Some(&'&') && lookahead.peek() != Some(&'&') {
+                self.chars.next();
+                let right = self.parse_value()?;
+                left = Condition::BitAnd(Box::new(left), Box::new(right));
+            } else {
+                return Ok(left);
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Condition, String> {
+        self.skip_whitespace();
+        if self.accept("(") {
+            let inner = self.parse_or()?;
+            return match self.accept(")") {
+                true => Ok(inner),
+                false => Err(String::from("Missing ')' in condition")),
+            };
+        }
+        if self.accept("[") {
+            let addr = self.parse_or()?;
+            return match self.accept("]") {
+                true => Ok(Condition::Memory(Box::new(addr))),
+                false => Err(String::from("Missing ']' in condition")),
+            };
+        }
+
+        let mut word = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_alphanumeric() || *c == '$')
+        {
+            word.push(c);
+        }
+        if word.is_empty() {
+            return match self.chars.peek() {
+                Some(c) => Err(format!("Unexpected '{}' in condition", c)),
+                None => Err(String::from("Unexpected end of condition")),
+            };
+        }
+
+        let register = match word.to_ascii_uppercase().as_str() {
+            "A" => Some(Register::A),
+            "X" => Some(Register::X),
+            "Y" => Some(Register::Y),
+            "P" => Some(Register::P),
+            "SP" => Some(Register::SP),
+            "PC" => Some(Register::PC),
+            _ => None,
+        };
+        if let Some(register) = register {
+            return Ok(Condition::Register(register));
+        }
+
+        let number = if let Some(hex) = word.strip_prefix('$') {
+            u16::from_str_radix(hex, 16)
+        } else if let Some(hex) = word.strip_prefix("0x") {
+            u16::from_str_radix(hex, 16)
+        } else {
+            word.parse()
+        };
+        number
+            .map(Condition::Number)
+            .map_err(|_| format!("Invalid value {} in condition", word))
+    }
+}
@@ -0,0 +1,428 @@
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use crate::nes::internals::{
+    cpu::{Registers, CPU},
+    debugger::{Breakpoint, BreakpointId, StopReason},
+};
+
+#[cfg(test)]
+mod test;
+
+// Ctrl-C sent by the client while the emulation is running
+const INTERRUPT_BYTE: u8 = 0x03;
+// how many instructions run between checks for an interrupt from the client
+const POLL_INTERVAL: usize = 1024;
+
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+/*
+  minimal GDB remote serial protocol stub.
+  registers are exposed in the order A X Y P SP (one byte each) and PC (two bytes,little endian)
+*/
+pub struct GdbServer {
+    listener: TcpListener,
+}
+
+impl GdbServer {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<GdbServer> {
+        Ok(GdbServer {
+            listener: TcpListener::bind(addr)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /*
+      waits for a client and serves it until it detaches,kills the emulation or disconnects.
+      `on_instruction` runs after each instruction while the client lets the emulation continue
+    */
+    pub fn serve<F: FnMut(&mut CPU)>(&self, cpu: &mut CPU, on_instruction: F) -> io::Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        // packets are tiny and every one of them waits for an answer,batching them only adds latency
+        stream.set_nodelay(true)?;
+        let mut session = Session {
+            stream,
+            cpu,
+            on_instruction,
+            // gdb software breakpoints live in the debugger,so they need to be mapped back when removed
+            breakpoints: Vec::new(),
+        };
+        session.serve()
+    }
+}
+
+struct Session<'a, F: FnMut(&mut CPU)> {
+    stream: TcpStream,
+    cpu: &'a mut CPU,
+    on_instruction: F,
+    breakpoints: Vec<(Breakpoint, BreakpointId)>,
+}
+
+enum Reply {
+    Packet(String),
+    // the session ends after sending the packet,if any
+    Close(Option<String>),
+}
+
+impl<F: FnMut(&mut CPU)> Session<'_, F> {
+    fn serve(&mut self) -> io::Result<()> {
+        loop {
+            let packet = match self.read_packet()? {
+                Some(packet) => packet,
+                None => return Ok(()),
+            };
+            match self.handle(&packet) {
+                Reply::Packet(reply) => self.write_packet(&reply)?,
+                Reply::Close(reply) => {
+                    if let Some(reply) = reply {
+                        self.write_packet(&reply)?;
+                    }
+                    self.remove_breakpoints();
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn handle(&mut self, packet: &str) -> Reply {
+        // the payload comes from the network,the first char may take more than 1 byte
+        let split = packet
+            .char_indices()
+            .nth(1)
+            .map_or(packet.len(), |(index, _)| index);
+        let (command, args) = packet.split_at(split);
+        let reply = match command {
+            "?" => stop_reply(SIGTRAP),
+            "g" => encode_registers(&self.cpu.registers()),
+            "G" => match decode_registers(args) {
+                Some(registers) => {
+                    self.cpu.set_registers(registers);
+                    ok()
+                }
+                None => error(),
+            },
+            "p" => match parse_hex(args).and_then(|n| self.read_register(n)) {
+                Some(value) => value,
+                None => error(),
+            },
+            "P" => match args.split_once('=') {
+                Some((n, value)) if self.write_register(n, value).is_some() => ok(),
+                _ => error(),
+            },
+            "m" => self.read_memory(args).unwrap_or_else(error),
+            "M" => self.write_memory(args).map(|_| ok()).unwrap_or_else(error),
+            // an empty reply tells gdb the kind isn't supported,so it can fall back to another one
+            "Z" | "z" if !Self::supports_breakpoint(args) => String::new(),
+            "Z" => self
+                .add_breakpoint(args)
+                .map(|_| ok())
+                .unwrap_or_else(error),
+            "z" => self
+                .remove_breakpoint(args)
+                .map(|_| ok())
+                .unwrap_or_else(error),
+            "s" => self.step(),
+            "c" => match self.resume() {
+                Ok(reply) => reply,
+                Err(err) => {
+                    return Reply::Close(Some(format!(
+                        "E{:02X}",
+                        err.raw_os_error().unwrap_or(0) as u8
+                    )))
+                }
+            },
+            "D" => return Reply::Close(Some(ok())),
+            "k" => return Reply::Close(None),
+            "H" => ok(),
+            "q" => query(args),
+            _ => String::new(),
+        };
+        Reply::Packet(reply)
+    }
+
+    fn read_register(&self, n: usize) -> Option<String> {
+        let registers = encode_registers(&self.cpu.registers());
+        // every register is one byte except the pc which is the last one
+        let (start, len) = match n {
+            0..=4 => (n * 2, 2),
+            5 => (10, 4),
+            _ => return None,
+        };
+        Some(registers[start..start + len].to_string())
+    }
+
+    fn write_register(&mut self, n: &str, value: &str) -> Option<()> {
+        let bytes = decode_hex(value)?;
+        let mut registers = self.cpu.registers();
+        match (parse_hex(n)?, bytes.as_slice()) {
+            (0, [a]) => registers.a = *a,
+            (1, [x]) => registers.x = *x,
+            (2, [y]) => registers.y = *y,
+            (3, [status]) => registers.status = *status,
+            (4, [stack_pointer]) => registers.stack_pointer = *stack_pointer,
+            (5, [lo, hi]) => registers.program_counter = (*hi as u16) << 8 | *lo as u16,
+            _ => return None,
+        }
+        self.cpu.set_registers(registers);
+        Some(())
+    }
+
+    // memory is peeked,reading a register through the debugger must not change the state of the machine
+    fn read_memory(&self, args: &str) -> Option<String> {
+        let (addr, len) = args.split_once(',')?;
+        let addr = parse_hex(addr)? as u16;
+        let len = parse_hex(len)? as u16;
+        let bytes = (0..len).map(|i| self.cpu.peek_memory(addr.wrapping_add(i)));
+        Some(bytes.map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    fn write_memory(&mut self, args: &str) -> Option<()> {
+        let (location, data) = args.split_once(':')?;
+        let (addr, len) = location.split_once(',')?;
+        let addr = parse_hex(addr)? as u16;
+        let bytes = decode_hex(data)?;
+        if bytes.len() != parse_hex(len)? {
+            return None;
+        }
+        for (i, byte) in bytes.into_iter().enumerate() {
+            self.cpu.poke_memory(addr.wrapping_add(i as u16), byte);
+        }
+        Some(())
+    }
+
+    // Z4 access watchpoints aren't supported
+    fn supports_breakpoint(args: &str) -> bool {
+        matches!(args.split(',').next(), Some("0" | "1" | "2" | "3"))
+    }
+
+    // Z0 is a software breakpoint,Z2 a write watchpoint and Z3 a read watchpoint
+    fn parse_breakpoint(args: &str) -> Option<Breakpoint> {
+        let mut parts = args.split(',');
+        let kind = parts.next()?;
+        let addr = parse_hex(parts.next()?)? as u16;
+        let len = parts.next().and_then(parse_hex).unwrap_or(1).max(1) as u16;
+        let range = addr..=addr.wrapping_add(len - 1);
+        match kind {
+            "0" | "1" => Some(Breakpoint::Execute(addr)),
+            "2" => Some(Breakpoint::Write(range)),
+            "3" => Some(Breakpoint::Read(range)),
+            _ => None,
+        }
+    }
+
+    fn add_breakpoint(&mut self, args: &str) -> Option<()> {
+        let breakpoint = Self::parse_breakpoint(args)?;
+        let id = self.cpu.debugger_mut().add(breakpoint.clone());
+        self.breakpoints.push((breakpoint, id));
+        Some(())
+    }
+
+    fn remove_breakpoint(&mut self, args: &str) -> Option<()> {
+        let breakpoint = Self::parse_breakpoint(args)?;
+        let index = self
+            .breakpoints
+            .iter()
+            .position(|(added, _)| *added == breakpoint)?;
+        let (_, id) = self.breakpoints.remove(index);
+        self.cpu.debugger_mut().remove(id);
+        Some(())
+    }
+
+    fn remove_breakpoints(&mut self) {
+        for (_, id) in self.breakpoints.drain(..) {
+            self.cpu.debugger_mut().remove(id);
+        }
+    }
+
+    fn step(&mut self) -> String {
+        match self.cpu.step() {
+            Some(StopReason::Brk) => exit_reply(),
+            Some(StopReason::InvalidOpcode { .. }) => stop_reply(SIGILL),
+            _ => stop_reply(SIGTRAP),
+        }
+    }
+
+    fn resume(&mut self) -> io::Result<String> {
+        let mut instructions = 0;
+        let mut interrupted = Ok(false);
+        let stream = &mut self.stream;
+        let on_instruction = &mut self.on_instruction;
+        let reason = self.cpu.run_while(|cpu| {
+            on_instruction(cpu);
+            instructions += 1;
+            if instructions % POLL_INTERVAL != 0 {
+                return true;
+            }
+            interrupted = poll_interrupt(stream);
+            !matches!(interrupted, Ok(true) | Err(_))
+        });
+        interrupted?;
+        Ok(match reason {
+            Some(StopReason::Brk) => exit_reply(),
+            Some(StopReason::Breakpoint(_, _)) => stop_reply(SIGTRAP),
+            Some(StopReason::InvalidOpcode { .. }) => stop_reply(SIGILL),
+            None => stop_reply(SIGINT),
+        })
+    }
+
+    // returns None when the client disconnects
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            let byte = match self.read_byte()? {
+                Some(byte) => byte,
+                None => return Ok(None),
+            };
+            match byte {
+                b'$' => {}
+                // an interrupt while stopped just reports the current state again
+                INTERRUPT_BYTE => {
+                    self.write_packet(&stop_reply(SIGINT))?;
+                    continue;
+                }
+                // acks and anything between packets are ignored
+                _ => continue,
+            }
+
+            let mut payload = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    Some(b'#') => break,
+                    Some(byte) => payload.push(byte),
+                    None => return Ok(None),
+                }
+            }
+            let mut checksum = [0; 2];
+            self.stream.read_exact(&mut checksum)?;
+
+            let expected = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|checksum| u8::from_str_radix(checksum, 16).ok());
+            if expected != Some(checksum_of(&payload)) {
+                self.stream.write_all(b"-")?;
+                continue;
+            }
+            self.stream.write_all(b"+")?;
+            return Ok(Some(String::from_utf8_lossy(&payload).into_owned()));
+        }
+    }
+
+    fn write_packet(&mut self, payload: &str) -> io::Result<()> {
+        let packet = format!("${}#{:02x}", payload, checksum_of(payload.as_bytes()));
+        loop {
+            self.stream.write_all(packet.as_bytes())?;
+            self.stream.flush()?;
+            // the client answers with '+' when the packet arrives intact and '-' to ask for it again
+            match self.read_byte()? {
+                Some(b'-') => continue,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0; 1];
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+}
+
+fn poll_interrupt(stream: &mut TcpStream) -> io::Result<bool> {
+    stream.set_nonblocking(true)?;
+    let mut byte = [0; 1];
+    let result = match stream.read(&mut byte) {
+        Ok(0) => Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "Client disconnected",
+        )),
+        Ok(_) => Ok(byte[0] == INTERRUPT_BYTE),
+        Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(false),
+        Err(err) => Err(err),
+    };
+    stream.set_nonblocking(false)?;
+    result
+}
+
+fn query(args: &str) -> String {
+    match args.split(':').next().unwrap_or_default() {
+        "Supported" => String::from("PacketSize=4000"),
+        "Attached" => String::from("1"),
+        "C" => String::from("QC1"),
+        "fThreadInfo" => String::from("m1"),
+        "sThreadInfo" => String::from("l"),
+        _ => String::new(),
+    }
+}
+
+fn ok() -> String {
+    String::from("OK")
+}
+
+fn error() -> String {
+    String::from("E01")
+}
+
+fn stop_reply(signal: u8) -> String {
+    format!("S{:02x}", signal)
+}
+
+// BRK halts the snake and the test programs,which gdb sees as the program exiting
+fn exit_reply() -> String {
+    String::from("W00")
+}
+
+fn checksum_of(payload: &[u8]) -> u8 {
+    payload.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn parse_hex(value: &str) -> Option<usize> {
+    usize::from_str_radix(value, 16).ok()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn encode_registers(registers: &Registers) -> String {
+    let [pc_lo, pc_hi] = registers.program_counter.to_le_bytes();
+    [
+        registers.a,
+        registers.x,
+        registers.y,
+        registers.status,
+        registers.stack_pointer,
+        pc_lo,
+        pc_hi,
+    ]
+    .iter()
+    .map(|byte| format!("{:02x}", byte))
+    .collect()
+}
+
+fn decode_registers(value: &str) -> Option<Registers> {
+    match decode_hex(value)?.as_slice() {
+        [a, x, y, status, stack_pointer, pc_lo, pc_hi] => Some(Registers {
+            a: *a,
+            x: *x,
+            y: *y,
+            status: *status,
+            stack_pointer: *stack_pointer,
+            program_counter: (*pc_hi as u16) << 8 | *pc_lo as u16,
+        }),
+        _ => None,
+    }
+}
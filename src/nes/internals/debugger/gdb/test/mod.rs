@@ -0,0 +1,186 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    thread::{self, JoinHandle},
+};
+
+use crate::nes::internals::{
+    bus::test::factory::cpu_with_program,
+    cpu::CPU,
+    debugger::gdb::{checksum_of, GdbServer},
+    memory::Memory,
+};
+
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn send(&mut self, payload: &str) -> String {
+        self.send_without_reply(payload);
+        self.receive()
+    }
+
+    fn send_without_reply(&mut self, payload: &str) {
+        let packet = format!("${}#{:02x}", payload, checksum_of(payload.as_bytes()));
+        self.stream.write_all(packet.as_bytes()).unwrap();
+        assert_eq!(self.read_byte(), b'+');
+    }
+
+    // sends a payload that isn't valid UTF-8
+    fn send_bytes(&mut self, payload: &[u8]) -> String {
+        let mut packet = vec![b'$'];
+        packet.extend_from_slice(payload);
+        packet.extend_from_slice(format!("#{:02x}", checksum_of(payload)).as_bytes());
+        self.stream.write_all(&packet).unwrap();
+        assert_eq!(self.read_byte(), b'+');
+        self.receive()
+    }
+
+    fn receive(&mut self) -> String {
+        assert_eq!(self.read_byte(), b'$');
+        let mut payload = Vec::new();
+        loop {
+            match self.read_byte() {
+                b'#' => break,
+                byte => payload.push(byte),
+            }
+        }
+        let mut checksum = [0; 2];
+        self.stream.read_exact(&mut checksum).unwrap();
+        assert_eq!(
+            u8::from_str_radix(std::str::from_utf8(&checksum).unwrap(), 16).unwrap(),
+            checksum_of(&payload)
+        );
+        self.stream.write_all(b"+").unwrap();
+        String::from_utf8(payload).unwrap()
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let mut byte = [0; 1];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+}
+
+fn start(program: Vec<u8>) -> (Client, JoinHandle<CPU>) {
+    let server = GdbServer::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let handle = thread::spawn(move || {
+        let mut cpu = cpu_with_program(program);
+        server.serve(&mut cpu, |_| {}).unwrap();
+        cpu
+    });
+    let stream = TcpStream::connect(addr).unwrap();
+    stream.set_nodelay(true).unwrap();
+    (Client { stream }, handle)
+}
+
+#[test]
+fn test_read_registers() {
+    let (mut client, handle) = start(vec![0x00]);
+
+    assert_eq!(client.send("?"), "S05");
+    assert_eq!(client.send("g"), "00000024ff0006");
+    assert_eq!(client.send("p5"), "0006");
+    assert_eq!(client.send("p3"), "24");
+    assert_eq!(client.send("p9"), "E01");
+    assert_eq!(client.send("D"), "OK");
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_write_registers() {
+    let (mut client, handle) = start(vec![0x00]);
+
+    assert_eq!(client.send("G0102032540fe07"), "OK");
+    assert_eq!(client.send("P0=aa"), "OK");
+    assert_eq!(client.send("g"), "aa02032540fe07");
+    client.send_without_reply("k");
+
+    let registers = handle.join().unwrap().registers();
+    assert_eq!(registers.a, 0xaa);
+    assert_eq!(registers.program_counter, 0x07fe);
+}
+
+#[test]
+fn test_read_and_write_memory() {
+    let (mut client, handle) = start(vec![0xa9, 0x05, 0x00]);
+
+    assert_eq!(client.send("m600,3"), "a90500");
+    assert_eq!(client.send("M10,2:beef"), "OK");
+    assert_eq!(client.send("m10,2"), "beef");
+    assert_eq!(client.send("M10,2:be"), "E01");
+    client.send_without_reply("k");
+
+    let mut cpu = handle.join().unwrap();
+    assert_eq!(cpu.read_from_memory(0x11), 0xef);
+}
+
+#[test]
+fn test_breakpoint_continue_and_step() {
+    /*
+    INX
+    INX
+    INX
+    BRK
+     */
+    let (mut client, handle) = start(vec![0xe8, 0xe8, 0xe8, 0x00]);
+
+    assert_eq!(client.send("Z0,602,1"), "OK");
+    assert_eq!(client.send("c"), "S05");
+    assert_eq!(client.send("p5"), "0206");
+    assert_eq!(client.send("p1"), "02");
+
+    assert_eq!(client.send("s"), "S05");
+    assert_eq!(client.send("p1"), "03");
+
+    assert_eq!(client.send("z0,602,1"), "OK");
+    assert_eq!(client.send("z0,602,1"), "E01");
+    assert_eq!(client.send("c"), "W00");
+    client.send_without_reply("k");
+
+    let cpu = handle.join().unwrap();
+    assert_eq!(cpu.debugger().breakpoints().count(), 0);
+}
+
+#[test]
+fn test_write_watchpoint() {
+    /*
+    LDA #$01
+    STA $10
+    INX
+    BRK
+     */
+    let (mut client, handle) = start(vec![0xa9, 0x01, 0x85, 0x10, 0xe8, 0x00]);
+
+    assert_eq!(client.send("Z2,10,1"), "OK");
+    assert_eq!(client.send("c"), "S05");
+    assert_eq!(client.send("p5"), "0406");
+    client.send_without_reply("k");
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_unsupported_packet() {
+    let (mut client, handle) = start(vec![0x00]);
+
+    assert_eq!(client.send("vCont?"), "");
+    assert_eq!(client.send("Z4,0600,1"), "");
+    assert_eq!(client.send("z4,0600,1"), "");
+    assert_eq!(client.send("Z0,zz,1"), "E01");
+    assert_eq!(client.send("qSupported:multiprocess+"), "PacketSize=4000");
+    client.send_without_reply("k");
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_non_ascii_packet() {
+    let (mut client, handle) = start(vec![0x00]);
+
+    assert_eq!(client.send_bytes(b"\xff"), "");
+    assert_eq!(client.send_bytes(b"\xff12"), "");
+    assert_eq!(client.send("?"), "S05");
+    client.send_without_reply("k");
+    handle.join().unwrap();
+}
@@ -0,0 +1,257 @@
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use super::{
+    bus::Access,
+    cpu::{Interrupt, CPU},
+};
+
+pub mod condition;
+pub mod gdb;
+
+use condition::Condition;
+
+#[cfg(test)]
+mod test;
+
+pub type BreakpointId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+    // stops before executing the instruction at the address
+    Execute(u16),
+    // stops after an instruction reads from / writes to the range
+    Read(RangeInclusive<u16>),
+    Write(RangeInclusive<u16>),
+    // stops before executing any instruction with this opcode,official or not
+    Opcode(u8),
+    // stops after the cpu jumps to the interrupt handler
+    Interrupt(Interrupt),
+    // stops before executing an instruction when the condition is true
+    Condition(Condition),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Hit {
+    Execute(u16),
+    Read { addr: u16, value: u8 },
+    Write { addr: u16, value: u8 },
+    Opcode { addr: u16, opcode: u8 },
+    Interrupt(Interrupt),
+    Condition(u16),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopReason {
+    // the program executed a BRK instruction,only when the CPU is set to stop on it
+    Brk,
+    Breakpoint(BreakpointId, Hit),
+    // an unofficial opcode,the CPU doesn't emulate them yet
+    InvalidOpcode { addr: u16, opcode: u8 },
+}
+
+struct Entry {
+    id: BreakpointId,
+    breakpoint: Breakpoint,
+    enabled: bool,
+}
+
+#[derive(Default)]
+pub struct Debugger {
+    entries: Vec<Entry>,
+    next_id: BreakpointId,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Debugger::default()
+    }
+
+    pub fn add(&mut self, breakpoint: Breakpoint) -> BreakpointId {
+        self.next_id += 1;
+        self.entries.push(Entry {
+            id: self.next_id,
+            breakpoint,
+            enabled: true,
+        });
+        self.next_id
+    }
+
+    pub fn remove(&mut self, id: BreakpointId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.len() != len
+    }
+
+    pub fn set_enabled(&mut self, id: BreakpointId, enabled: bool) -> bool {
+        match self.entries.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = (BreakpointId, &Breakpoint, bool)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.id, &entry.breakpoint, entry.enabled))
+    }
+
+    // memory accesses are only recorded by the bus while there is something watching them
+    pub fn has_watchpoints(&self) -> bool {
+        self.enabled()
+            .any(|entry| matches!(entry.breakpoint, Breakpoint::Read(_) | Breakpoint::Write(_)))
+    }
+
+    pub fn check_instruction(&self, cpu: &CPU) -> Option<(BreakpointId, Hit)> {
+        let addr = cpu.registers().program_counter;
+        self.enabled().find_map(|entry| {
+            let hit = match &entry.breakpoint {
+                Breakpoint::Execute(target) if *target == addr => Hit::Execute(addr),
+                Breakpoint::Opcode(opcode) if cpu.peek_memory(addr) == *opcode => Hit::Opcode {
+                    addr,
+                    opcode: *opcode,
+                },
+                Breakpoint::Condition(condition) if condition.is_true(cpu) => Hit::Condition(addr),
+                _ => return None,
+            };
+            Some((entry.id, hit))
+        })
+    }
+
+    pub fn check_accesses(&self, accesses: &[Access]) -> Option<(BreakpointId, Hit)> {
+        accesses.iter().find_map(|access| {
+            self.enabled().find_map(|entry| {
+                let hit = match (&entry.breakpoint, access) {
+                    (Breakpoint::Read(range), Access::Read { addr, value })
+                        if range.contains(addr) =>
+                    {
+                        Hit::Read {
+                            addr: *addr,
+                            value: *value,
+                        }
+                    }
+                    (Breakpoint::Write(range), Access::Write { addr, value })
+                        if range.contains(addr) =>
+                    {
+                        Hit::Write {
+                            addr: *addr,
+                            value: *value,
+                        }
+                    }
+                    _ => return None,
+                };
+                Some((entry.id, hit))
+            })
+        })
+    }
+
+    pub fn check_interrupt(&self, interrupt: Interrupt) -> Option<(BreakpointId, Hit)> {
+        self.enabled().find_map(|entry| match entry.breakpoint {
+            Breakpoint::Interrupt(target) if target == interrupt => {
+                Some((entry.id, Hit::Interrupt(interrupt)))
+            }
+            _ => None,
+        })
+    }
+
+    fn enabled(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(|entry| entry.enabled)
+    }
+}
+
+fn parse_addr(value: &str) -> Result<u16, String> {
+    let digits = value.trim_start_matches('$');
+    u16::from_str_radix(digits, 16).map_err(|_| format!("Invalid address {}", value))
+}
+
+// accepts a single address (C000) or an inclusive range (C000-C0FF)
+pub fn parse_addr_range(value: &str) -> Result<RangeInclusive<u16>, String> {
+    match value.split_once('-') {
+        Some((start, end)) => Ok(parse_addr(start)?..=parse_addr(end)?),
+        None => {
+            let addr = parse_addr(value)?;
+            Ok(addr..=addr)
+        }
+    }
+}
+
+/*
+  C000 | exec:C000        execute breakpoint
+  read:0200-02FF          read watchpoint
+  write:4014              write watchpoint
+  opcode:00               opcode breakpoint
+  nmi | irq               interrupt breakpoint
+  if:A == $10             conditional breakpoint
+*/
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.trim();
+        let (kind, value) = spec.split_once(':').unwrap_or(("exec", spec));
+        match kind.to_ascii_lowercase().as_str() {
+            "exec" if value.eq_ignore_ascii_case("nmi") => {
+                Ok(Breakpoint::Interrupt(Interrupt::NMI))
+            }
+            "exec" if value.eq_ignore_ascii_case("irq") => {
+                Ok(Breakpoint::Interrupt(Interrupt::IRQ))
+            }
+            "exec" => Ok(Breakpoint::Execute(parse_addr(value)?)),
+            "read" => Ok(Breakpoint::Read(parse_addr_range(value)?)),
+            "write" => Ok(Breakpoint::Write(parse_addr_range(value)?)),
+            "opcode" => {
+                let opcode = u8::from_str_radix(value.trim_start_matches('$'), 16)
+                    .map_err(|_| format!("Invalid opcode {}", value))?;
+                Ok(Breakpoint::Opcode(opcode))
+            }
+            "if" => Ok(Breakpoint::Condition(Condition::parse(value)?)),
+            _ => Err(format!("Unknown breakpoint {}", spec)),
+        }
+    }
+}
+
+fn format_range(range: &RangeInclusive<u16>) -> String {
+    match range.start() == range.end() {
+        true => format!("{:04X}", range.start()),
+        false => format!("{:04X}-{:04X}", range.start(), range.end()),
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::Execute(addr) => write!(f, "exec:{:04X}", addr),
+            Breakpoint::Read(range) => write!(f, "read:{}", format_range(range)),
+            Breakpoint::Write(range) => write!(f, "write:{}", format_range(range)),
+            Breakpoint::Opcode(opcode) => write!(f, "opcode:{:02X}", opcode),
+            Breakpoint::Interrupt(interrupt) => write!(f, "{:?}", interrupt),
+            Breakpoint::Condition(condition) => write!(f, "if:{}", condition),
+        }
+    }
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StopReason::Brk => write!(f, "BRK executed"),
+            StopReason::InvalidOpcode { addr, opcode } => {
+                write!(f, "unsupported opcode {:02X} at {:04X}", opcode, addr)
+            }
+            StopReason::Breakpoint(id, hit) => {
+                write!(f, "breakpoint #{} hit: ", id)?;
+                match hit {
+                    Hit::Execute(addr) => write!(f, "execute at {:04X}", addr),
+                    Hit::Read { addr, value } => write!(f, "read {:02X} from {:04X}", value, addr),
+                    Hit::Write { addr, value } => write!(f, "write {:02X} to {:04X}", value, addr),
+                    Hit::Opcode { addr, opcode } => {
+                        write!(f, "opcode {:02X} at {:04X}", opcode, addr)
+                    }
+                    Hit::Interrupt(interrupt) => write!(f, "{:?} taken", interrupt),
+                    Hit::Condition(addr) => write!(f, "condition true at {:04X}", addr),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::nes::internals::{
+    bus::test::factory::cpu_with_program,
+    debugger::condition::{Comparison, Condition, Register},
+    memory::Memory,
+};
+
+#[test]
+fn test_parse_comparison() {
+    assert_eq!(
+        Condition::parse("A == $10"),
+        Ok(Condition::Compare(
+            Box::new(Condition::Register(Register::A)),
+            Comparison::Equal,
+            Box::new(Condition::Number(0x10))
+        ))
+    );
+}
+
+#[test]
+fn test_parse_numbers() {
+    assert_eq!(Condition::parse("$ff"), Ok(Condition::Number(0xFF)));
+    assert_eq!(Condition::parse("0x10"), Ok(Condition::Number(0x10)));
+    assert_eq!(Condition::parse("10"), Ok(Condition::Number(10)));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(Condition::parse("A ==").is_err());
+    assert!(Condition::parse("[$10").is_err());
+    assert!(Condition::parse("(A == 1").is_err());
+    assert!(Condition::parse("A == 1 )").is_err());
+    assert!(Condition::parse("Q == 1").is_err());
+}
+
+#[test]
+fn test_and_has_higher_precedence_than_or() {
+    let condition = Condition::parse("1 || 0 && 0").unwrap();
+    assert!(matches!(condition, Condition::Or(_, _)));
+}
+
+#[test]
+fn test_evaluate_memory_and_registers() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    cpu.write_to_memory(0x10, 0x81);
+
+    assert!(Condition::parse("[$10] & $80").unwrap().is_true(&cpu));
+    assert!(Condition::parse("[$10] & $80 && PC >= $0600")
+        .unwrap()
+        .is_true(&cpu));
+    assert!(!Condition::parse("[$10] == 0 || SP < $F0")
+        .unwrap()
+        .is_true(&cpu));
+    assert!(Condition::parse("([$10] == 0 || SP > $F0) && P == $24")
+        .unwrap()
+        .is_true(&cpu));
+}
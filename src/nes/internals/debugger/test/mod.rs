@@ -0,0 +1,271 @@
+mod condition;
+
+use crate::nes::internals::{
+    bus::{
+        test::factory::{build_bus, cpu_with_program},
+        PG_ROOM_START,
+    },
+    cpu::{Interrupt, CPU},
+    debugger::{Breakpoint, Hit, StopReason},
+    memory::Memory,
+};
+
+#[test]
+fn test_run_stops_on_brk() {
+    let mut cpu = cpu_with_program(vec![0xe8, 0x00]);
+    assert_eq!(cpu.run(), StopReason::Brk);
+}
+
+#[test]
+fn test_execute_breakpoint_stops_before_instruction() {
+    /*
+    INX
+    INX
+    INX
+    BRK
+     */
+    let mut cpu = cpu_with_program(vec![0xe8, 0xe8, 0xe8, 0x00]);
+    let id = cpu.debugger_mut().add(Breakpoint::Execute(0x0602));
+
+    assert_eq!(cpu.run(), StopReason::Breakpoint(id, Hit::Execute(0x0602)));
+    assert_eq!(cpu.registers().program_counter, 0x0602);
+    assert_eq!(cpu.registers().x, 2);
+
+    // resuming executes the instruction under the breakpoint
+    assert_eq!(cpu.run(), StopReason::Brk);
+    assert_eq!(cpu.registers().x, 3);
+}
+
+#[test]
+fn test_breakpoint_on_the_first_instruction() {
+    let mut cpu = cpu_with_program(vec![0xe8, 0xe8, 0x00]);
+    let id = cpu.debugger_mut().add(Breakpoint::Execute(0x0600));
+
+    assert_eq!(cpu.run(), StopReason::Breakpoint(id, Hit::Execute(0x0600)));
+    assert_eq!(cpu.registers().x, 0);
+}
+
+#[test]
+fn test_breakpoint_is_checked_after_the_callback_stops() {
+    /*
+    INX
+    INX
+    BRK
+     */
+    let mut cpu = cpu_with_program(vec![0xe8, 0xe8, 0x00]);
+    let id = cpu.debugger_mut().add(Breakpoint::Execute(0x0601));
+
+    // the callback stops right before the breakpoint
+    assert_eq!(cpu.run_while(|_| false), None);
+    assert_eq!(cpu.run(), StopReason::Breakpoint(id, Hit::Execute(0x0601)));
+    assert_eq!(cpu.registers().x, 1);
+}
+
+#[test]
+fn test_resuming_from_an_unofficial_opcode_stops() {
+    // INX,then $02 which the CPU doesn't emulate
+    let mut cpu = cpu_with_program(vec![0xe8, 0x02]);
+    let id = cpu.debugger_mut().add(Breakpoint::Opcode(0x02));
+
+    assert_eq!(
+        cpu.run(),
+        StopReason::Breakpoint(
+            id,
+            Hit::Opcode {
+                addr: 0x0601,
+                opcode: 0x02
+            }
+        )
+    );
+    let reason = StopReason::InvalidOpcode {
+        addr: 0x0601,
+        opcode: 0x02,
+    };
+    assert_eq!(cpu.run(), reason);
+    assert_eq!(cpu.registers().program_counter, 0x0601);
+    assert_eq!(reason.to_string(), "unsupported opcode 02 at 0601");
+}
+
+#[test]
+fn test_disabled_breakpoint_is_ignored() {
+    let mut cpu = cpu_with_program(vec![0xe8, 0xe8, 0x00]);
+    let id = cpu.debugger_mut().add(Breakpoint::Execute(0x0601));
+    cpu.debugger_mut().set_enabled(id, false);
+
+    assert_eq!(cpu.run(), StopReason::Brk);
+}
+
+#[test]
+fn test_removed_breakpoint_is_ignored() {
+    let mut cpu = cpu_with_program(vec![0xe8, 0xe8, 0x00]);
+    let id = cpu.debugger_mut().add(Breakpoint::Execute(0x0601));
+
+    assert!(cpu.debugger_mut().remove(id));
+    assert!(!cpu.debugger_mut().remove(id));
+    assert_eq!(cpu.run(), StopReason::Brk);
+}
+
+#[test]
+fn test_write_watchpoint_stops_after_instruction() {
+    /*
+    LDA #$05
+    STA $0210
+    INX
+    BRK
+     */
+    let mut cpu = cpu_with_program(vec![0xa9, 0x05, 0x8d, 0x10, 0x02, 0xe8, 0x00]);
+    let id = cpu.debugger_mut().add(Breakpoint::Write(0x0200..=0x02FF));
+
+    assert_eq!(
+        cpu.run(),
+        StopReason::Breakpoint(
+            id,
+            Hit::Write {
+                addr: 0x0210,
+                value: 0x05
+            }
+        )
+    );
+    assert_eq!(cpu.registers().program_counter, 0x0605);
+}
+
+#[test]
+fn test_read_watchpoint() {
+    /*
+    LDA $10
+    BRK
+     */
+    let mut cpu = cpu_with_program(vec![0xa5, 0x10, 0x00]);
+    cpu.write_to_memory(0x10, 0x33);
+    let id = cpu.debugger_mut().add(Breakpoint::Read(0x0010..=0x0010));
+
+    assert_eq!(
+        cpu.run(),
+        StopReason::Breakpoint(
+            id,
+            Hit::Read {
+                addr: 0x0010,
+                value: 0x33
+            }
+        )
+    );
+}
+
+#[test]
+fn test_opcode_breakpoint() {
+    /*
+    INX
+    INY
+    BRK
+     */
+    let mut cpu = cpu_with_program(vec![0xe8, 0xc8, 0x00]);
+    let id = cpu.debugger_mut().add(Breakpoint::Opcode(0xc8));
+
+    assert_eq!(
+        cpu.run(),
+        StopReason::Breakpoint(
+            id,
+            Hit::Opcode {
+                addr: 0x0601,
+                opcode: 0xc8
+            }
+        )
+    );
+}
+
+#[test]
+fn test_opcode_breakpoint_on_unofficial_opcode() {
+    let mut cpu = cpu_with_program(vec![0xe8, 0x02]);
+    let id = cpu.debugger_mut().add(Breakpoint::Opcode(0x02));
+
+    assert_eq!(
+        cpu.run(),
+        StopReason::Breakpoint(
+            id,
+            Hit::Opcode {
+                addr: 0x0601,
+                opcode: 0x02
+            }
+        )
+    );
+}
+
+#[test]
+fn test_condition_breakpoint() {
+    /*
+    loop:
+    INX
+    JMP loop
+     */
+    let mut cpu = cpu_with_program(vec![0xe8, 0x4c, 0x00, 0x06]);
+    let id = cpu
+        .debugger_mut()
+        .add("if:X == 5 && PC == $0600".parse().unwrap());
+
+    assert_eq!(
+        cpu.run(),
+        StopReason::Breakpoint(id, Hit::Condition(0x0600))
+    );
+    assert_eq!(cpu.registers().x, 5);
+}
+
+#[test]
+fn test_nmi_breakpoint_stops_on_handler() {
+    let bus = build_bus!(
+        pg_room[0xFFFA - PG_ROOM_START] = 0x00,
+        pg_room[0xFFFB - PG_ROOM_START] = 0x07
+    );
+    let mut cpu = CPU::new(bus);
+    cpu.load(vec![0xe8, 0x00]);
+    let id = cpu
+        .debugger_mut()
+        .add(Breakpoint::Interrupt(Interrupt::NMI));
+    cpu.request_interrupt(Interrupt::NMI);
+
+    assert_eq!(
+        cpu.run(),
+        StopReason::Breakpoint(id, Hit::Interrupt(Interrupt::NMI))
+    );
+    let registers = cpu.registers();
+    assert_eq!(registers.program_counter, 0x0700);
+    assert_eq!(registers.stack_pointer, 0xFC);
+    assert_eq!(cpu.read_from_memory(0x01FF), 0x06);
+    assert_eq!(cpu.read_from_memory(0x01FE), 0x00);
+    assert_eq!(cpu.read_from_memory(0x01FD), 0x24);
+}
+
+#[test]
+fn test_irq_is_masked_by_interrupt_disable() {
+    let mut cpu = cpu_with_program(vec![0xe8, 0x00]);
+    cpu.debugger_mut()
+        .add(Breakpoint::Interrupt(Interrupt::IRQ));
+    cpu.request_interrupt(Interrupt::IRQ);
+
+    // the cpu powers on with the I flag set
+    assert_eq!(cpu.run(), StopReason::Brk);
+}
+
+#[test]
+fn test_parse_breakpoints() {
+    assert_eq!("C000".parse(), Ok(Breakpoint::Execute(0xC000)));
+    assert_eq!("exec:$8000".parse(), Ok(Breakpoint::Execute(0x8000)));
+    assert_eq!(
+        "read:0200-02FF".parse(),
+        Ok(Breakpoint::Read(0x0200..=0x02FF))
+    );
+    assert_eq!("write:4014".parse(), Ok(Breakpoint::Write(0x4014..=0x4014)));
+    assert_eq!("opcode:00".parse(), Ok(Breakpoint::Opcode(0x00)));
+    assert_eq!("nmi".parse(), Ok(Breakpoint::Interrupt(Interrupt::NMI)));
+    assert_eq!("IRQ".parse(), Ok(Breakpoint::Interrupt(Interrupt::IRQ)));
+    assert!("jump:C000".parse::<Breakpoint>().is_err());
+}
+
+#[test]
+fn test_display_breakpoints() {
+    let breakpoint: Breakpoint = "if:[$10] & $80 != 0 || A > 3".parse().unwrap();
+    assert_eq!(breakpoint.to_string(), "if:[$10] & $80 != $0 || A > $3");
+    assert_eq!(
+        Breakpoint::Read(0x0200..=0x02FF).to_string(),
+        "read:0200-02FF"
+    );
+}
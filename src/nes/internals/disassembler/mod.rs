@@ -0,0 +1,130 @@
+use super::{
+    cpu::CPU,
+    opcodes::{AddressingMode, Opcodes},
+};
+
+#[cfg(test)]
+mod test;
+
+pub struct Instruction {
+    pub bytes: Vec<u8>,
+    /*
+      the instruction in assembly syntax,annotated with the effective address and the value stored on it.
+      e.g. "LDA ($89),Y = 0300 @ 0300 = 89"
+    */
+    pub text: String,
+}
+
+/*
+  decodes the instruction at addr using the current cpu registers to resolve the effective address.
+  memory is only peeked,so disassembling never changes the state of the machine
+*/
+pub fn disassemble(cpu: &CPU, addr: u16) -> Instruction {
+    let opcode_value = cpu.peek_memory(addr);
+    let opcode = match Opcodes::from_u8(opcode_value) {
+        Ok(opcode) => opcode,
+        Err(_) => {
+            return Instruction {
+                bytes: vec![opcode_value],
+                text: format!(".DB ${:02X}", opcode_value),
+            }
+        }
+    };
+
+    let registers = cpu.registers();
+    let peek = |addr: u16| cpu.peek_memory(addr);
+    let peek_le = |lo_addr: u16, hi_addr: u16| (peek(hi_addr) as u16) << 8 | peek(lo_addr) as u16;
+
+    let lo = peek(addr.wrapping_add(1));
+    let word = peek_le(addr.wrapping_add(1), addr.wrapping_add(2));
+
+    let (len, operand) = if opcode.is_branch() {
+        // the offset is relative to the address of the next instruction
+        let target = addr.wrapping_add(2).wrapping_add(lo as i8 as u16);
+        (2, format!("${:04X}", target))
+    } else {
+        match opcode.addressing_mode() {
+            None => (1, String::new()),
+            Some(AddressingMode::ACCUMULATOR) => (1, String::from("A")),
+            Some(AddressingMode::IMMEDIATE) => (2, format!("#${:02X}", lo)),
+            Some(AddressingMode::ZERO_PAGE) => {
+                (2, format!("${:02X} = {:02X}", lo, peek(lo as u16)))
+            }
+            Some(AddressingMode::ZERO_PAGE_X) => {
+                let effective = lo.wrapping_add(registers.x) as u16;
+                let value = peek(effective);
+                (
+                    2,
+                    format!("${:02X},X @ {:02X} = {:02X}", lo, effective, value),
+                )
+            }
+            Some(AddressingMode::ZERO_PAGE_Y) => {
+                let effective = lo.wrapping_add(registers.y) as u16;
+                let value = peek(effective);
+                (
+                    2,
+                    format!("${:02X},Y @ {:02X} = {:02X}", lo, effective, value),
+                )
+            }
+            Some(AddressingMode::ABSOLUTE) => match opcode {
+                Opcodes::JMP(_) | Opcodes::JSR(_) => (3, format!("${:04X}", word)),
+                _ => (3, format!("${:04X} = {:02X}", word, peek(word))),
+            },
+            Some(AddressingMode::ABSOLUTE_X) => {
+                let effective = word.wrapping_add(registers.x as u16);
+                let value = peek(effective);
+                (
+                    3,
+                    format!("${:04X},X @ {:04X} = {:02X}", word, effective, value),
+                )
+            }
+            Some(AddressingMode::ABSOLUTE_Y) => {
+                let effective = word.wrapping_add(registers.y as u16);
+                let value = peek(effective);
+                (
+                    3,
+                    format!("${:04X},Y @ {:04X} = {:02X}", word, effective, value),
+                )
+            }
+            Some(AddressingMode::INDIRECT) => {
+                // same page boundary bug as the cpu,the high byte never leaves the page of the pointer
+                let hi_addr = (word & 0xFF00) | (word.wrapping_add(1) & 0x00FF);
+                let target = peek_le(word, hi_addr);
+                (3, format!("(${:04X}) = {:04X}", word, target))
+            }
+            Some(AddressingMode::INDIRECT_X) => {
+                let ptr = lo.wrapping_add(registers.x);
+                let effective = peek_le(ptr as u16, ptr.wrapping_add(1) as u16);
+                let value = peek(effective);
+                (
+                    2,
+                    format!(
+                        "(${:02X},X) @ {:02X} = {:04X} = {:02X}",
+                        lo, ptr, effective, value
+                    ),
+                )
+            }
+            Some(AddressingMode::INDIRECT_Y) => {
+                let base = peek_le(lo as u16, lo.wrapping_add(1) as u16);
+                let effective = base.wrapping_add(registers.y as u16);
+                let value = peek(effective);
+                (
+                    2,
+                    format!(
+                        "(${:02X}),Y = {:04X} @ {:04X} = {:02X}",
+                        lo, base, effective, value
+                    ),
+                )
+            }
+        }
+    };
+
+    let bytes = (0..len).map(|i| peek(addr.wrapping_add(i))).collect();
+    let text = if operand.is_empty() {
+        String::from(opcode.mnemonic())
+    } else {
+        format!("{} {}", opcode.mnemonic(), operand)
+    };
+
+    Instruction { bytes, text }
+}
@@ -0,0 +1,70 @@
+use crate::nes::internals::{
+    bus::test::factory::cpu_with_program, disassembler::disassemble, memory::Memory,
+};
+
+#[test]
+fn test_disassemble_implied() {
+    let cpu = cpu_with_program(vec![0xe8]);
+    let instruction = disassemble(&cpu, 0x0600);
+    assert_eq!(instruction.text, "INX");
+    assert_eq!(instruction.bytes, vec![0xe8]);
+}
+
+#[test]
+fn test_disassemble_immediate() {
+    let cpu = cpu_with_program(vec![0xa9, 0x05]);
+    let instruction = disassemble(&cpu, 0x0600);
+    assert_eq!(instruction.text, "LDA #$05");
+    assert_eq!(instruction.bytes, vec![0xa9, 0x05]);
+}
+
+#[test]
+fn test_disassemble_zero_page_shows_value() {
+    let mut cpu = cpu_with_program(vec![0x85, 0x10]);
+    cpu.write_to_memory(0x10, 0x42);
+    let instruction = disassemble(&cpu, 0x0600);
+    assert_eq!(instruction.text, "STA $10 = 42");
+}
+
+#[test]
+fn test_disassemble_absolute_jump_has_no_value() {
+    let cpu = cpu_with_program(vec![0x4c, 0xf5, 0xc5]);
+    let instruction = disassemble(&cpu, 0x0600);
+    assert_eq!(instruction.text, "JMP $C5F5");
+    assert_eq!(instruction.bytes, vec![0x4c, 0xf5, 0xc5]);
+}
+
+#[test]
+fn test_disassemble_indirect_y() {
+    let mut cpu = cpu_with_program(vec![0xb1, 0x89]);
+    cpu.write_to_memory(0x89, 0x00);
+    cpu.write_to_memory(0x8a, 0x03);
+    cpu.write_to_memory(0x0300, 0x89);
+    let instruction = disassemble(&cpu, 0x0600);
+    assert_eq!(instruction.text, "LDA ($89),Y = 0300 @ 0300 = 89");
+}
+
+#[test]
+fn test_disassemble_indirect_jump_with_page_boundary_bug() {
+    let mut cpu = cpu_with_program(vec![0x6c, 0xff, 0x02]);
+    cpu.write_to_memory(0x02ff, 0x80);
+    cpu.write_to_memory(0x0200, 0x40);
+    cpu.write_to_memory(0x0300, 0x50);
+    let instruction = disassemble(&cpu, 0x0600);
+    assert_eq!(instruction.text, "JMP ($02FF) = 4080");
+}
+
+#[test]
+fn test_disassemble_branch_backwards() {
+    let cpu = cpu_with_program(vec![0xd0, 0xfe]);
+    let instruction = disassemble(&cpu, 0x0600);
+    assert_eq!(instruction.text, "BNE $0600");
+}
+
+#[test]
+fn test_disassemble_invalid_opcode() {
+    let cpu = cpu_with_program(vec![0xff]);
+    let instruction = disassemble(&cpu, 0x0600);
+    assert_eq!(instruction.text, ".DB $FF");
+    assert_eq!(instruction.bytes, vec![0xff]);
+}
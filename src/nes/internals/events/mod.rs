@@ -0,0 +1,197 @@
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use super::{
+    image::Image,
+    ppu::{DOTS_PER_SCANLINE, SCREEN_HEIGHT, SCREEN_WIDTH},
+    region::Region,
+};
+
+#[cfg(test)]
+mod test;
+
+// background of the event map,the picture,the blanking parts of the visible scanlines and vblank
+const PICTURE_COLOR: u32 = 0x404040;
+const HBLANK_COLOR: u32 = 0x282828;
+const VBLANK_COLOR: u32 = 0x101010;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventKind {
+    // $2000-$2007
+    PpuWrite,
+    // $4000-$4017
+    ApuWrite,
+    // $4020-$5FFF and $8000-$FFFF,where the mappers have their registers
+    MapperWrite,
+    Nmi,
+    Irq,
+    SpriteZeroHit,
+}
+
+impl EventKind {
+    // the kind of event a CPU write is,None for RAM and PRG-RAM
+    pub fn of_write(addr: u16) -> Option<EventKind> {
+        match addr {
+            0x2000..=0x3FFF => Some(EventKind::PpuWrite),
+            0x4000..=0x4017 => Some(EventKind::ApuWrite),
+            0x4020..=0x5FFF | 0x8000..=0xFFFF => Some(EventKind::MapperWrite),
+            _ => None,
+        }
+    }
+
+    // marker of the event in the map
+    pub fn color(&self) -> u32 {
+        match self {
+            EventKind::PpuWrite => 0x40A0FF,
+            EventKind::ApuWrite => 0xFFE040,
+            EventKind::MapperWrite => 0xC060FF,
+            EventKind::Nmi => 0xFF4040,
+            EventKind::Irq => 0xFF9020,
+            EventKind::SpriteZeroHit => 0x40FF60,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::PpuWrite => "ppu",
+            EventKind::ApuWrite => "apu",
+            EventKind::MapperWrite => "mapper",
+            EventKind::Nmi => "nmi",
+            EventKind::Irq => "irq",
+            EventKind::SpriteZeroHit => "sprite0",
+        }
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ppu" => Ok(EventKind::PpuWrite),
+            "apu" => Ok(EventKind::ApuWrite),
+            "mapper" => Ok(EventKind::MapperWrite),
+            "nmi" => Ok(EventKind::Nmi),
+            "irq" => Ok(EventKind::Irq),
+            "sprite0" => Ok(EventKind::SpriteZeroHit),
+            _ => Err(format!(
+                "Invalid event {},expected ppu, apu, mapper, nmi, irq or sprite0",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Event {
+    pub scanline: usize,
+    pub dot: usize,
+    pub kind: EventKind,
+    // address and value of the register writes
+    pub write: Option<(u16, u8)>,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>3} {:>3} {:<7}",
+            self.scanline,
+            self.dot,
+            self.kind.name()
+        )?;
+        if let Some((addr, value)) = self.write {
+            write!(f, " ${:04X} = ${:02X}", addr, value)?;
+        }
+        Ok(())
+    }
+}
+
+/*
+  the events of a frame with the dot where they happened,to see which part of the picture a raster effect hits.
+  the events of the frame being emulated are kept apart,only complete frames are shown
+*/
+#[derive(Default)]
+pub struct EventLog {
+    enabled: bool,
+    frame: usize,
+    current: Vec<Event>,
+    last: Vec<Event>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        EventLog::default()
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.current.clear();
+            self.last.clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // called with the frame the PPU is in,once it changes the frame being logged is complete
+    pub fn set_frame(&mut self, frame: usize) {
+        if frame == self.frame {
+            return;
+        }
+        if frame == self.frame + 1 {
+            self.last = std::mem::take(&mut self.current);
+        } else {
+            // a frame was missed,what was logged doesn't cover a whole frame
+            self.current.clear();
+            self.last.clear();
+        }
+        self.frame = frame;
+    }
+
+    pub fn record(&mut self, event: Event) {
+        if self.enabled {
+            self.current.push(event);
+        }
+    }
+
+    // events of the last complete frame,in the order they happened
+    pub fn events(&self) -> &[Event] {
+        &self.last
+    }
+
+    pub fn query(
+        &self,
+        kind: Option<EventKind>,
+        scanlines: RangeInclusive<usize>,
+    ) -> impl Iterator<Item = &Event> {
+        self.events().iter().filter(move |event| {
+            kind.is_none_or(|kind| event.kind == kind) && scanlines.contains(&event.scanline)
+        })
+    }
+
+    /*
+      one pixel per dot,341 wide and as tall as the frame of the region.
+      the frame starts at scanline 0,so the pre-render scanline is the last row
+    */
+    pub fn image(&self, region: Region) -> Image {
+        let scanlines = region.scanlines_per_frame();
+        let mut image = Image::new(DOTS_PER_SCANLINE, scanlines);
+        image.fill(0, 0, DOTS_PER_SCANLINE, SCREEN_HEIGHT, HBLANK_COLOR);
+        image.fill(1, 0, SCREEN_WIDTH, SCREEN_HEIGHT, PICTURE_COLOR);
+        image.fill(
+            0,
+            SCREEN_HEIGHT,
+            DOTS_PER_SCANLINE,
+            scanlines - SCREEN_HEIGHT,
+            VBLANK_COLOR,
+        );
+        for event in self.events() {
+            if event.scanline < scanlines && event.dot < DOTS_PER_SCANLINE {
+                image.set(event.dot, event.scanline, event.kind.color());
+            }
+        }
+        image
+    }
+}
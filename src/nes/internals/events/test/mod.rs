@@ -0,0 +1,120 @@
+use crate::nes::internals::{
+    bus::Bus,
+    cpu::CPU,
+    events::{Event, EventKind, EventLog},
+    region::Region,
+};
+
+fn write_event(scanline: usize, dot: usize, addr: u16, value: u8) -> Event {
+    Event {
+        scanline,
+        dot,
+        kind: EventKind::of_write(addr).unwrap(),
+        write: Some((addr, value)),
+    }
+}
+
+#[test]
+fn test_write_kinds() {
+    assert_eq!(EventKind::of_write(0x2005), Some(EventKind::PpuWrite));
+    assert_eq!(EventKind::of_write(0x3FFF), Some(EventKind::PpuWrite));
+    assert_eq!(EventKind::of_write(0x4014), Some(EventKind::ApuWrite));
+    assert_eq!(EventKind::of_write(0x8000), Some(EventKind::MapperWrite));
+    assert_eq!(EventKind::of_write(0x5000), Some(EventKind::MapperWrite));
+    assert_eq!(EventKind::of_write(0x0200), None);
+    assert_eq!(EventKind::of_write(0x6000), None);
+    assert_eq!(EventKind::of_write(0x4018), None);
+}
+
+#[test]
+fn test_only_complete_frames_are_shown() {
+    let mut log = EventLog::new();
+    log.set_enabled(true);
+    log.record(write_event(10, 20, 0x2001, 0x1E));
+    assert!(log.events().is_empty());
+
+    log.set_frame(1);
+    assert_eq!(log.events(), &[write_event(10, 20, 0x2001, 0x1E)]);
+
+    // skipping a frame drops what was logged
+    log.record(write_event(30, 40, 0x2005, 0x00));
+    log.set_frame(3);
+    assert!(log.events().is_empty());
+}
+
+#[test]
+fn test_disabled_log_records_nothing() {
+    let mut log = EventLog::new();
+    log.record(write_event(10, 20, 0x2001, 0x1E));
+    log.set_frame(1);
+    assert!(log.events().is_empty());
+}
+
+#[test]
+fn test_query() {
+    let mut log = EventLog::new();
+    log.set_enabled(true);
+    log.record(write_event(0, 5, 0x2006, 0x20));
+    log.record(write_event(100, 5, 0x4015, 0x0F));
+    log.record(write_event(120, 5, 0x2005, 0x08));
+    log.set_frame(1);
+
+    let ppu: Vec<_> = log.query(Some(EventKind::PpuWrite), 0..=261).collect();
+    assert_eq!(ppu.len(), 2);
+    let middle: Vec<_> = log.query(None, 50..=150).collect();
+    assert_eq!(middle.len(), 2);
+    assert_eq!(middle[0].write, Some((0x4015, 0x0F)));
+}
+
+#[test]
+fn test_image() {
+    let mut log = EventLog::new();
+    log.set_enabled(true);
+    log.record(write_event(120, 200, 0x2005, 0x08));
+    log.set_frame(1);
+
+    let image = log.image(Region::Ntsc);
+    assert_eq!((image.width, image.height), (341, 262));
+    assert_eq!(image.get(200, 120), EventKind::PpuWrite.color());
+    assert_ne!(image.get(201, 120), EventKind::PpuWrite.color());
+
+    let image = log.image(Region::Pal);
+    assert_eq!((image.width, image.height), (341, 312));
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(
+        write_event(0, 257, 0x2005, 0x00).to_string(),
+        "  0 257 ppu     $2005 = $00"
+    );
+}
+
+#[test]
+fn test_cpu_logs_nmi_and_writes() {
+    let mut bus = Bus::new();
+    bus.poke_memory(0xFFFA, 0x00);
+    bus.poke_memory(0xFFFB, 0x07);
+    bus.events_mut().set_enabled(true);
+    let mut cpu = CPU::new(bus);
+    /*
+    0600 LDA #$80
+    0602 STA $2000
+    0605 JMP $0605
+    0700 STA $2005
+    0703 RTI
+     */
+    cpu.load(vec![0xa9, 0x80, 0x8d, 0x00, 0x20, 0x4c, 0x05, 0x06]);
+    for (i, byte) in [0x8d, 0x05, 0x20, 0x40].iter().enumerate() {
+        cpu.poke_memory(0x0700 + i as u16, *byte);
+    }
+    cpu.run_while(|cpu| cpu.bus().frame_count() < 2);
+
+    let events = cpu.bus().events().events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].kind, EventKind::Nmi);
+    assert_eq!(events[0].scanline, 241);
+    assert_eq!(events[1].write, Some((0x2005, 0x80)));
+    assert_eq!(events[1].scanline, 241);
+    assert!(events[1].dot > events[0].dot);
+}
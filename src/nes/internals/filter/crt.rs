@@ -0,0 +1,32 @@
+use super::super::image::{scale, Image};
+
+pub const CRT_FACTOR: usize = 3;
+// the last row of every pixel is the dark gap between the scanlines of the tube
+const SCANLINE_GAP: f32 = 0.5;
+// an aperture grille has a stripe of each color,the other two colors are dimmed in it
+const GRILLE_DIM: f32 = 0.7;
+// brings back the light lost to the mask
+const BRIGHTNESS: f32 = 1.25;
+
+/*
+  looks like a CRT TV: every pixel becomes 3x3,the last row is a dark gap between scanlines
+  and each column is one of the red,green and blue stripes of an aperture grille
+*/
+pub fn crt(image: &Image) -> Image {
+    let mut output = Image::new(image.width * CRT_FACTOR, image.height * CRT_FACTOR);
+    for y in 0..output.height {
+        let row = if y % CRT_FACTOR == CRT_FACTOR - 1 {
+            SCANLINE_GAP
+        } else {
+            1.0
+        };
+        for x in 0..output.width {
+            let pixel = image.get((x / CRT_FACTOR) as isize, (y / CRT_FACTOR) as isize);
+            let mut channels = [GRILLE_DIM; 3];
+            channels[x % 3] = 1.0;
+            let [r, g, b] = channels.map(|channel| channel * row * BRIGHTNESS);
+            output.set(x, y, scale(pixel, r, g, b));
+        }
+    }
+    output
+}
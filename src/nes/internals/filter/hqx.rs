@@ -0,0 +1,54 @@
+use super::super::image::{is_different, mix, Image};
+
+/*
+  hqNx: every pixel becomes NxN,the subpixels in its corners are blended with the neighbours
+  the eye would see as the same shape.
+  like hqx it compares the colors in YUV,but instead of its big tables of cases
+  each corner is decided by its 3 neighbours:
+    the horizontal and vertical ones are alike and different from the center: an edge crosses the corner,
+    the subpixels blend towards them,more the closer they are to the corner.
+    only the diagonal one is different: the corner is softened a bit towards it
+*/
+pub fn hqx(image: &Image, factor: usize) -> Image {
+    let mut output = Image::new(image.width * factor, image.height * factor);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let (ix, iy) = (x as isize, y as isize);
+            let center = image.get(ix, iy);
+            for sy in 0..factor {
+                for sx in 0..factor {
+                    // position of the subpixel inside the pixel,from -1 to 1
+                    let fx = (2 * sx + 1) as f32 / factor as f32 - 1.0;
+                    let fy = (2 * sy + 1) as f32 / factor as f32 - 1.0;
+                    let color = if fx == 0.0 || fy == 0.0 {
+                        // the middle row and column of an odd factor don't touch a corner
+                        center
+                    } else {
+                        let (dx, dy) = (fx.signum() as isize, fy.signum() as isize);
+                        let horizontal = image.get(ix + dx, iy);
+                        let vertical = image.get(ix, iy + dy);
+                        let diagonal = image.get(ix + dx, iy + dy);
+                        corner(center, horizontal, vertical, diagonal, fx.abs() + fy.abs())
+                    };
+                    output.set(x * factor + sx, y * factor + sy, color);
+                }
+            }
+        }
+    }
+    output
+}
+
+// distance goes from 0 in the center of the pixel to 2 in its corner
+fn corner(center: u32, horizontal: u32, vertical: u32, diagonal: u32, distance: f32) -> u32 {
+    let weight = (distance - 0.5).clamp(0.0, 1.0);
+    if !is_different(horizontal, vertical) && is_different(center, horizontal) {
+        mix(center, mix(horizontal, vertical, 0.5), weight * 0.5)
+    } else if is_different(center, diagonal)
+        && !is_different(center, horizontal)
+        && !is_different(center, vertical)
+    {
+        mix(center, diagonal, weight * 0.25)
+    } else {
+        center
+    }
+}
@@ -0,0 +1,119 @@
+mod crt;
+mod hqx;
+pub mod ntsc;
+mod scale;
+mod xbr;
+
+#[cfg(test)]
+mod test;
+
+use std::str::FromStr;
+
+use super::{
+    image::Image,
+    palette::{NtscSettings, Palette},
+    ppu::{frame::Frame, SCREEN_HEIGHT, SCREEN_WIDTH},
+};
+pub use ntsc::NtscFilter;
+
+// the biggest picture a filter makes,hq4x
+pub const MAX_OUTPUT_SIZE: (usize, usize) = (SCREEN_WIDTH * 4, SCREEN_HEIGHT * 4);
+
+// names of the filters in the order the hotkey goes through them
+const NAMES: [&str; 9] = [
+    "none", "ntsc", "scale2x", "scale3x", "hq2x", "hq3x", "hq4x", "xbr", "crt",
+];
+
+// post-processing that turns the frames of the PPU into the picture shown on the screen
+#[derive(Default)]
+pub enum Filter {
+    // one RGB pixel per dot,straight from the palette
+    #[default]
+    None,
+    // simulates the composite video signal of a NTSC console,it ignores the palette
+    Ntsc(NtscFilter),
+    // pixel art scalers,they keep the edges sharp without blurring
+    Scale2x,
+    Scale3x,
+    Hq2x,
+    Hq3x,
+    Hq4x,
+    Xbr,
+    // scanlines and an aperture grille,3 times the size of the frame
+    Crt,
+}
+
+impl Filter {
+    // (width,height) of the pictures made by the filter
+    pub fn output_size(&self) -> (usize, usize) {
+        let factor = match self {
+            Filter::None => 1,
+            Filter::Ntsc(_) => return (ntsc::OUTPUT_WIDTH, SCREEN_HEIGHT),
+            Filter::Scale2x | Filter::Hq2x | Filter::Xbr => 2,
+            Filter::Scale3x | Filter::Hq3x => 3,
+            Filter::Hq4x => 4,
+            Filter::Crt => crt::CRT_FACTOR,
+        };
+        (SCREEN_WIDTH * factor, SCREEN_HEIGHT * factor)
+    }
+
+    pub fn name(&self) -> &'static str {
+        NAMES[self.index()]
+    }
+
+    // the filter after this one,it wraps around to no filter
+    pub fn next(&self) -> Filter {
+        let name = NAMES[(self.index() + 1) % NAMES.len()];
+        name.parse().unwrap()
+    }
+
+    // 3 bytes per pixel,red green blue
+    pub fn apply(&mut self, frame: &Frame, palette: &Palette) -> Vec<u8> {
+        let scaler = match self {
+            Filter::None => return frame.to_rgb24(palette),
+            Filter::Ntsc(filter) => return filter.apply(frame),
+            Filter::Scale2x => scale::scale2x,
+            Filter::Scale3x => scale::scale3x,
+            Filter::Hq2x => |image: &Image| hqx::hqx(image, 2),
+            Filter::Hq3x => |image: &Image| hqx::hqx(image, 3),
+            Filter::Hq4x => |image: &Image| hqx::hqx(image, 4),
+            Filter::Xbr => xbr::xbr2x,
+            Filter::Crt => crt::crt,
+        };
+        scaler(&Image::from_frame(frame, palette)).to_rgb24()
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Filter::None => 0,
+            Filter::Ntsc(_) => 1,
+            Filter::Scale2x => 2,
+            Filter::Scale3x => 3,
+            Filter::Hq2x => 4,
+            Filter::Hq3x => 5,
+            Filter::Hq4x => 6,
+            Filter::Xbr => 7,
+            Filter::Crt => 8,
+        }
+    }
+}
+
+// the filters by name,the NTSC one uses the default TV settings
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(Filter::None),
+            "ntsc" => Ok(Filter::Ntsc(NtscFilter::new(NtscSettings::default()))),
+            "scale2x" => Ok(Filter::Scale2x),
+            "scale3x" => Ok(Filter::Scale3x),
+            "hq2x" => Ok(Filter::Hq2x),
+            "hq3x" => Ok(Filter::Hq3x),
+            "hq4x" => Ok(Filter::Hq4x),
+            "xbr" => Ok(Filter::Xbr),
+            "crt" => Ok(Filter::Crt),
+            _ => Err(format!("Unknown filter {}", name)),
+        }
+    }
+}
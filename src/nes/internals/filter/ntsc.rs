@@ -0,0 +1,133 @@
+use super::super::{
+    palette::{
+        ntsc::{carrier, level, to_byte, yiq_to_rgb, PHASES},
+        NtscSettings, EMPHASIS_PALETTE_SIZE,
+    },
+    ppu::{frame::Frame, SCREEN_HEIGHT, SCREEN_WIDTH},
+};
+
+// the PPU outputs 8 samples of the signal for every dot,a subcarrier cycle is 12 of them
+const SAMPLES_PER_DOT: usize = 8;
+const SAMPLES_PER_SCANLINE: usize = SCREEN_WIDTH * SAMPLES_PER_DOT;
+// RGB pixels decoded from every dot
+const OUTPUT_PER_DOT: usize = 2;
+pub const OUTPUT_WIDTH: usize = SCREEN_WIDTH * OUTPUT_PER_DOT;
+const SAMPLES_PER_OUTPUT: usize = SAMPLES_PER_DOT / OUTPUT_PER_DOT;
+/*
+  the TV takes the brightness of a pixel from a whole subcarrier cycle around it,which removes the color from it,
+  and the color from 4 cycles,the color has a lot less bandwidth.
+  those windows are what blends the neighbour pixels
+*/
+const LUMA_WINDOW: usize = PHASES;
+const CHROMA_WINDOW: usize = PHASES * 4;
+// the black border around the picture,enough for the widest window
+const BORDER: usize = CHROMA_WINDOW / 2;
+// a scanline is 341 dots,so the next one starts 341 * 8 samples later,4 phases further in the subcarrier
+const SCANLINE_PHASE_SHIFT: usize = 4;
+/*
+  a frame is 262 scanlines,so the next one starts 4 phases later,
+  but odd frames are a dot shorter when rendering and shift it 8 phases,so frames alternate between 2 phases.
+  that is what makes the dot crawl
+*/
+const ODD_FRAME_PHASE: usize = 4;
+const GAMMA_TABLE_SIZE: usize = 4096;
+
+/*
+  decodes frames the way a TV decodes the composite signal of the console.
+  the colors of neighbour pixels bleed into each other,so dithered pixels blend,
+  thin lines get color fringes and the fringes crawl from frame to frame
+*/
+pub struct NtscFilter {
+    // signal level of every pixel at every phase
+    levels: Vec<[f32; PHASES]>,
+    // the subcarrier (cos,sin) at every phase
+    carrier: [(f32, f32); PHASES],
+    // linear RGB to bytes with the gamma of the settings
+    gamma: Vec<u8>,
+    settings: NtscSettings,
+    odd_frame: bool,
+}
+
+impl NtscFilter {
+    pub fn new(settings: NtscSettings) -> Self {
+        let levels = (0..EMPHASIS_PALETTE_SIZE as u16)
+            .map(|pixel| {
+                let mut levels = [0.0; PHASES];
+                for (phase, value) in levels.iter_mut().enumerate() {
+                    *value = level(pixel, phase) as f32;
+                }
+                levels
+            })
+            .collect();
+        let mut wave = [(0.0, 0.0); PHASES];
+        for (phase, value) in wave.iter_mut().enumerate() {
+            let (cos, sin) = carrier(phase, &settings);
+            *value = (cos as f32, sin as f32);
+        }
+        let gamma = (0..GAMMA_TABLE_SIZE)
+            .map(|i| to_byte(i as f64 / (GAMMA_TABLE_SIZE - 1) as f64, settings.gamma))
+            .collect();
+
+        NtscFilter {
+            levels,
+            carrier: wave,
+            gamma,
+            settings,
+            odd_frame: false,
+        }
+    }
+
+    /*
+      decodes a frame into OUTPUT_WIDTH x 240 RGB pixels.
+      every call is the next frame of the console,the phase of the signal changes between them
+    */
+    pub fn apply(&mut self, frame: &Frame) -> Vec<u8> {
+        let frame_phase = if self.odd_frame { ODD_FRAME_PHASE } else { 0 };
+        self.odd_frame = !self.odd_frame;
+
+        let mut rgb = Vec::with_capacity(OUTPUT_WIDTH * SCREEN_HEIGHT * 3);
+        // running sums of y,i and q over the scanline,a window is the difference of two of them
+        let mut sums = vec![(0.0f32, 0.0f32, 0.0f32); SAMPLES_PER_SCANLINE + BORDER * 2 + 1];
+        for y in 0..SCREEN_HEIGHT {
+            let line_phase = frame_phase + y * SCANLINE_PHASE_SHIFT;
+            let mut sum = (0.0, 0.0, 0.0);
+            for sample in 0..SAMPLES_PER_SCANLINE {
+                let pixel = frame.pixel(sample / SAMPLES_PER_DOT, y);
+                let phase = (line_phase + sample) % PHASES;
+                let level = self.levels[pixel as usize][phase];
+                let (cos, sin) = self.carrier[phase];
+                sum.0 += level;
+                sum.1 += level * cos;
+                sum.2 += level * sin;
+                sums[BORDER + sample + 1] = sum;
+            }
+            // the black border after the picture doesn't add anything
+            for value in sums[BORDER + SAMPLES_PER_SCANLINE + 1..].iter_mut() {
+                *value = sum;
+            }
+
+            for x in 0..OUTPUT_WIDTH {
+                // the windows are centered in the samples of the output pixel
+                let center = BORDER + x * SAMPLES_PER_OUTPUT + SAMPLES_PER_OUTPUT / 2;
+                let luma = sums[center + LUMA_WINDOW / 2].0 - sums[center - LUMA_WINDOW / 2].0;
+                let (start, end) = (
+                    sums[center - CHROMA_WINDOW / 2],
+                    sums[center + CHROMA_WINDOW / 2],
+                );
+                let (r, g, b) = yiq_to_rgb(
+                    (luma / LUMA_WINDOW as f32) as f64,
+                    ((end.1 - start.1) / CHROMA_WINDOW as f32) as f64,
+                    ((end.2 - start.2) / CHROMA_WINDOW as f32) as f64,
+                    &self.settings,
+                );
+                rgb.extend_from_slice(&[self.to_byte(r), self.to_byte(g), self.to_byte(b)]);
+            }
+        }
+        rgb
+    }
+
+    fn to_byte(&self, value: f64) -> u8 {
+        let index = value.clamp(0.0, 1.0) * (GAMMA_TABLE_SIZE - 1) as f64;
+        self.gamma[index.round() as usize]
+    }
+}
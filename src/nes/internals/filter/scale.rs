@@ -0,0 +1,75 @@
+use super::super::image::Image;
+
+/*
+  scale2x (EPX): every pixel becomes 2x2,a corner takes the color of its two neighbours
+  when they are the same and the other two are different.
+      A
+    C P B   ->   E0 E1
+      D          E2 E3
+*/
+pub fn scale2x(image: &Image) -> Image {
+    let mut output = Image::new(image.width * 2, image.height * 2);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let (ix, iy) = (x as isize, y as isize);
+            let p = image.get(ix, iy);
+            let a = image.get(ix, iy - 1);
+            let b = image.get(ix + 1, iy);
+            let c = image.get(ix - 1, iy);
+            let d = image.get(ix, iy + 1);
+
+            let corner = |first: u32, second: u32, other_first: u32, other_second: u32| {
+                if first == second && first != other_first && second != other_second {
+                    first
+                } else {
+                    p
+                }
+            };
+            output.set(x * 2, y * 2, corner(c, a, d, b));
+            output.set(x * 2 + 1, y * 2, corner(a, b, c, d));
+            output.set(x * 2, y * 2 + 1, corner(d, c, b, a));
+            output.set(x * 2 + 1, y * 2 + 1, corner(b, d, a, c));
+        }
+    }
+    output
+}
+
+/*
+  scale3x (AdvMAME3x),the same idea with 3x3 pixels.
+    A B C        E0 E1 E2
+    D E F   ->   E3 E4 E5
+    G H I        E6 E7 E8
+*/
+pub fn scale3x(image: &Image) -> Image {
+    let mut output = Image::new(image.width * 3, image.height * 3);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let (ix, iy) = (x as isize, y as isize);
+            let [a, b, c] = [-1, 0, 1].map(|dx| image.get(ix + dx, iy - 1));
+            let [d, e, f] = [-1, 0, 1].map(|dx| image.get(ix + dx, iy));
+            let [g, h, i] = [-1, 0, 1].map(|dx| image.get(ix + dx, iy + 1));
+
+            let top_left = d == b && b != f && d != h;
+            let top_right = b == f && b != d && f != h;
+            let bottom_left = d == h && d != b && h != f;
+            let bottom_right = h == f && d != h && b != f;
+
+            let pick = |condition: bool, color: u32| if condition { color } else { e };
+            let pixels = [
+                pick(top_left, d),
+                pick((top_left && e != c) || (top_right && e != a), b),
+                pick(top_right, f),
+                pick((top_left && e != g) || (bottom_left && e != a), d),
+                e,
+                pick((top_right && e != i) || (bottom_right && e != c), f),
+                pick(bottom_left, d),
+                pick((bottom_left && e != i) || (bottom_right && e != g), h),
+                pick(bottom_right, f),
+            ];
+            for (n, pixel) in pixels.into_iter().enumerate() {
+                output.set(x * 3 + n % 3, y * 3 + n / 3, pixel);
+            }
+        }
+    }
+    output
+}
@@ -0,0 +1,54 @@
+use crate::nes::internals::{filter::Filter, palette::Palette, ppu::frame::Frame};
+
+mod ntsc;
+mod scale;
+
+#[test]
+fn test_no_filter_uses_the_palette() {
+    let mut frame = Frame::new();
+    frame.set_pixel(3, 4, 0x16, 0);
+    let palette = Palette::default();
+    let mut filter = Filter::None;
+
+    assert_eq!(filter.output_size(), (256, 240));
+    assert_eq!(filter.apply(&frame, &palette), frame.to_rgb24(&palette));
+}
+
+#[test]
+fn test_parse_filter() {
+    assert!(matches!("none".parse(), Ok(Filter::None)));
+    let ntsc: Filter = "NTSC".parse().unwrap();
+    assert_eq!(ntsc.output_size(), (512, 240));
+    assert!("bilinear".parse::<Filter>().is_err());
+}
+
+#[test]
+fn test_scaler_output_sizes() {
+    let frame = Frame::new();
+    let palette = Palette::default();
+    for (name, size) in [
+        ("scale2x", (512, 480)),
+        ("scale3x", (768, 720)),
+        ("hq4x", (1024, 960)),
+        ("xbr", (512, 480)),
+        ("crt", (768, 720)),
+    ] {
+        let mut filter: Filter = name.parse().unwrap();
+        assert_eq!(filter.output_size(), size);
+        assert_eq!(filter.apply(&frame, &palette).len(), size.0 * size.1 * 3);
+    }
+}
+
+#[test]
+fn test_next_filter_goes_through_all_of_them() {
+    let mut filter = Filter::None;
+    let mut names = Vec::new();
+    for _ in 0..9 {
+        filter = filter.next();
+        names.push(filter.name());
+    }
+    assert_eq!(
+        names,
+        ["ntsc", "scale2x", "scale3x", "hq2x", "hq3x", "hq4x", "xbr", "crt", "none"]
+    );
+}
@@ -0,0 +1,74 @@
+use crate::nes::internals::{
+    filter::NtscFilter,
+    palette::{NtscSettings, Palette},
+    ppu::frame::Frame,
+};
+
+fn filled(color: u8) -> Frame {
+    let mut frame = Frame::new();
+    for y in 0..240 {
+        for x in 0..256 {
+            frame.set_pixel(x, y, color, 0);
+        }
+    }
+    frame
+}
+
+fn rgb(output: &[u8], x: usize, y: usize) -> (u8, u8, u8) {
+    let i = (y * 512 + x) * 3;
+    (output[i], output[i + 1], output[i + 2])
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    a.0.abs_diff(b.0) as u32 + a.1.abs_diff(b.1) as u32 + a.2.abs_diff(b.2) as u32
+}
+
+#[test]
+fn test_flat_color_matches_the_generated_palette() {
+    let settings = NtscSettings::default();
+    let palette = Palette::generate(&settings);
+    let mut filter = NtscFilter::new(settings);
+
+    for color in [0x0F, 0x16, 0x21, 0x30] {
+        let output = filter.apply(&filled(color));
+        assert_eq!(output.len(), 512 * 240 * 3);
+        // away from the borders every pixel sees a whole cycle of the same color
+        for (x, y) in [(100, 10), (255, 120), (400, 239)] {
+            assert!(distance(rgb(&output, x, y), palette.color(color as u16)) <= 3);
+        }
+    }
+}
+
+#[test]
+fn test_dithering_blends() {
+    let mut frame = Frame::new();
+    for y in 0..240 {
+        for x in 0..256 {
+            frame.set_pixel(x, y, if x % 2 == 0 { 0x0F } else { 0x30 }, 0);
+        }
+    }
+    let output = NtscFilter::new(NtscSettings::default()).apply(&frame);
+
+    // the black and white columns end up as two close grays,without any color
+    let black = rgb(&output, 200, 100);
+    let white = rgb(&output, 202, 100);
+    assert!(black.0 > 60 && white.0 < 200);
+    assert!(black.0 == black.1 && black.1 == black.2);
+}
+
+#[test]
+fn test_dot_crawl_alternates_between_frames() {
+    let mut frame = filled(0x0F);
+    for y in 0..240 {
+        frame.set_pixel(128, y, 0x30, 0);
+    }
+    let mut filter = NtscFilter::new(NtscSettings::default());
+    let first = filter.apply(&frame);
+    let second = filter.apply(&frame);
+    let third = filter.apply(&frame);
+
+    assert_ne!(first, second);
+    assert_eq!(first, third);
+    // the fringes change from scanline to scanline
+    assert_ne!(rgb(&first, 253, 0), rgb(&first, 253, 1));
+}
@@ -0,0 +1,109 @@
+use crate::nes::internals::{
+    filter::{
+        crt::crt,
+        hqx::hqx,
+        scale::{scale2x, scale3x},
+        xbr::xbr2x,
+    },
+    image::{unpack, Image},
+};
+
+const BLACK: u32 = 0x000000;
+const WHITE: u32 = 0xFFFFFF;
+
+fn image(width: usize, pixels: &[u32]) -> Image {
+    Image {
+        width,
+        height: pixels.len() / width,
+        pixels: pixels.to_vec(),
+    }
+}
+
+// a white triangle in the top left corner,its edge is a diagonal staircase
+fn staircase() -> Image {
+    image(
+        3,
+        &[
+            WHITE, WHITE, BLACK, //
+            WHITE, BLACK, BLACK, //
+            BLACK, BLACK, BLACK,
+        ],
+    )
+}
+
+#[test]
+fn test_scalers_keep_flat_images() {
+    let flat = image(4, &[0x123456; 16]);
+    for scaled in [
+        scale2x(&flat),
+        scale3x(&flat),
+        hqx(&flat, 2),
+        hqx(&flat, 3),
+        hqx(&flat, 4),
+        xbr2x(&flat),
+    ] {
+        assert!(scaled.pixels.iter().all(|pixel| *pixel == 0x123456));
+    }
+}
+
+#[test]
+fn test_scale2x_rounds_the_staircase() {
+    let scaled = scale2x(&staircase());
+    assert_eq!((scaled.width, scaled.height), (6, 6));
+
+    // the top left corner of the center pixel takes the color of the triangle
+    assert_eq!(scaled.get(2, 2), WHITE);
+    assert_eq!(scaled.get(3, 2), BLACK);
+    assert_eq!(scaled.get(2, 3), BLACK);
+    assert_eq!(scaled.get(3, 3), BLACK);
+}
+
+#[test]
+fn test_scale3x_rounds_the_staircase() {
+    let scaled = scale3x(&staircase());
+    assert_eq!((scaled.width, scaled.height), (9, 9));
+
+    assert_eq!(scaled.get(3, 3), WHITE);
+    assert_eq!(scaled.get(4, 4), BLACK);
+    assert_eq!(scaled.get(5, 5), BLACK);
+}
+
+#[test]
+fn test_hqx_blends_the_corners_of_an_edge() {
+    for factor in [2, 3, 4] {
+        let scaled = hqx(&staircase(), factor);
+        assert_eq!(scaled.width, 3 * factor);
+
+        let corner = scaled.get(factor as isize, factor as isize);
+        let (r, g, b) = unpack(corner);
+        assert!(
+            r > 0 && r < 255 && r == g && g == b,
+            "{}x {:06X}",
+            factor,
+            corner
+        );
+        // the opposite corner stays black
+        let last = (factor * 2 - 1) as isize;
+        assert_eq!(scaled.get(last, last), BLACK);
+    }
+}
+
+#[test]
+fn test_xbr_blends_the_corner_of_a_diagonal() {
+    let scaled = xbr2x(&staircase());
+    assert_eq!(scaled.get(2, 2), 0x808080);
+    assert_eq!(scaled.get(3, 3), BLACK);
+}
+
+#[test]
+fn test_crt_scanlines_and_grille() {
+    let scaled = crt(&image(1, &[0x808080]));
+    assert_eq!((scaled.width, scaled.height), (3, 3));
+
+    // each column is brighter in its own color
+    assert_eq!(unpack(scaled.get(0, 0)), (160, 112, 112));
+    assert_eq!(unpack(scaled.get(1, 0)), (112, 160, 112));
+    assert_eq!(unpack(scaled.get(2, 1)), (112, 112, 160));
+    // the last row is the gap between scanlines
+    assert_eq!(unpack(scaled.get(0, 2)), (80, 56, 56));
+}
@@ -0,0 +1,55 @@
+use super::super::image::{distance, mix, Image};
+
+/*
+  2xBR: every pixel becomes 2x2,a corner is blended with a neighbour when the edge through it
+  is closer to the diagonal than to the straight lines.
+  the pixels around the bottom right corner of E:
+          A1 B1 C1
+       A0 A  B  C  C4
+       D0 D  E  F  F4
+       G0 G  H  I  I4
+          G5 H5 I5
+  the other 3 corners are the same with the neighbourhood rotated
+*/
+pub fn xbr2x(image: &Image) -> Image {
+    let mut output = Image::new(image.width * 2, image.height * 2);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            for (corner_x, corner_y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                // directions from E towards the corner
+                let dx: isize = if corner_x == 1 { 1 } else { -1 };
+                let dy: isize = if corner_y == 1 { 1 } else { -1 };
+                // a pixel relative to E,with x pointing to the corner horizontally and y vertically
+                let at =
+                    |rx: isize, ry: isize| image.get(x as isize + rx * dx, y as isize + ry * dy);
+                let color = blend_corner(&at);
+                output.set(x * 2 + corner_x, y * 2 + corner_y, color);
+            }
+        }
+    }
+    output
+}
+
+fn blend_corner(at: &dyn Fn(isize, isize) -> u32) -> u32 {
+    let e = at(0, 0);
+    let (b, c, d) = (at(0, -1), at(1, -1), at(-1, 0));
+    let (f, g, h, i) = (at(1, 0), at(-1, 1), at(0, 1), at(1, 1));
+    let (f4, h5, i4, i5) = (at(2, 0), at(0, 2), at(2, 1), at(1, 2));
+
+    // weight of the edge going through the corner and of the one along it
+    let across =
+        distance(e, c) + distance(e, g) + distance(i, f4) + distance(i, h5) + 4.0 * distance(h, f);
+    let along =
+        distance(h, d) + distance(h, i5) + distance(f, i4) + distance(f, b) + 4.0 * distance(e, i);
+
+    if across < along && e != f && e != h {
+        let neighbour = if distance(e, f) <= distance(e, h) {
+            f
+        } else {
+            h
+        };
+        mix(e, neighbour, 0.5)
+    } else {
+        e
+    }
+}
@@ -0,0 +1,174 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use super::{palette::Palette, ppu::frame::Frame};
+
+#[cfg(test)]
+mod test;
+
+// the differences in YUV a pixel needs to be seen as a different color,the thresholds of hqx
+const Y_THRESHOLD: f32 = 48.0;
+const U_THRESHOLD: f32 = 7.0;
+const V_THRESHOLD: f32 = 6.0;
+
+// an RGB picture,every pixel is 0xRRGGBB.the scalers and the debug viewers draw on them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn from_frame(frame: &Frame, palette: &Palette) -> Self {
+        let pixels = frame
+            .pixels()
+            .iter()
+            .map(|pixel| {
+                let (r, g, b) = palette.color(*pixel);
+                pack(r, g, b)
+            })
+            .collect();
+        Image {
+            width: frame.width(),
+            height: frame.height(),
+            pixels,
+        }
+    }
+
+    // the pixels outside the picture repeat the ones in its edges
+    pub fn get(&self, x: isize, y: isize) -> u32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: u32) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    // copies another image with its top left corner at (x,y),whatever falls outside is cut
+    pub fn blit(&mut self, x: usize, y: usize, image: &Image) {
+        for row in 0..image.height.min(self.height.saturating_sub(y)) {
+            for column in 0..image.width.min(self.width.saturating_sub(x)) {
+                self.set(
+                    x + column,
+                    y + row,
+                    image.pixels[row * image.width + column],
+                );
+            }
+        }
+    }
+
+    // fills a w x h rectangle
+    pub fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, color: u32) {
+        for row in y..(y + h).min(self.height) {
+            for column in x..(x + w).min(self.width) {
+                self.set(column, row, color);
+            }
+        }
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        self.save_png_with_text(path, &[])
+    }
+
+    // the (keyword,text) pairs go in tEXt chunks,or iTXt when the text doesn't fit in latin-1
+    pub fn save_png_with_text<P: AsRef<Path>>(
+        &self,
+        path: P,
+        text: &[(&str, String)],
+    ) -> Result<(), String> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|err| format!("Could not create {}: {}", path.display(), err))?;
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        for (keyword, text) in text {
+            let latin1 = text.chars().all(|c| (c as u32) <= 0xFF);
+            let result = if latin1 {
+                encoder.add_text_chunk(keyword.to_string(), text.clone())
+            } else {
+                encoder.add_itxt_chunk(keyword.to_string(), text.clone())
+            };
+            result.map_err(|err| format!("Invalid text {}: {}", keyword, err))?;
+        }
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.to_rgb24()))
+            .map_err(|err| format!("Could not write {}: {}", path.display(), err))
+    }
+
+    // 3 bytes per pixel,red green blue
+    pub fn to_rgb24(&self) -> Vec<u8> {
+        let mut rgb = Vec::with_capacity(self.pixels.len() * 3);
+        for pixel in &self.pixels {
+            let (r, g, b) = unpack(*pixel);
+            rgb.extend_from_slice(&[r, g, b]);
+        }
+        rgb
+    }
+}
+
+pub fn pack(r: u8, g: u8, b: u8) -> u32 {
+    (r as u32) << 16 | (g as u32) << 8 | b as u32
+}
+
+pub fn unpack(color: u32) -> (u8, u8, u8) {
+    ((color >> 16) as u8, (color >> 8) as u8, color as u8)
+}
+
+// a * (1 - amount) + b * amount,channel by channel
+pub fn mix(a: u32, b: u32, amount: f32) -> u32 {
+    let (a, b) = (unpack(a), unpack(b));
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    pack(channel(a.0, b.0), channel(a.1, b.1), channel(a.2, b.2))
+}
+
+// scales every channel,values above 1 saturate
+pub fn scale(color: u32, r: f32, g: f32, b: f32) -> u32 {
+    let (red, green, blue) = unpack(color);
+    let channel = |value: u8, factor: f32| (value as f32 * factor).round().min(255.0) as u8;
+    pack(channel(red, r), channel(green, g), channel(blue, b))
+}
+
+fn yuv(color: u32) -> (f32, f32, f32) {
+    let (r, g, b) = unpack(color);
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    (
+        0.299 * r + 0.587 * g + 0.114 * b,
+        -0.169 * r - 0.331 * g + 0.5 * b,
+        0.5 * r - 0.419 * g - 0.081 * b,
+    )
+}
+
+// true when the eye sees two colors as different,they are compared in YUV like hqx does
+pub fn is_different(a: u32, b: u32) -> bool {
+    if a == b {
+        return false;
+    }
+    let (a, b) = (yuv(a), yuv(b));
+    (a.0 - b.0).abs() > Y_THRESHOLD
+        || (a.1 - b.1).abs() > U_THRESHOLD
+        || (a.2 - b.2).abs() > V_THRESHOLD
+}
+
+// how far two colors are,weighting YUV the way xBR does
+pub fn distance(a: u32, b: u32) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+    let (a, b) = (yuv(a), yuv(b));
+    (a.0 - b.0).abs() * Y_THRESHOLD
+        + (a.1 - b.1).abs() * U_THRESHOLD
+        + (a.2 - b.2).abs() * V_THRESHOLD
+}
@@ -0,0 +1,66 @@
+use crate::nes::internals::image::{mix, pack, unpack, Image};
+
+#[test]
+fn test_pack_and_mix() {
+    assert_eq!(pack(0x12, 0x34, 0x56), 0x123456);
+    assert_eq!(unpack(0x123456), (0x12, 0x34, 0x56));
+    assert_eq!(mix(0x000000, 0xFF8040, 0.5), 0x804020);
+}
+
+#[test]
+fn test_blit_cuts_at_the_edges() {
+    let mut image = Image::new(4, 4);
+    let mut square = Image::new(3, 3);
+    square.fill(0, 0, 3, 3, 0xFFFFFF);
+    image.blit(2, 2, &square);
+
+    assert_eq!(image.get(1, 1), 0);
+    assert_eq!(image.get(2, 2), 0xFFFFFF);
+    assert_eq!(image.get(3, 3), 0xFFFFFF);
+    assert_eq!(image.pixels.iter().filter(|pixel| **pixel != 0).count(), 4);
+}
+
+#[test]
+fn test_save_png() {
+    let mut image = Image::new(3, 2);
+    image.set(1, 1, 0x123456);
+    let path = std::env::temp_dir().join("image-test.png");
+    image.save_png(&path).unwrap();
+
+    let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!((info.width, info.height), (3, 2));
+    assert_eq!(&pixels[12..15], &[0x12, 0x34, 0x56]);
+}
+
+#[test]
+fn test_save_png_with_text() {
+    let image = Image::new(1, 1);
+    let path = std::env::temp_dir().join("image-test-text.png");
+    let text = [
+        ("Frame", "120".to_string()),
+        ("ROM", "ドラゴン".to_string()),
+    ];
+    image.save_png_with_text(&path, &text).unwrap();
+
+    let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut pixels).unwrap();
+    reader.finish().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let info = reader.info();
+    let latin1 = &info.uncompressed_latin1_text[0];
+    assert_eq!(
+        (latin1.keyword.as_str(), latin1.text.as_str()),
+        ("Frame", "120")
+    );
+    let utf8 = &info.utf8_text[0];
+    assert_eq!(utf8.keyword, "ROM");
+    assert_eq!(utf8.get_text().unwrap(), "ドラゴン");
+}
@@ -2,11 +2,11 @@
 mod test;
 
 pub trait Memory {
-    fn read_from_memory(&self, addr: u16) -> u8;
+    fn read_from_memory(&mut self, addr: u16) -> u8;
 
     fn write_to_memory(&mut self, addr: u16, data: u8);
 
-    fn read_from_memory_le(&self, addr: u16) -> u16 {
+    fn read_from_memory_le(&mut self, addr: u16) -> u16 {
         let lo = self.read_from_memory(addr) as u16;
         let hi = self.read_from_memory(addr + 1) as u16;
         /*hi << 8 moves the value of the first half of this 16bit data to the second half
@@ -5,7 +5,7 @@ struct Cpu {
 }
 
 impl Memory for Cpu {
-    fn read_from_memory(&self, addr: u16) -> u8 {
+    fn read_from_memory(&mut self, addr: u16) -> u8 {
         return self.memory[addr as usize];
     }
     fn write_to_memory(&mut self, addr: u16, data: u8) {
@@ -18,7 +18,7 @@ fn test_read_from_memory_le() {
     let mut memory: [u8; 0xFFFF] = [0; 0xFFFF];
     memory[0x1234] = 0x56;
     memory[0x1235] = 0x78;
-    let cpu = Cpu { memory };
+    let mut cpu = Cpu { memory };
     assert_eq!(cpu.read_from_memory_le(0x1234), 0x7856);
 }
 
@@ -1,4 +1,16 @@
 pub mod bus;
+pub mod cartridge;
 pub mod cpu;
+pub mod debugger;
+pub mod disassembler;
+pub mod events;
+pub mod filter;
+pub mod image;
 pub mod memory;
 mod opcodes;
+pub mod palette;
+pub mod ppu;
+pub mod ram_pattern;
+pub mod recorder;
+pub mod region;
+pub mod trace;
@@ -34,6 +34,31 @@ impl AddressingMode {
     }
 }
 
+/*
+  base amount of cycles each opcode takes, indexed by the opcode value.
+  page crossing and taken branches add extra cycles on top of it
+*/
+#[rustfmt::skip]
+pub const CYCLES: [u8; 256] = [
+//  0  1  2  3  4  5  6  7  8  9  A  B  C  D  E  F
+    7, 6, 2, 8, 3, 3, 5, 5, 3, 2, 2, 2, 4, 4, 6, 6, // 0
+    2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 1
+    6, 6, 2, 8, 3, 3, 5, 5, 4, 2, 2, 2, 4, 4, 6, 6, // 2
+    2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 3
+    6, 6, 2, 8, 3, 3, 5, 5, 3, 2, 2, 2, 3, 4, 6, 6, // 4
+    2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 5
+    6, 6, 2, 8, 3, 3, 5, 5, 4, 2, 2, 2, 5, 4, 6, 6, // 6
+    2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 7
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4, // 8
+    2, 6, 2, 6, 4, 4, 4, 4, 2, 5, 2, 5, 5, 5, 5, 5, // 9
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4, // A
+    2, 5, 2, 5, 4, 4, 4, 4, 2, 4, 2, 4, 4, 4, 4, 4, // B
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6, // C
+    2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // D
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6, // E
+    2, 5, 2, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // F
+];
+
 #[derive(Debug, PartialEq)]
 pub enum Opcodes {
     ADC(AddressingMode),
@@ -301,4 +326,124 @@ impl Opcodes {
             _ => Err(()),
         }
     }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::ADC(_) => "ADC",
+            Self::AND(_) => "AND",
+            Self::ASL(_) => "ASL",
+            Self::BCC => "BCC",
+            Self::BCS => "BCS",
+            Self::BEQ => "BEQ",
+            Self::BIT(_) => "BIT",
+            Self::BMI => "BMI",
+            Self::BNE => "BNE",
+            Self::BPL => "BPL",
+            Self::BRK => "BRK",
+            Self::BVC => "BVC",
+            Self::BVS => "BVS",
+            Self::CLC => "CLC",
+            Self::CLD => "CLD",
+            Self::CLI => "CLI",
+            Self::CLV => "CLV",
+            Self::CMP(_) => "CMP",
+            Self::CPX(_) => "CPX",
+            Self::CPY(_) => "CPY",
+            Self::DEC(_) => "DEC",
+            Self::DEX => "DEX",
+            Self::DEY => "DEY",
+            Self::EOR(_) => "EOR",
+            Self::INC(_) => "INC",
+            Self::INX => "INX",
+            Self::INY => "INY",
+            Self::JMP(_) => "JMP",
+            Self::JSR(_) => "JSR",
+            Self::LDA(_) => "LDA",
+            Self::LDX(_) => "LDX",
+            Self::LDY(_) => "LDY",
+            Self::LSR(_) => "LSR",
+            Self::NOP => "NOP",
+            Self::ORA(_) => "ORA",
+            Self::PHA => "PHA",
+            Self::PHP => "PHP",
+            Self::PLA => "PLA",
+            Self::PLP => "PLP",
+            Self::ROL(_) => "ROL",
+            Self::ROR(_) => "ROR",
+            Self::RTI => "RTI",
+            Self::RTS => "RTS",
+            Self::SBC(_) => "SBC",
+            Self::SEC => "SEC",
+            Self::SED => "SED",
+            Self::SEI => "SEI",
+            Self::STA(_) => "STA",
+            Self::STX(_) => "STX",
+            Self::STY(_) => "STY",
+            Self::TAX => "TAX",
+            Self::TAY => "TAY",
+            Self::TSX => "TSX",
+            Self::TXA => "TXA",
+            Self::TXS => "TXS",
+            Self::TYA => "TYA",
+        }
+    }
+
+    pub fn addressing_mode(&self) -> Option<&AddressingMode> {
+        match self {
+            Self::ADC(mode)
+            | Self::AND(mode)
+            | Self::ASL(mode)
+            | Self::BIT(mode)
+            | Self::CMP(mode)
+            | Self::CPX(mode)
+            | Self::CPY(mode)
+            | Self::DEC(mode)
+            | Self::EOR(mode)
+            | Self::INC(mode)
+            | Self::JMP(mode)
+            | Self::JSR(mode)
+            | Self::LDA(mode)
+            | Self::LDX(mode)
+            | Self::LDY(mode)
+            | Self::LSR(mode)
+            | Self::ORA(mode)
+            | Self::ROL(mode)
+            | Self::ROR(mode)
+            | Self::SBC(mode)
+            | Self::STA(mode)
+            | Self::STX(mode)
+            | Self::STY(mode) => Some(mode),
+            _ => None,
+        }
+    }
+
+    pub fn is_branch(&self) -> bool {
+        matches!(
+            self,
+            Self::BCC
+                | Self::BCS
+                | Self::BEQ
+                | Self::BMI
+                | Self::BNE
+                | Self::BPL
+                | Self::BVC
+                | Self::BVS
+        )
+    }
+
+    //only the instructions that just read from memory take an extra cycle when the indexed address crosses a page
+    pub fn has_page_cross_penalty(&self) -> bool {
+        matches!(
+            self,
+            Self::ADC(_)
+                | Self::AND(_)
+                | Self::CMP(_)
+                | Self::EOR(_)
+                | Self::LDA(_)
+                | Self::LDX(_)
+                | Self::LDY(_)
+                | Self::ORA(_)
+                | Self::SBC(_)
+        )
+    }
 }
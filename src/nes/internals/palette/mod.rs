@@ -0,0 +1,127 @@
+pub mod ntsc;
+
+#[cfg(test)]
+mod test;
+
+use std::path::Path;
+
+pub use ntsc::NtscSettings;
+
+pub const PALETTE_SIZE: usize = 64;
+// a palette with a variant of every color for each combination of the 3 emphasis bits
+pub const EMPHASIS_PALETTE_SIZE: usize = PALETTE_SIZE * 8;
+const EMPHASIS_COLOR_MASK: u16 = 0x1FF;
+// an emphasis bit attenuates the other two color channels to this fraction
+const EMPHASIS_ATTENUATION: f64 = 0.746;
+
+// the colors of the 2C02,as captured by most emulators
+#[rustfmt::skip]
+pub const DEFAULT_COLORS: [(u8, u8, u8); PALETTE_SIZE] = [
+   (0x80, 0x80, 0x80), (0x00, 0x3D, 0xA6), (0x00, 0x12, 0xB0), (0x44, 0x00, 0x96), (0xA1, 0x00, 0x5E),
+   (0xC7, 0x00, 0x28), (0xBA, 0x06, 0x00), (0x8C, 0x17, 0x00), (0x5C, 0x2F, 0x00), (0x10, 0x45, 0x00),
+   (0x05, 0x4A, 0x00), (0x00, 0x47, 0x2E), (0x00, 0x41, 0x66), (0x00, 0x00, 0x00), (0x05, 0x05, 0x05),
+   (0x05, 0x05, 0x05), (0xC7, 0xC7, 0xC7), (0x00, 0x77, 0xFF), (0x21, 0x55, 0xFF), (0x82, 0x37, 0xFA),
+   (0xEB, 0x2F, 0xB5), (0xFF, 0x29, 0x50), (0xFF, 0x22, 0x00), (0xD6, 0x32, 0x00), (0xC4, 0x62, 0x00),
+   (0x35, 0x80, 0x00), (0x05, 0x8F, 0x00), (0x00, 0x8A, 0x55), (0x00, 0x99, 0xCC), (0x21, 0x21, 0x21),
+   (0x09, 0x09, 0x09), (0x09, 0x09, 0x09), (0xFF, 0xFF, 0xFF), (0x0F, 0xD7, 0xFF), (0x69, 0xA2, 0xFF),
+   (0xD4, 0x80, 0xFF), (0xFF, 0x45, 0xF3), (0xFF, 0x61, 0x8B), (0xFF, 0x88, 0x33), (0xFF, 0x9C, 0x12),
+   (0xFA, 0xBC, 0x20), (0x9F, 0xE3, 0x0E), (0x2B, 0xF0, 0x35), (0x0C, 0xF0, 0xA4), (0x05, 0xFB, 0xFF),
+   (0x5E, 0x5E, 0x5E), (0x0D, 0x0D, 0x0D), (0x0D, 0x0D, 0x0D), (0xFF, 0xFF, 0xFF), (0xA6, 0xFC, 0xFF),
+   (0xB3, 0xEC, 0xFF), (0xDA, 0xAB, 0xEB), (0xFF, 0xA8, 0xF9), (0xFF, 0xAB, 0xB3), (0xFF, 0xD2, 0xB0),
+   (0xFF, 0xEF, 0xA6), (0xFF, 0xF7, 0x9C), (0xD7, 0xE8, 0x95), (0xA6, 0xED, 0xAF), (0xA2, 0xF2, 0xDA),
+   (0x99, 0xFF, 0xFC), (0xDD, 0xDD, 0xDD), (0x11, 0x11, 0x11), (0x11, 0x11, 0x11),
+];
+
+/*
+  maps the pixels of a frame to RGB colors.
+  it always has the 512 entries,the 64 colors for each combination of the emphasis bits
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: Vec<(u8, u8, u8)>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            colors: with_emphasis(&DEFAULT_COLORS),
+        }
+    }
+}
+
+impl Palette {
+    /*
+      parses a .pal file,a list of RGB triplets.
+      192 bytes have the 64 colors,1536 bytes also have the 7 emphasis variants after them.
+      the variants missing from a 192 bytes file are computed from its colors
+    */
+    pub fn from_pal(raw: &[u8]) -> Result<Palette, String> {
+        if raw.len() != PALETTE_SIZE * 3 && raw.len() != EMPHASIS_PALETTE_SIZE * 3 {
+            return Err(format!(
+                "Palette must have {} or {} bytes,found {}",
+                PALETTE_SIZE * 3,
+                EMPHASIS_PALETTE_SIZE * 3,
+                raw.len()
+            ));
+        }
+        let colors: Vec<(u8, u8, u8)> = raw
+            .chunks(3)
+            .map(|color| (color[0], color[1], color[2]))
+            .collect();
+        if colors.len() == PALETTE_SIZE {
+            return Ok(Palette {
+                colors: with_emphasis(&colors),
+            });
+        }
+        Ok(Palette { colors })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Palette, String> {
+        let path = path.as_ref();
+        let raw = std::fs::read(path)
+            .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+        Palette::from_pal(&raw)
+    }
+
+    // computes the colors from the composite signal the PPU outputs
+    pub fn generate(settings: &NtscSettings) -> Palette {
+        let colors = (0..EMPHASIS_PALETTE_SIZE as u16)
+            .map(|pixel| ntsc::decode(pixel, settings))
+            .collect();
+        Palette { colors }
+    }
+
+    pub fn color(&self, pixel: u16) -> (u8, u8, u8) {
+        self.colors[(pixel & EMPHASIS_COLOR_MASK) as usize]
+    }
+}
+
+/*
+  builds the emphasis variants of 64 RGB colors.
+  emphasizing red darkens green and blue and so on,the blacks in columns $xE and $xF don't change
+*/
+fn with_emphasis(colors: &[(u8, u8, u8)]) -> Vec<(u8, u8, u8)> {
+    let attenuate = |channel: u8, attenuated: bool| {
+        if attenuated {
+            (channel as f64 * EMPHASIS_ATTENUATION).round() as u8
+        } else {
+            channel
+        }
+    };
+    let mut extended = Vec::with_capacity(EMPHASIS_PALETTE_SIZE);
+    for emphasis in 0..8u8 {
+        let (red, green, blue) = (emphasis & 1 != 0, emphasis & 2 != 0, emphasis & 4 != 0);
+        for (index, &(r, g, b)) in colors.iter().enumerate() {
+            if index & 0x0E == 0x0E {
+                extended.push((r, g, b));
+                continue;
+            }
+            extended.push((
+                attenuate(r, green || blue),
+                attenuate(g, red || blue),
+                attenuate(b, red || green),
+            ));
+        }
+    }
+    extended
+}
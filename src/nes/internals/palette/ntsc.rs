@@ -0,0 +1,149 @@
+use std::{f64::consts::PI, str::FromStr};
+
+use super::EMPHASIS_ATTENUATION;
+
+/*
+  voltage levels of the PPU video signal,relative to sync.
+  each color is a square wave between a low and a high level,the luma selects the pair
+*/
+const SIGNAL_LOW: [f64; 4] = [0.350, 0.518, 0.962, 1.550];
+const SIGNAL_HIGH: [f64; 4] = [1.094, 1.506, 1.962, 1.962];
+const BLACK: f64 = 0.518;
+const WHITE: f64 = 1.962;
+// the color subcarrier takes 12 PPU master clock phases
+pub const PHASES: usize = 12;
+// lines the decoder up with the colorburst so $x2 comes out blue and $x6 red
+const PHASE_OFFSET: f64 = 3.9;
+
+// knobs of the palette generator,they behave like the ones of a TV set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NtscSettings {
+    // rotation of every hue,in degrees
+    pub hue: f64,
+    pub saturation: f64,
+    pub contrast: f64,
+    pub brightness: f64,
+    // gamma of the display,2.2 leaves the signal untouched
+    pub gamma: f64,
+}
+
+impl Default for NtscSettings {
+    fn default() -> Self {
+        NtscSettings {
+            hue: 0.0,
+            saturation: 1.0,
+            contrast: 1.0,
+            brightness: 0.0,
+            gamma: 2.2,
+        }
+    }
+}
+
+// parses "hue=10,saturation=1.2",the missing settings keep their default
+impl FromStr for NtscSettings {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut settings = NtscSettings::default();
+        for setting in spec.split(',').filter(|setting| !setting.trim().is_empty()) {
+            let (name, value) = setting
+                .split_once('=')
+                .ok_or(format!("Invalid palette setting {}", setting))?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid value {}", value))?;
+            match name.trim() {
+                "hue" => settings.hue = value,
+                "saturation" => settings.saturation = value,
+                "contrast" => settings.contrast = value,
+                "brightness" => settings.brightness = value,
+                "gamma" if value > 0.0 => settings.gamma = value,
+                _ => return Err(format!("Invalid palette setting {}", setting)),
+            }
+        }
+        Ok(settings)
+    }
+}
+
+// voltage of the signal of a pixel ("...EEELLCCCC") at one of the 12 phases
+pub fn signal(pixel: u16, phase: usize) -> f64 {
+    let color = (pixel & 0x0F) as usize;
+    let mut level = ((pixel >> 4) & 0b11) as usize;
+    let emphasis = (pixel >> 6) & 0b111;
+
+    // colors $xE and $xF are black
+    if color > 13 {
+        level = 1;
+    }
+    let mut low = SIGNAL_LOW[level];
+    let mut high = SIGNAL_HIGH[level];
+    // color $x0 is a flat high level and $xD a flat low one
+    if color == 0 {
+        low = high;
+    }
+    if color > 12 {
+        high = low;
+    }
+
+    let in_color_phase = |color: usize| (color + phase) % PHASES < PHASES / 2;
+    let mut signal = if in_color_phase(color) { high } else { low };
+    // red,green and blue emphasis attenuate the signal in the phases of colors 0,4 and 8,but not the blacks
+    let emphasized = (emphasis & 0b001 != 0 && in_color_phase(0))
+        || (emphasis & 0b010 != 0 && in_color_phase(4))
+        || (emphasis & 0b100 != 0 && in_color_phase(8));
+    if emphasized && color < 14 {
+        signal *= EMPHASIS_ATTENUATION;
+    }
+    signal
+}
+
+// decodes the signal of a pixel the way a TV would,through YIQ
+pub fn decode(pixel: u16, settings: &NtscSettings) -> (u8, u8, u8) {
+    let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+    for phase in 0..PHASES {
+        let level = level(pixel, phase);
+        let (cos, sin) = carrier(phase, settings);
+        y += level;
+        i += level * cos;
+        q += level * sin;
+    }
+
+    let phases = PHASES as f64;
+    let (r, g, b) = yiq_to_rgb(y / phases, i / phases, q / phases, settings);
+    (
+        to_byte(r, settings.gamma),
+        to_byte(g, settings.gamma),
+        to_byte(b, settings.gamma),
+    )
+}
+
+// the signal of a pixel scaled so black is 0 and white is 1
+pub fn level(pixel: u16, phase: usize) -> f64 {
+    (signal(pixel, phase) - BLACK) / (WHITE - BLACK)
+}
+
+// cosine and sine of the color subcarrier the TV demodulates with,at one of the 12 phases
+pub fn carrier(phase: usize, settings: &NtscSettings) -> (f64, f64) {
+    let hue = settings.hue / 30.0; // 12 phases in 360 degrees
+    let angle = PI * (phase as f64 + PHASE_OFFSET + hue) / 6.0;
+    (angle.cos(), angle.sin())
+}
+
+// applies the TV knobs to a demodulated YIQ color,the result is linear RGB between 0 and 1
+pub fn yiq_to_rgb(y: f64, i: f64, q: f64, settings: &NtscSettings) -> (f64, f64, f64) {
+    let y = y * settings.contrast + settings.brightness;
+    let i = i * settings.saturation * 2.0;
+    let q = q * settings.saturation * 2.0;
+
+    (
+        y + 0.956 * i + 0.621 * q,
+        y - 0.272 * i - 0.647 * q,
+        y - 1.106 * i + 1.703 * q,
+    )
+}
+
+pub fn to_byte(value: f64, gamma: f64) -> u8 {
+    let value = value.clamp(0.0, 1.0).powf(2.2 / gamma);
+    (value * 255.0).round() as u8
+}
@@ -0,0 +1,62 @@
+use crate::nes::internals::palette::{NtscSettings, Palette};
+
+mod ntsc;
+
+#[test]
+fn test_default_palette() {
+    let palette = Palette::default();
+    assert_eq!(palette.color(0x00), (0x80, 0x80, 0x80));
+    assert_eq!(palette.color(0x0F), (0x05, 0x05, 0x05));
+    assert_eq!(palette.color(0x3F), (0x11, 0x11, 0x11));
+}
+
+#[test]
+fn test_default_palette_has_emphasis() {
+    let palette = Palette::default();
+    // red emphasis darkens green and blue
+    assert_eq!(palette.color(0b001_110000), (0xFF, 0xBE, 0xBE));
+    assert_eq!(palette.color(0b111_110000), (0xBE, 0xBE, 0xBE));
+    // the blacks don't change
+    assert_eq!(palette.color(0b111_001111), palette.color(0x0F));
+}
+
+#[test]
+fn test_load_64_color_pal() {
+    let mut raw = vec![0; 192];
+    raw[0x16 * 3..0x16 * 3 + 3].copy_from_slice(&[0xB5, 0x31, 0x20]);
+    let palette = Palette::from_pal(&raw).unwrap();
+
+    assert_eq!(palette.color(0x16), (0xB5, 0x31, 0x20));
+    // the emphasis variants are computed
+    assert_eq!(palette.color(0b100_010110), (0x87, 0x25, 0x20));
+}
+
+#[test]
+fn test_load_pal_with_emphasis() {
+    let mut raw = vec![0; 1536];
+    raw[0x16 * 3] = 0x10;
+    raw[(0x40 + 0x16) * 3] = 0x20;
+    raw[(0x1C0 + 0x16) * 3] = 0x30;
+    let palette = Palette::from_pal(&raw).unwrap();
+
+    assert_eq!(palette.color(0x16).0, 0x10);
+    assert_eq!(palette.color(0b001_010110).0, 0x20);
+    assert_eq!(palette.color(0b111_010110).0, 0x30);
+}
+
+#[test]
+fn test_load_pal_with_invalid_size() {
+    assert!(Palette::from_pal(&[0; 191]).is_err());
+    assert!(Palette::from_pal(&[0; 1535]).is_err());
+}
+
+#[test]
+fn test_load_missing_file() {
+    assert!(Palette::load("/this/file/does/not/exist.pal").is_err());
+}
+
+#[test]
+fn test_generated_palette_has_emphasis() {
+    let palette = Palette::generate(&NtscSettings::default());
+    assert_ne!(palette.color(0x16), palette.color(0b100_010110));
+}
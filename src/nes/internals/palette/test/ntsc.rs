@@ -0,0 +1,81 @@
+use crate::nes::internals::palette::{ntsc::signal, NtscSettings, Palette};
+
+fn generate(spec: &str) -> Palette {
+    Palette::generate(&spec.parse().unwrap())
+}
+
+#[test]
+fn test_signal_levels() {
+    // color $x0 is flat,$xD and above only use the low level
+    assert!((0..12).all(|phase| signal(0x10, phase) == 1.506));
+    assert!((0..12).all(|phase| signal(0x0D, phase) == 0.350));
+    assert!((0..12).all(|phase| signal(0x2F, phase) == 0.518));
+    // the other colors spend half of the phases on each level
+    let high = (0..12)
+        .filter(|phase| signal(0x16, *phase) == 1.506)
+        .count();
+    assert_eq!(high, 6);
+}
+
+#[test]
+fn test_grays_and_blacks() {
+    let palette = generate("");
+    assert_eq!(palette.color(0x0F), (0, 0, 0));
+    assert_eq!(palette.color(0x1D), (0, 0, 0));
+    assert_eq!(palette.color(0x20), (255, 255, 255));
+    let (r, g, b) = palette.color(0x00);
+    assert!(r == g && g == b && r > 80 && r < 120);
+}
+
+#[test]
+fn test_hues() {
+    let palette = generate("");
+    let (r, g, b) = palette.color(0x12);
+    assert!(b > r && b > g, "$12 should be blue");
+    let (r, g, b) = palette.color(0x16);
+    assert!(r > g && r > b, "$16 should be red");
+    let (r, g, b) = palette.color(0x1A);
+    assert!(g > r && g > b, "$1A should be green");
+}
+
+#[test]
+fn test_emphasis_darkens_other_channels() {
+    let palette = generate("");
+    let (r, g, b) = palette.color(0x30);
+    let (red_r, red_g, red_b) = palette.color(0b001_110000);
+
+    assert!(red_g < g && red_b < b);
+    assert!(red_r > red_g);
+    assert!(red_r <= r);
+}
+
+#[test]
+fn test_settings() {
+    let default = generate("");
+    let (r, _, _) = default.color(0x00);
+    let (brighter, _, _) = generate("brightness=0.1").color(0x00);
+    assert!(brighter > r);
+
+    let (r, g, _) = generate("saturation=0").color(0x16);
+    assert_eq!(r, g);
+
+    assert_ne!(generate("hue=30").color(0x16), default.color(0x16));
+    let (contrasted, _, _) = generate("contrast=0.5").color(0x00);
+    assert!(contrasted < r);
+    let (dark, _, _) = generate("gamma=1.0").color(0x00);
+    assert!(dark < r);
+}
+
+#[test]
+fn test_parse_settings() {
+    let settings: NtscSettings = "hue=-15, saturation=1.5,gamma=1.8".parse().unwrap();
+    assert_eq!(settings.hue, -15.0);
+    assert_eq!(settings.saturation, 1.5);
+    assert_eq!(settings.contrast, 1.0);
+    assert_eq!(settings.gamma, 1.8);
+
+    assert!("tint=3".parse::<NtscSettings>().is_err());
+    assert!("hue=red".parse::<NtscSettings>().is_err());
+    assert!("gamma=0".parse::<NtscSettings>().is_err());
+    assert!("hue".parse::<NtscSettings>().is_err());
+}
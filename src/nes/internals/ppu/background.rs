@@ -0,0 +1,197 @@
+use super::{
+    registers::{ControlRegister, MaskRegister},
+    NAMETABLES, PPU,
+};
+
+// masks of the parts of v and t
+const COARSE_X: u16 = 0x001F;
+const COARSE_Y: u16 = 0x03E0;
+const NAMETABLE_X: u16 = 0x0400;
+const NAMETABLE_Y: u16 = 0x0800;
+const FINE_Y: u16 = 0x7000;
+// bits copied from t to v at the end of each scanline
+const HORIZONTAL_BITS: u16 = COARSE_X | NAMETABLE_X;
+// bits copied from t to v during the pre-render scanline
+const VERTICAL_BITS: u16 = COARSE_Y | NAMETABLE_Y | FINE_Y;
+
+const ATTRIBUTE_TABLE: u16 = 0x23C0;
+
+/*
+  the background pipeline.
+  every 8 dots the PPU fetches the nametable byte,the attribute byte and the two pattern bytes of the next tile into the latches,
+  they are moved into the low 8 bits of the shift registers,which shift once per dot
+*/
+pub struct Background {
+    next_tile_id: u8,
+    next_tile_attribute: u8,
+    next_tile_lo: u8,
+    next_tile_hi: u8,
+    pattern_lo: u16,
+    pattern_hi: u16,
+    attribute_lo: u16,
+    attribute_hi: u16,
+}
+
+impl Background {
+    pub fn new() -> Self {
+        Background {
+            next_tile_id: 0,
+            next_tile_attribute: 0,
+            next_tile_lo: 0,
+            next_tile_hi: 0,
+            pattern_lo: 0,
+            pattern_hi: 0,
+            attribute_lo: 0,
+            attribute_hi: 0,
+        }
+    }
+
+    fn shift(&mut self) {
+        self.pattern_lo <<= 1;
+        self.pattern_hi <<= 1;
+        self.attribute_lo <<= 1;
+        self.attribute_hi <<= 1;
+    }
+
+    fn reload(&mut self) {
+        self.pattern_lo = (self.pattern_lo & 0xFF00) | self.next_tile_lo as u16;
+        self.pattern_hi = (self.pattern_hi & 0xFF00) | self.next_tile_hi as u16;
+        // the attribute is the same for the whole tile,so its bits are spread over the 8 pixels
+        let attribute_lo = if self.next_tile_attribute & 0b01 != 0 {
+            0xFF
+        } else {
+            0x00
+        };
+        let attribute_hi = if self.next_tile_attribute & 0b10 != 0 {
+            0xFF
+        } else {
+            0x00
+        };
+        self.attribute_lo = (self.attribute_lo & 0xFF00) | attribute_lo;
+        self.attribute_hi = (self.attribute_hi & 0xFF00) | attribute_hi;
+    }
+}
+
+impl PPU {
+    // memory accesses and scroll updates of a single dot of a visible or pre-render scanline
+    pub(super) fn fetch_background(&mut self) {
+        let dot = self.dot;
+        if (2..=257).contains(&dot) || (321..=337).contains(&dot) {
+            self.background.shift();
+
+            match (dot - 1) % 8 {
+                0 => {
+                    // the tile fetched in the previous 8 dots enters the shift registers
+                    self.background.reload();
+                    self.background.next_tile_id = self.read_vram(NAMETABLES | (self.v & 0x0FFF));
+                }
+                2 => {
+                    let v = self.v;
+                    // each attribute byte covers 4x4 tiles,divided into 4 quadrants of 2x2 tiles
+                    let addr = ATTRIBUTE_TABLE
+                        | (v & (NAMETABLE_X | NAMETABLE_Y))
+                        | ((v >> 4) & 0x38)
+                        | ((v >> 2) & 0x07);
+                    let mut attribute = self.read_vram(addr);
+                    // bit 1 of coarse Y and bit 1 of coarse X select the quadrant
+                    if v & 0x0040 != 0 {
+                        attribute >>= 4;
+                    }
+                    if v & 0b10 != 0 {
+                        attribute >>= 2;
+                    }
+                    self.background.next_tile_attribute = attribute & 0b11;
+                }
+                4 => {
+                    let addr = self.tile_row_addr();
+                    self.background.next_tile_lo = self.read_vram(addr);
+                }
+                6 => {
+                    let addr = self.tile_row_addr() + 8;
+                    self.background.next_tile_hi = self.read_vram(addr);
+                }
+                7 => self.increment_coarse_x(),
+                _ => {}
+            }
+        }
+
+        match dot {
+            256 => self.increment_y(),
+            257 => {
+                self.background.reload();
+                self.v = (self.v & !HORIZONTAL_BITS) | (self.t & HORIZONTAL_BITS);
+            }
+            280..=304 if self.is_pre_render_scanline() => {
+                self.v = (self.v & !VERTICAL_BITS) | (self.t & VERTICAL_BITS);
+            }
+            // unused nametable fetches at the end of the scanline
+            338 | 340 => {
+                self.background.next_tile_id = self.read_vram(NAMETABLES | (self.v & 0x0FFF));
+            }
+            _ => {}
+        }
+    }
+
+    /*
+      4 bit palette index (palette << 2 | pixel) of the background at the current dot,
+      0 means the pixel is transparent
+    */
+    pub(super) fn background_pixel(&self, x: usize) -> u8 {
+        if !self.mask.contains(MaskRegister::SHOW_BACKGROUND)
+            || (x < 8 && !self.mask.contains(MaskRegister::LEFTMOST_8PXL_BACKGROUND))
+        {
+            return 0;
+        }
+
+        let bit = 0x8000 >> self.x;
+        let background = &self.background;
+        let pixel = ((background.pattern_hi & bit != 0) as u8) << 1
+            | (background.pattern_lo & bit != 0) as u8;
+        if pixel == 0 {
+            return 0;
+        }
+        let palette = ((background.attribute_hi & bit != 0) as u8) << 1
+            | (background.attribute_lo & bit != 0) as u8;
+        palette << 2 | pixel
+    }
+
+    pub(super) fn increment_coarse_x(&mut self) {
+        if self.v & COARSE_X == 31 {
+            // wraps into the next horizontal nametable
+            self.v &= !COARSE_X;
+            self.v ^= NAMETABLE_X;
+        } else {
+            self.v += 1;
+        }
+    }
+
+    pub(super) fn increment_y(&mut self) {
+        if self.v & FINE_Y != FINE_Y {
+            self.v += 0x1000;
+            return;
+        }
+        self.v &= !FINE_Y;
+        let mut coarse_y = (self.v & COARSE_Y) >> 5;
+        if coarse_y == 29 {
+            // the last row of tiles,wraps into the next vertical nametable
+            coarse_y = 0;
+            self.v ^= NAMETABLE_Y;
+        } else if coarse_y == 31 {
+            // rows 30 and 31 are the attribute table,they wrap without switching nametables
+            coarse_y = 0;
+        } else {
+            coarse_y += 1;
+        }
+        self.v = (self.v & !COARSE_Y) | (coarse_y << 5);
+    }
+
+    fn tile_row_addr(&self) -> u16 {
+        let table = if self.ctrl.contains(ControlRegister::BACKGROUND_PATTERN_ADDR) {
+            0x1000
+        } else {
+            0
+        };
+        let fine_y = (self.v & FINE_Y) >> 12;
+        table + self.background.next_tile_id as u16 * 16 + fine_y
+    }
+}
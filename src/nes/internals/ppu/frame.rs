@@ -0,0 +1,66 @@
+use super::{super::palette::Palette, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+// bits of a pixel holding the PPUMASK emphasis bits,the lower 6 bits are the palette index
+pub const EMPHASIS_SHIFT: u16 = 6;
+const COLOR_MASK: u16 = 0x3F;
+
+/*
+  a picture made by the PPU,every pixel is the 6 bit palette index plus the 3 emphasis bits:
+    ...E EECC CCCC
+  it doesn't know about colors,a palette turns it into RGB
+*/
+#[derive(Clone)]
+pub struct Frame {
+    pixels: Vec<u16>,
+}
+
+impl Frame {
+    pub fn new() -> Self {
+        Frame {
+            pixels: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        SCREEN_WIDTH
+    }
+
+    pub fn height(&self) -> usize {
+        SCREEN_HEIGHT
+    }
+
+    pub fn pixels(&self) -> &[u16] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u16 {
+        self.pixels[y * SCREEN_WIDTH + x]
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u8, emphasis: u8) {
+        self.pixels[y * SCREEN_WIDTH + x] =
+            (color as u16 & COLOR_MASK) | (emphasis as u16) << EMPHASIS_SHIFT;
+    }
+
+    // 3 bytes per pixel,red green blue
+    pub fn to_rgb24(&self, palette: &Palette) -> Vec<u8> {
+        let mut rgb = Vec::with_capacity(self.pixels.len() * 3);
+        for pixel in &self.pixels {
+            let (r, g, b) = palette.color(*pixel);
+            rgb.extend_from_slice(&[r, g, b]);
+        }
+        rgb
+    }
+
+    // 4 bytes per pixel,red green blue and an opaque alpha.
+    // the SDL window takes RGB24,this one is for frontends that want RGBA
+    #[allow(dead_code)]
+    pub fn to_rgba(&self, palette: &Palette) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in &self.pixels {
+            let (r, g, b) = palette.color(*pixel);
+            rgba.extend_from_slice(&[r, g, b, 0xFF]);
+        }
+        rgba
+    }
+}
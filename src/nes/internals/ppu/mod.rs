@@ -0,0 +1,605 @@
+mod background;
+pub mod frame;
+pub mod registers;
+mod sprites;
+pub mod viewer;
+
+#[cfg(test)]
+mod test;
+
+use super::{
+    cartridge::{Chr, Mirroring},
+    ram_pattern::RamFiller,
+    region::Region,
+};
+use background::Background;
+use frame::Frame;
+use registers::{ControlRegister, MaskRegister, StatusRegister, STATUS_DRIVEN_BITS};
+use sprites::Sprites;
+
+pub const PPUCTRL: u16 = 0x2000;
+pub const PPUMASK: u16 = 0x2001;
+pub const PPUSTATUS: u16 = 0x2002;
+pub const OAMADDR: u16 = 0x2003;
+pub const OAMDATA: u16 = 0x2004;
+pub const PPUSCROLL: u16 = 0x2005;
+pub const PPUADDR: u16 = 0x2006;
+pub const PPUDATA: u16 = 0x2007;
+
+pub const DOTS_PER_SCANLINE: usize = 341;
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 240;
+
+pub const OAM_SIZE: usize = 256;
+
+const PATTERN_TABLES_END: u16 = 0x1FFF;
+const NAMETABLES: u16 = 0x2000;
+const NAMETABLES_MIRRORS_END: u16 = 0x3EFF;
+const PALETTE_TABLE: u16 = 0x3F00;
+const PALETTE_TABLE_MIRRORS_END: u16 = 0x3FFF;
+const VRAM_ADDR_MASK: u16 = 0x3FFF;
+const NAMETABLE_SIZE: u16 = 0x400;
+// 2KB of CIRAM inside the console plus the 2KB a four-screen cartridge brings
+const VRAM_SIZE: usize = 0x1000;
+const PALETTE_TABLE_SIZE: usize = 32;
+
+/*
+  a bit of the PPU open bus fades to 0 around 600ms after it was last driven,which is about 36 NTSC frames.
+  every region runs about the same amount of dots per second,so it doesn't depend on the frame size
+*/
+const OPEN_BUS_DECAY_DOTS: usize = 36 * DOTS_PER_SCANLINE * 262;
+// bits 2-4 of a sprite attribute byte don't exist in OAM,so they always read back as 0
+const OAM_ATTRIBUTE_MASK: u8 = 0b1110_0011;
+// palette entries are only 6 bits wide,PPUDATA fills the upper 2 bits from the PPU open bus
+const PALETTE_DRIVEN_BITS: u8 = 0b0011_1111;
+
+pub struct PPU {
+    ctrl: ControlRegister,
+    mask: MaskRegister,
+    status: StatusRegister,
+    oam_addr: u8,
+    oam_data: [u8; OAM_SIZE],
+    // pattern tables,they live in the cartridge
+    chr: Chr,
+    mirroring: Mirroring,
+    vram: [u8; VRAM_SIZE],
+    palette_table: [u8; PALETTE_TABLE_SIZE],
+    /*
+      the internal "loopy" registers,they hold the scroll and the vram address at the same time.
+      v and t are 15 bits:
+        yyy NN YYYYY XXXXX
+        ||| || ||||| +++++-- coarse X scroll
+        ||| || +++++-------- coarse Y scroll
+        ||| ++-------------- nametable select
+        +++----------------- fine Y scroll
+    */
+    // current vram address,also the position being rendered
+    v: u16,
+    // temporary vram address,where the top left tile of the screen is
+    t: u16,
+    // fine X scroll,3 bits
+    x: u8,
+    /*
+      w,PPUSCROLL and PPUADDR take two writes each and share the same toggle to know which one is next.
+      false = first write,true = second write
+    */
+    write_toggle: bool,
+    // PPUDATA reads outside the palette return the value fetched by the previous read
+    read_buffer: u8,
+    /*
+      the PPU has its own data bus between the CPU and the registers.
+      every write fills it and reads only refresh the bits the register drives,the rest come from the latch
+    */
+    io_latch: u8,
+    // dot in which each bit of the latch was last driven,used to make them decay
+    io_latch_refreshed: [usize; 8],
+    background: Background,
+    sprites: Sprites,
+    // the frame being rendered,it is complete once vblank starts
+    frame_buffer: Frame,
+    scanline: usize,
+    dot: usize,
+    frame: usize,
+    // with rendering enabled odd frames skip the last dot of the pre-render scanline
+    odd_frame: bool,
+    // set when the NMI line goes from high to low,the CPU takes it before its next instruction
+    nmi_pending: bool,
+    // (scanline,dot) where sprite 0 hit was set,until the event log takes it
+    sprite_zero_hit: Option<(usize, usize)>,
+    // reading $2002 right before vblank starts makes the PPU miss setting the flag this frame
+    suppress_vblank: bool,
+    // amount of dots executed since power on
+    dots: usize,
+    // the console the PPU belongs to,it decides the size of the frame and when vblank starts
+    region: Region,
+}
+
+impl PPU {
+    pub fn new(chr: Chr, mirroring: Mirroring) -> Self {
+        PPU {
+            ctrl: ControlRegister::empty(),
+            mask: MaskRegister::empty(),
+            status: StatusRegister::empty(),
+            oam_addr: 0,
+            oam_data: [0; OAM_SIZE],
+            chr,
+            mirroring,
+            vram: [0; VRAM_SIZE],
+            palette_table: [0; PALETTE_TABLE_SIZE],
+            v: 0,
+            t: 0,
+            x: 0,
+            write_toggle: false,
+            read_buffer: 0,
+            io_latch: 0,
+            io_latch_refreshed: [0; 8],
+            background: Background::new(),
+            sprites: Sprites::new(),
+            frame_buffer: Frame::new(),
+            scanline: 0,
+            dot: 0,
+            frame: 0,
+            odd_frame: false,
+            nmi_pending: false,
+            sprite_zero_hit: None,
+            suppress_vblank: false,
+            dots: 0,
+            region: Region::Ntsc,
+        }
+    }
+
+    /*
+      the state of the PPU when the console is switched on,only the cartridge and the settings are kept.
+      the CHR-RAM and the OAM get the power-on pattern.
+      the frame and dot counters keep going,they belong to the emulator rather than to the console
+    */
+    pub fn power_on(&mut self, filler: &mut RamFiller) {
+        let mut chr = std::mem::replace(&mut self.chr, Chr::Ram(Vec::new()));
+        if let Chr::Ram(ram) = &mut chr {
+            filler.fill(ram);
+        }
+        let mut ppu = PPU::new(chr, self.mirroring);
+        filler.fill(&mut ppu.oam_data);
+        ppu.set_region(self.region);
+        ppu.set_unlimited_sprites(self.unlimited_sprites());
+        ppu.frame = self.frame;
+        ppu.dots = self.dots;
+        ppu.io_latch_refreshed = self.io_latch_refreshed;
+        *self = ppu;
+    }
+
+    // the reset button only clears PPUCTRL,PPUMASK,the scroll,the write toggle and the read buffer
+    pub fn reset(&mut self) {
+        self.ctrl = ControlRegister::empty();
+        self.mask = MaskRegister::empty();
+        self.t = 0;
+        self.x = 0;
+        self.write_toggle = false;
+        self.read_buffer = 0;
+        self.odd_frame = false;
+        self.nmi_pending = false;
+    }
+
+    pub fn tick(&mut self, dots: usize) {
+        for _ in 0..dots {
+            self.step();
+        }
+    }
+
+    // (scanline,dot) of the next dot to be rendered
+    pub fn position(&self) -> (usize, usize) {
+        (self.scanline, self.dot)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frame
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+        if self.scanline >= region.scanlines_per_frame() {
+            self.scanline = region.pre_render_scanline();
+        }
+    }
+
+    pub fn frame_buffer(&self) -> &Frame {
+        &self.frame_buffer
+    }
+
+    // returns where sprite 0 hit happened once for every frame it does
+    pub fn take_sprite_zero_hit(&mut self) -> Option<(usize, usize)> {
+        self.sprite_zero_hit.take()
+    }
+
+    // returns true once for every NMI the PPU generated
+    pub fn poll_nmi(&mut self) -> bool {
+        std::mem::take(&mut self.nmi_pending)
+    }
+
+    pub fn ctrl(&self) -> ControlRegister {
+        self.ctrl
+    }
+
+    pub fn mask(&self) -> MaskRegister {
+        self.mask
+    }
+
+    pub fn status(&self) -> StatusRegister {
+        self.status
+    }
+
+    pub fn oam_addr(&self) -> u8 {
+        self.oam_addr
+    }
+
+    pub fn vram_addr(&self) -> u16 {
+        self.v
+    }
+
+    pub fn temp_vram_addr(&self) -> u16 {
+        self.t
+    }
+
+    pub fn fine_x(&self) -> u8 {
+        self.x
+    }
+
+    pub fn write_toggle(&self) -> bool {
+        self.write_toggle
+    }
+
+    pub fn read_buffer(&self) -> u8 {
+        self.read_buffer
+    }
+
+    pub fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    pub fn io_latch(&self) -> u8 {
+        self.decayed_io_latch()
+    }
+
+    // addr must already be mirrored down to $2000-$2007
+    pub fn read_register(&mut self, addr: u16) -> u8 {
+        match addr {
+            PPUSTATUS => {
+                /*
+                  the race with the vblank flag:
+                  one dot before it is set the read returns it clear and the flag is never set this frame,
+                  on the dots right after it is set the read returns it set but the NMI is lost
+                */
+                if self.scanline == self.region.vblank_scanline() {
+                    match self.dot {
+                        1 => self.suppress_vblank = true,
+                        2..=3 => self.nmi_pending = false,
+                        _ => {}
+                    }
+                }
+                let value = self.status.bits() | (self.decayed_io_latch() & !STATUS_DRIVEN_BITS);
+                self.status.remove(StatusRegister::VBLANK_STARTED);
+                self.write_toggle = false;
+                self.drive_io_latch(value, STATUS_DRIVEN_BITS)
+            }
+            OAMDATA => {
+                let value = self.read_oam();
+                self.drive_io_latch(value, 0xFF)
+            }
+            PPUDATA => {
+                let addr = self.v;
+                self.increment_vram_addr();
+                match addr {
+                    PALETTE_TABLE..=PALETTE_TABLE_MIRRORS_END => {
+                        /*
+                          palette reads skip the buffer and are returned right away,
+                          but the buffer is still filled with the nametable byte "under" the palette
+                        */
+                        self.read_buffer = self.read_vram(addr - 0x1000);
+                        let value = (self.decayed_io_latch() & !PALETTE_DRIVEN_BITS)
+                            | self.read_palette(addr);
+                        self.drive_io_latch(value, PALETTE_DRIVEN_BITS)
+                    }
+                    _ => {
+                        let value = self.read_buffer;
+                        self.read_buffer = self.read_vram(addr);
+                        self.drive_io_latch(value, 0xFF)
+                    }
+                }
+            }
+            // write only registers,nothing drives the bus
+            _ => self.decayed_io_latch(),
+        }
+    }
+
+    // reads a register the same way the CPU would,but without any of the side effects
+    pub fn peek_register(&self, addr: u16) -> u8 {
+        let latch = self.decayed_io_latch();
+        match addr {
+            PPUSTATUS => self.status.bits() | (latch & !STATUS_DRIVEN_BITS),
+            OAMDATA => self.read_oam(),
+            PPUDATA => match self.v {
+                PALETTE_TABLE..=PALETTE_TABLE_MIRRORS_END => {
+                    (latch & !PALETTE_DRIVEN_BITS) | self.read_palette(self.v)
+                }
+                _ => self.read_buffer,
+            },
+            _ => latch,
+        }
+    }
+
+    // addr must already be mirrored down to $2000-$2007
+    pub fn write_register(&mut self, addr: u16, data: u8) {
+        self.drive_io_latch(data, 0xFF);
+        match addr {
+            PPUCTRL => {
+                let ctrl = ControlRegister::from_bits_retain(data);
+                let nmi_enabled = ctrl.contains(ControlRegister::GENERATE_NMI);
+                if nmi_enabled
+                    && !self.ctrl.contains(ControlRegister::GENERATE_NMI)
+                    && self.status.contains(StatusRegister::VBLANK_STARTED)
+                {
+                    // enabling NMI during vblank pulls the NMI line right away
+                    self.nmi_pending = true;
+                } else if !nmi_enabled
+                    && self.scanline == self.region.vblank_scanline()
+                    && matches!(self.dot, 2..=3)
+                {
+                    // disabling it right after vblank started cancels the NMI before the CPU sees it
+                    self.nmi_pending = false;
+                }
+                self.ctrl = ctrl;
+                // t: ...GH.. ........ <- d: ......GH
+                self.t = (self.t & !0x0C00) | ((data as u16 & 0b11) << 10);
+            }
+            PPUMASK => self.mask = MaskRegister::from_bits_retain(data),
+            PPUSTATUS => { /* read only,only the latch sees the value */ }
+            OAMADDR => self.oam_addr = data,
+            OAMDATA => {
+                self.oam_data[self.oam_addr as usize] = data;
+                self.oam_addr = self.oam_addr.wrapping_add(1);
+            }
+            PPUSCROLL => {
+                let data = data as u16;
+                if self.write_toggle {
+                    // t: FGH..AB CDE..... <- d: ABCDEFGH
+                    self.t = (self.t & !0x73E0) | ((data & 0b111) << 12) | ((data & 0xF8) << 2);
+                } else {
+                    // t: ....... ...ABCDE <- d: ABCDE...,x: FGH
+                    self.t = (self.t & !0x001F) | (data >> 3);
+                    self.x = data as u8 & 0b111;
+                }
+                self.write_toggle = !self.write_toggle;
+            }
+            PPUADDR => {
+                // high byte first,the address is only 14 bits wide so bit 14 of t is cleared
+                let data = data as u16;
+                if self.write_toggle {
+                    self.t = (self.t & 0xFF00) | data;
+                    self.v = self.t;
+                } else {
+                    self.t = (self.t & 0x00FF) | ((data & 0x3F) << 8);
+                }
+                self.write_toggle = !self.write_toggle;
+            }
+            PPUDATA => {
+                self.write_vram(self.v, data);
+                self.increment_vram_addr();
+            }
+            _ => unreachable!("Invalid PPU register {:04X}", addr),
+        }
+    }
+
+    fn read_oam(&self) -> u8 {
+        let value = self.oam_data[self.oam_addr as usize];
+        if self.oam_addr & 0b11 == 2 {
+            value & OAM_ATTRIBUTE_MASK
+        } else {
+            value
+        }
+    }
+
+    fn increment_vram_addr(&mut self) {
+        if self.is_rendering() {
+            // while rendering the PPUDATA increment glitches into both scroll increments
+            self.increment_coarse_x();
+            self.increment_y();
+        } else {
+            self.v = self.v.wrapping_add(self.ctrl.vram_addr_increment()) & 0x7FFF;
+        }
+    }
+
+    fn is_rendering_enabled(&self) -> bool {
+        self.mask
+            .intersects(MaskRegister::SHOW_BACKGROUND | MaskRegister::SHOW_SPRITES)
+    }
+
+    // the PPU is fetching tiles,that only happens in the visible and pre-render scanlines
+    fn is_rendering(&self) -> bool {
+        self.is_rendering_enabled()
+            && (self.scanline < SCREEN_HEIGHT || self.is_pre_render_scanline())
+    }
+
+    fn is_pre_render_scanline(&self) -> bool {
+        self.scanline == self.region.pre_render_scanline()
+    }
+
+    // runs a single dot
+    fn step(&mut self) {
+        self.dots += 1;
+
+        if self.dot == 1 {
+            if self.scanline == self.region.vblank_scanline() {
+                if !self.suppress_vblank {
+                    self.status.insert(StatusRegister::VBLANK_STARTED);
+                    if self.ctrl.contains(ControlRegister::GENERATE_NMI) {
+                        self.nmi_pending = true;
+                    }
+                }
+                self.suppress_vblank = false;
+            } else if self.is_pre_render_scanline() {
+                self.status.remove(
+                    StatusRegister::VBLANK_STARTED
+                        | StatusRegister::SPRITE_ZERO_HIT
+                        | StatusRegister::SPRITE_OVERFLOW,
+                );
+            }
+        }
+
+        if self.scanline < SCREEN_HEIGHT || self.is_pre_render_scanline() {
+            if self.is_rendering_enabled() {
+                self.fetch_background();
+                self.fetch_sprites();
+            }
+            if self.scanline < SCREEN_HEIGHT && (1..=SCREEN_WIDTH).contains(&self.dot) {
+                self.render_pixel();
+            }
+        }
+
+        self.dot += 1;
+        if self.is_pre_render_scanline()
+            && self.dot == DOTS_PER_SCANLINE - 1
+            && self.odd_frame
+            && self.region.skips_odd_frame_dot()
+            && self.is_rendering_enabled()
+        {
+            // the idle dot at the end of the pre-render scanline is skipped
+            self.dot = DOTS_PER_SCANLINE;
+        }
+        if self.dot == DOTS_PER_SCANLINE {
+            self.dot = 0;
+            self.scanline += 1;
+            if self.scanline == self.region.scanlines_per_frame() {
+                self.scanline = 0;
+                self.frame += 1;
+                self.odd_frame = !self.odd_frame;
+            }
+        }
+    }
+
+    fn render_pixel(&mut self) {
+        let x = self.dot - 1;
+        let color = if self.is_rendering_enabled() {
+            let background = self.background_pixel(x);
+            let pixel = match self.sprite_pixel(x) {
+                Some((sprite, behind_background, is_sprite_zero)) => {
+                    // sprite 0 hit doesn't care about priority,but it never happens at x = 255
+                    if is_sprite_zero
+                        && background != 0
+                        && x != 255
+                        && !self.status.contains(StatusRegister::SPRITE_ZERO_HIT)
+                    {
+                        self.status.insert(StatusRegister::SPRITE_ZERO_HIT);
+                        self.sprite_zero_hit = Some((self.scanline, self.dot));
+                    }
+                    if background == 0 || !behind_background {
+                        // sprites use the second half of the palette
+                        0x10 | sprite
+                    } else {
+                        background
+                    }
+                }
+                None => background,
+            };
+            self.read_palette(PALETTE_TABLE + pixel as u16)
+        } else if (PALETTE_TABLE..=PALETTE_TABLE_MIRRORS_END).contains(&self.v) {
+            // with rendering off the backdrop comes from wherever v points,if it points to the palette
+            self.read_palette(self.v)
+        } else {
+            self.read_palette(PALETTE_TABLE)
+        };
+        let emphasis = self.mask.emphasis(self.region.swaps_red_green_emphasis());
+        self.frame_buffer
+            .set_pixel(x, self.scanline, color, emphasis);
+    }
+
+    // greyscale also applies to the palette reads of PPUDATA
+    fn read_palette(&self, addr: u16) -> u8 {
+        self.read_vram(addr) & self.mask.greyscale_mask()
+    }
+
+    fn read_vram(&self, addr: u16) -> u8 {
+        let addr = addr & VRAM_ADDR_MASK;
+        match addr {
+            0..=PATTERN_TABLES_END => self.chr.read(addr),
+            NAMETABLES..=NAMETABLES_MIRRORS_END => self.vram[self.mirror_vram_addr(addr)],
+            PALETTE_TABLE..=PALETTE_TABLE_MIRRORS_END => {
+                self.palette_table[mirror_palette_addr(addr)] & PALETTE_DRIVEN_BITS
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_vram(&mut self, addr: u16, data: u8) {
+        let addr = addr & VRAM_ADDR_MASK;
+        match addr {
+            0..=PATTERN_TABLES_END => self.chr.write(addr, data),
+            NAMETABLES..=NAMETABLES_MIRRORS_END => {
+                let index = self.mirror_vram_addr(addr);
+                self.vram[index] = data;
+            }
+            PALETTE_TABLE..=PALETTE_TABLE_MIRRORS_END => {
+                self.palette_table[mirror_palette_addr(addr)] = data
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /*
+      maps $2000-$3EFF to an index in vram.
+      with horizontal mirroring:
+        [ A ] [ a ]
+        [ B ] [ b ]
+      with vertical mirroring:
+        [ A ] [ B ]
+        [ a ] [ b ]
+    */
+    fn mirror_vram_addr(&self, addr: u16) -> usize {
+        // $3000-$3EFF mirrors $2000-$2EFF
+        let vram_index = (addr - NAMETABLES) & 0x0FFF;
+        let nametable = vram_index / NAMETABLE_SIZE;
+        let offset = vram_index % NAMETABLE_SIZE;
+        let bank = match self.mirroring {
+            Mirroring::Horizontal => nametable / 2,
+            Mirroring::Vertical => nametable % 2,
+            Mirroring::SingleScreenLower => 0,
+            Mirroring::SingleScreenUpper => 1,
+            Mirroring::FourScreen => nametable,
+        };
+        (bank * NAMETABLE_SIZE + offset) as usize
+    }
+
+    fn decayed_io_latch(&self) -> u8 {
+        let mut latch = self.io_latch;
+        for bit in 0..8 {
+            if self.dots - self.io_latch_refreshed[bit] >= OPEN_BUS_DECAY_DOTS {
+                latch &= !(1 << bit);
+            }
+        }
+        latch
+    }
+
+    // puts the driven bits of value in the latch and returns the value
+    fn drive_io_latch(&mut self, value: u8, driven_bits: u8) -> u8 {
+        self.io_latch = (self.decayed_io_latch() & !driven_bits) | (value & driven_bits);
+        for bit in 0..8 {
+            if driven_bits & (1 << bit) != 0 {
+                self.io_latch_refreshed[bit] = self.dots;
+            }
+        }
+        value
+    }
+}
+
+// $3F10,$3F14,$3F18 and $3F1C are mirrors of the background entries $3F00,$3F04,$3F08 and $3F0C
+fn mirror_palette_addr(addr: u16) -> usize {
+    let index = (addr & 0x1F) as usize;
+    if index >= 0x10 && index.is_multiple_of(4) {
+        index - 0x10
+    } else {
+        index
+    }
+}
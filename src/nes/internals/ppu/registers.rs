@@ -0,0 +1,112 @@
+use bitflags::bitflags;
+
+bitflags!(
+    /*
+      PPUCTRL ($2000)
+      7  bit  0
+      VPHB SINN
+      |||| ||||
+      |||| ||++- base nametable address (0 = $2000; 1 = $2400; 2 = $2800; 3 = $2C00)
+      |||| |+--- vram address increment per PPUDATA access (0: add 1; 1: add 32)
+      |||| +---- sprite pattern table address for 8x8 sprites (0: $0000; 1: $1000)
+      |||+------ background pattern table address (0: $0000; 1: $1000)
+      ||+------- sprite size (0: 8x8; 1: 8x16)
+      |+-------- PPU master/slave select
+      +--------- generate an NMI at the start of vblank
+    */
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct ControlRegister: u8 {
+        const NAMETABLE1 = 0b0000_0001;
+        const NAMETABLE2 = 0b0000_0010;
+        const VRAM_ADD_INCREMENT = 0b0000_0100;
+        const SPRITE_PATTERN_ADDR = 0b0000_1000;
+        const BACKGROUND_PATTERN_ADDR = 0b0001_0000;
+        const SPRITE_SIZE = 0b0010_0000;
+        const MASTER_SLAVE_SELECT = 0b0100_0000;
+        const GENERATE_NMI = 0b1000_0000;
+    }
+);
+
+bitflags!(
+    /*
+      PPUMASK ($2001)
+      7  bit  0
+      BGRs bMmG
+      |||| ||||
+      |||| |||+- greyscale
+      |||| ||+-- show background in the leftmost 8 pixels
+      |||| |+--- show sprites in the leftmost 8 pixels
+      |||| +---- show background
+      |||+------ show sprites
+      ||+------- emphasize red
+      |+-------- emphasize green
+      +--------- emphasize blue
+    */
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct MaskRegister: u8 {
+        const GREYSCALE = 0b0000_0001;
+        const LEFTMOST_8PXL_BACKGROUND = 0b0000_0010;
+        const LEFTMOST_8PXL_SPRITE = 0b0000_0100;
+        const SHOW_BACKGROUND = 0b0000_1000;
+        const SHOW_SPRITES = 0b0001_0000;
+        const EMPHASISE_RED = 0b0010_0000;
+        const EMPHASISE_GREEN = 0b0100_0000;
+        const EMPHASISE_BLUE = 0b1000_0000;
+    }
+);
+
+bitflags!(
+    /*
+      PPUSTATUS ($2002)
+      7  bit  0
+      VSO. ....
+      |||| ||||
+      |||+-++++- not driven by the PPU,they come from the PPU open bus
+      ||+------- sprite overflow
+      |+-------- sprite 0 hit
+      +--------- vblank started
+    */
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct StatusRegister: u8 {
+        const SPRITE_OVERFLOW = 0b0010_0000;
+        const SPRITE_ZERO_HIT = 0b0100_0000;
+        const VBLANK_STARTED = 0b1000_0000;
+    }
+);
+
+impl ControlRegister {
+    pub fn vram_addr_increment(&self) -> u16 {
+        if self.contains(ControlRegister::VRAM_ADD_INCREMENT) {
+            32
+        } else {
+            1
+        }
+    }
+}
+
+impl MaskRegister {
+    /*
+      the emphasis bits in the order of the frame pixels: red,green,blue.
+      the PAL and Dendy PPUs have the red and green bits swapped
+    */
+    pub fn emphasis(&self, swap_red_green: bool) -> u8 {
+        let emphasis = self.bits() >> 5;
+        if swap_red_green {
+            (emphasis & 0b100) | (emphasis & 0b001) << 1 | (emphasis & 0b010) >> 1
+        } else {
+            emphasis
+        }
+    }
+
+    // greyscale keeps only the luma of a color,the grays of column $x0
+    pub fn greyscale_mask(&self) -> u8 {
+        if self.contains(MaskRegister::GREYSCALE) {
+            0x30
+        } else {
+            0x3F
+        }
+    }
+}
+
+// the three status bits are the only ones the PPU drives when $2002 is read
+pub const STATUS_DRIVEN_BITS: u8 = 0b1110_0000;
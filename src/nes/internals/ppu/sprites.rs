@@ -0,0 +1,224 @@
+use super::{
+    registers::{ControlRegister, MaskRegister, StatusRegister},
+    PPU, SCREEN_HEIGHT,
+};
+
+// the hardware can only draw 8 sprites per scanline
+const SECONDARY_OAM_SPRITES: usize = 8;
+pub(super) const OAM_SPRITES: usize = 64;
+
+pub(super) const ATTRIBUTE_PALETTE: u8 = 0b0000_0011;
+pub(super) const ATTRIBUTE_BEHIND_BACKGROUND: u8 = 0b0010_0000;
+pub(super) const ATTRIBUTE_FLIP_HORIZONTAL: u8 = 0b0100_0000;
+pub(super) const ATTRIBUTE_FLIP_VERTICAL: u8 = 0b1000_0000;
+
+// a sprite already fetched for the scanline being rendered
+#[derive(Debug, Clone, Copy)]
+pub struct SpriteUnit {
+    x: u8,
+    // already flipped horizontally,so bit 7 is always the leftmost pixel
+    pattern_lo: u8,
+    pattern_hi: u8,
+    attributes: u8,
+    is_sprite_zero: bool,
+}
+
+pub struct Sprites {
+    // sprites found by the evaluation,8 entries of 4 bytes
+    secondary_oam: [u8; SECONDARY_OAM_SPRITES * 4],
+    // sprites found past the 8th one,only kept when the limit is removed
+    extra_sprites: Vec<[u8; 4]>,
+    secondary_oam_count: usize,
+    sprite_zero_in_secondary: bool,
+    // sprites of the scanline being rendered
+    units: Vec<SpriteUnit>,
+    // draw every sprite in a scanline instead of the first 8,the overflow flag still works as the hardware
+    unlimited: bool,
+}
+
+impl Sprites {
+    pub fn new() -> Self {
+        Sprites {
+            secondary_oam: [0xFF; SECONDARY_OAM_SPRITES * 4],
+            extra_sprites: Vec::new(),
+            secondary_oam_count: 0,
+            sprite_zero_in_secondary: false,
+            units: Vec::with_capacity(OAM_SPRITES),
+            unlimited: false,
+        }
+    }
+}
+
+impl PPU {
+    pub fn set_unlimited_sprites(&mut self, unlimited: bool) {
+        self.sprites.unlimited = unlimited;
+    }
+
+    pub fn unlimited_sprites(&self) -> bool {
+        self.sprites.unlimited
+    }
+
+    // sprite work of a single dot of a visible or pre-render scanline
+    pub(super) fn fetch_sprites(&mut self) {
+        match self.dot {
+            257 => {
+                if self.scanline < SCREEN_HEIGHT {
+                    self.evaluate_sprites();
+                    self.load_sprite_units();
+                } else {
+                    // there is no evaluation in the pre-render scanline,so nothing is drawn in the first one
+                    self.sprites.units.clear();
+                }
+            }
+            // OAMADDR is reset during the sprite tile fetches
+            258..=320 => self.oam_addr = 0,
+            _ => {}
+        }
+    }
+
+    fn sprite_height(&self) -> usize {
+        if self.ctrl.contains(ControlRegister::SPRITE_SIZE) {
+            16
+        } else {
+            8
+        }
+    }
+
+    fn is_sprite_in_range(&self, y: u8) -> bool {
+        let row = self.scanline as isize - y as isize;
+        row >= 0 && row < self.sprite_height() as isize
+    }
+
+    /*
+      looks for the sprites of the next scanline.
+      the sprite Y in OAM is one less than the scanline where it shows up,so comparing against
+      the current scanline gives the sprites of the next one
+    */
+    fn evaluate_sprites(&mut self) {
+        self.sprites.secondary_oam = [0xFF; SECONDARY_OAM_SPRITES * 4];
+        self.sprites.extra_sprites.clear();
+        self.sprites.sprite_zero_in_secondary = false;
+
+        let mut found = 0;
+        let mut n = 0;
+        while n < OAM_SPRITES && found < SECONDARY_OAM_SPRITES {
+            let entry = &self.oam_data[n * 4..n * 4 + 4];
+            if self.is_sprite_in_range(entry[0]) {
+                self.sprites.secondary_oam[found * 4..found * 4 + 4].copy_from_slice(entry);
+                if n == 0 {
+                    self.sprites.sprite_zero_in_secondary = true;
+                }
+                found += 1;
+            }
+            n += 1;
+        }
+        self.sprites.secondary_oam_count = found;
+
+        if self.sprites.unlimited {
+            for sprite in n..OAM_SPRITES {
+                let entry = &self.oam_data[sprite * 4..sprite * 4 + 4];
+                if self.is_sprite_in_range(entry[0]) {
+                    self.sprites
+                        .extra_sprites
+                        .push([entry[0], entry[1], entry[2], entry[3]]);
+                }
+            }
+        }
+
+        /*
+          after 8 sprites the hardware keeps looking to set the overflow flag,but it increments
+          both the sprite and the byte index,so it checks tiles,attributes and X positions as if they were Y
+        */
+        let mut m = 0;
+        while n < OAM_SPRITES {
+            let y = self.oam_data[n * 4 + m];
+            if self.is_sprite_in_range(y) {
+                self.status.insert(StatusRegister::SPRITE_OVERFLOW);
+                break;
+            }
+            n += 1;
+            m = (m + 1) & 0b11;
+        }
+    }
+
+    fn load_sprite_units(&mut self) {
+        self.sprites.units.clear();
+        let secondary_oam = self.sprites.secondary_oam;
+        let sprites = secondary_oam
+            .chunks(4)
+            .take(self.sprites.secondary_oam_count)
+            .map(|entry| [entry[0], entry[1], entry[2], entry[3]])
+            .collect::<Vec<[u8; 4]>>();
+        let extra_sprites = std::mem::take(&mut self.sprites.extra_sprites);
+
+        for (index, entry) in sprites.iter().chain(extra_sprites.iter()).enumerate() {
+            let [y, tile, attributes, x] = *entry;
+            let addr = self.sprite_row_addr(y, tile, attributes);
+            let mut pattern_lo = self.read_vram(addr);
+            let mut pattern_hi = self.read_vram(addr + 8);
+            if attributes & ATTRIBUTE_FLIP_HORIZONTAL != 0 {
+                pattern_lo = pattern_lo.reverse_bits();
+                pattern_hi = pattern_hi.reverse_bits();
+            }
+            self.sprites.units.push(SpriteUnit {
+                x,
+                pattern_lo,
+                pattern_hi,
+                attributes,
+                is_sprite_zero: index == 0 && self.sprites.sprite_zero_in_secondary,
+            });
+        }
+        self.sprites.extra_sprites = extra_sprites;
+    }
+
+    fn sprite_row_addr(&self, y: u8, tile: u8, attributes: u8) -> u16 {
+        let height = self.sprite_height();
+        let mut row = self.scanline - y as usize;
+        if attributes & ATTRIBUTE_FLIP_VERTICAL != 0 {
+            row = height - 1 - row;
+        }
+
+        let (table, tile) = if height == 16 {
+            // 8x16 sprites take the table from bit 0 of the tile,the bottom half is the next tile
+            let table = (tile as u16 & 1) * 0x1000;
+            let tile = (tile & 0xFE) as u16 + (row / 8) as u16;
+            (table, tile)
+        } else if self.ctrl.contains(ControlRegister::SPRITE_PATTERN_ADDR) {
+            (0x1000, tile as u16)
+        } else {
+            (0, tile as u16)
+        };
+        table + tile * 16 + (row % 8) as u16
+    }
+
+    /*
+      returns the 4 bit palette index of the first opaque sprite at x,if it is behind the background
+      and if it is sprite 0
+    */
+    pub(super) fn sprite_pixel(&self, x: usize) -> Option<(u8, bool, bool)> {
+        if !self.mask.contains(MaskRegister::SHOW_SPRITES)
+            || (x < 8 && !self.mask.contains(MaskRegister::LEFTMOST_8PXL_SPRITE))
+        {
+            return None;
+        }
+
+        self.sprites.units.iter().find_map(|sprite| {
+            let column = x.checked_sub(sprite.x as usize)?;
+            if column >= 8 {
+                return None;
+            }
+            let bit = 0x80 >> column;
+            let pixel =
+                ((sprite.pattern_hi & bit != 0) as u8) << 1 | (sprite.pattern_lo & bit != 0) as u8;
+            if pixel == 0 {
+                return None;
+            }
+            let palette = sprite.attributes & ATTRIBUTE_PALETTE;
+            Some((
+                palette << 2 | pixel,
+                sprite.attributes & ATTRIBUTE_BEHIND_BACKGROUND != 0,
+                sprite.is_sprite_zero,
+            ))
+        })
+    }
+}
@@ -0,0 +1,140 @@
+use crate::nes::internals::{
+    cartridge::{Chr, Mirroring},
+    ppu::PPU,
+};
+
+use super::set_vram_addr;
+
+pub fn write_vram(ppu: &mut PPU, addr: u16, data: &[u8]) {
+    set_vram_addr(ppu, addr);
+    for byte in data {
+        ppu.write_register(0x2007, *byte);
+    }
+}
+
+/*
+  tile 1 is filled with color 1 and tile 2 with color 2.
+  the first nametable is made of tile 1 and the second one of tile 2
+*/
+fn ppu_with_two_nametables() -> PPU {
+    let mut chr = vec![0; 0x2000];
+    chr[0x10..0x18].copy_from_slice(&[0xFF; 8]);
+    chr[0x28..0x30].copy_from_slice(&[0xFF; 8]);
+    let mut ppu = PPU::new(Chr::Rom(chr), Mirroring::Vertical);
+
+    write_vram(&mut ppu, 0x2000, &[1; 0x3C0]);
+    write_vram(&mut ppu, 0x2400, &[2; 0x3C0]);
+    write_vram(
+        &mut ppu,
+        0x3F00,
+        &[0x0F, 0x11, 0x22, 0x33, 0x0F, 0x15, 0x26, 0x37, 0x0F, 0x2A],
+    );
+    // leaves v pointing outside the palette
+    set_vram_addr(&mut ppu, 0x0000);
+    ppu
+}
+
+fn enable_background(ppu: &mut PPU) {
+    ppu.write_register(0x2001, 0b0000_1010);
+}
+
+pub fn run_until(ppu: &mut PPU, scanline: usize, dot: usize) {
+    while ppu.position() != (scanline, dot) {
+        ppu.tick(1);
+    }
+}
+
+// runs a whole frame after the pre-render scanline of the previous one
+pub fn render_frame(ppu: &mut PPU) {
+    run_until(ppu, 261, 0);
+    run_until(ppu, 240, 0);
+}
+
+pub fn pixel(ppu: &PPU, x: usize, y: usize) -> u8 {
+    ppu.frame_buffer.pixel(x, y) as u8
+}
+
+#[test]
+fn test_render_background() {
+    let mut ppu = ppu_with_two_nametables();
+    enable_background(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert!(ppu.frame_buffer.pixels().iter().all(|color| *color == 0x11));
+}
+
+#[test]
+fn test_rendering_disabled_shows_backdrop() {
+    let mut ppu = ppu_with_two_nametables();
+    render_frame(&mut ppu);
+
+    assert!(ppu.frame_buffer.pixels().iter().all(|color| *color == 0x0F));
+}
+
+#[test]
+fn test_hidden_leftmost_background() {
+    let mut ppu = ppu_with_two_nametables();
+    ppu.write_register(0x2001, 0b0000_1000);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 7, 100), 0x0F);
+    assert_eq!(pixel(&ppu, 8, 100), 0x11);
+}
+
+#[test]
+fn test_fine_x_scroll() {
+    let mut ppu = ppu_with_two_nametables();
+    ppu.write_register(0x2005, 4);
+    ppu.write_register(0x2005, 0);
+    enable_background(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 251, 10), 0x11);
+    assert_eq!(pixel(&ppu, 252, 10), 0x22);
+}
+
+#[test]
+fn test_attribute_table() {
+    let mut ppu = ppu_with_two_nametables();
+    // top left 2x2 tiles use palette 1,the top right ones palette 2
+    write_vram(&mut ppu, 0x23C0, &[0b0000_1001]);
+    set_vram_addr(&mut ppu, 0x0000);
+    enable_background(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 15, 15), 0x15);
+    assert_eq!(pixel(&ppu, 16, 15), 0x2A);
+    assert_eq!(pixel(&ppu, 15, 16), 0x11);
+    assert_eq!(pixel(&ppu, 32, 0), 0x11);
+}
+
+#[test]
+fn test_mid_frame_scroll_split() {
+    let mut ppu = ppu_with_two_nametables();
+    enable_background(&mut ppu);
+    run_until(&mut ppu, 261, 0);
+    run_until(&mut ppu, 120, 100);
+    // the new X scroll is only copied to v at dot 257,the vertical one waits for the next frame
+    ppu.write_register(0x2005, 128);
+    ppu.write_register(0x2005, 64);
+    run_until(&mut ppu, 240, 0);
+
+    assert_eq!(pixel(&ppu, 200, 120), 0x11);
+    assert_eq!(pixel(&ppu, 200, 121), 0x22);
+    assert_eq!(pixel(&ppu, 10, 121), 0x11);
+}
+
+#[test]
+fn test_vertical_scroll_wraps_into_next_nametable() {
+    let mut ppu = ppu_with_two_nametables();
+    ppu.write_register(0x2000, 0b01);
+    // Y scroll 232 leaves 8 lines of the first nametable before wrapping
+    ppu.write_register(0x2005, 0);
+    ppu.write_register(0x2005, 232);
+    enable_background(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 0, 7), 0x22);
+    // vertical mirroring,the nametable below the second is the second again
+    assert_eq!(pixel(&ppu, 0, 8), 0x22);
+}
@@ -0,0 +1,82 @@
+use crate::nes::internals::{palette::Palette, ppu::frame::Frame, region::Region};
+
+use super::{
+    background::{render_frame, write_vram},
+    new_ppu, set_vram_addr,
+};
+
+#[test]
+fn test_frame_pixel_keeps_emphasis() {
+    let mut frame = Frame::new();
+    frame.set_pixel(10, 20, 0x16, 0b101);
+
+    assert_eq!(frame.pixel(10, 20), 0b101_010110);
+    assert_eq!(frame.pixels().len(), frame.width() * frame.height());
+}
+
+#[test]
+fn test_frame_to_rgb24() {
+    let mut frame = Frame::new();
+    frame.set_pixel(0, 0, 0x30, 0);
+    frame.set_pixel(1, 0, 0x16, 0);
+    let rgb = frame.to_rgb24(&Palette::default());
+
+    assert_eq!(rgb.len(), 256 * 240 * 3);
+    assert_eq!(&rgb[0..6], &[0xFF, 0xFF, 0xFF, 0xFF, 0x22, 0x00]);
+    // every other pixel is color $00
+    assert_eq!(&rgb[6..9], &[0x80, 0x80, 0x80]);
+}
+
+#[test]
+fn test_frame_to_rgba() {
+    let mut frame = Frame::new();
+    frame.set_pixel(255, 239, 0x0F, 0);
+    let rgba = frame.to_rgba(&Palette::default());
+
+    assert_eq!(rgba.len(), 256 * 240 * 4);
+    assert_eq!(&rgba[rgba.len() - 4..], &[0x05, 0x05, 0x05, 0xFF]);
+}
+
+#[test]
+fn test_ppu_writes_emphasis_to_the_frame() {
+    let mut ppu = new_ppu();
+    write_vram(&mut ppu, 0x3F00, &[0x21]);
+    set_vram_addr(&mut ppu, 0x0000);
+    ppu.write_register(0x2001, 0b1010_0000);
+    render_frame(&mut ppu);
+
+    assert_eq!(ppu.frame_buffer().pixel(100, 100), 0b101_100001);
+}
+
+#[test]
+fn test_pal_ppu_swaps_red_and_green_emphasis() {
+    let mut ppu = new_ppu();
+    ppu.set_region(Region::Pal);
+    write_vram(&mut ppu, 0x3F00, &[0x21]);
+    set_vram_addr(&mut ppu, 0x0000);
+    ppu.write_register(0x2001, 0b1010_0000);
+    render_frame(&mut ppu);
+
+    assert_eq!(ppu.frame_buffer().pixel(100, 100), 0b110_100001);
+}
+
+#[test]
+fn test_greyscale_keeps_only_the_luma() {
+    let mut ppu = new_ppu();
+    write_vram(&mut ppu, 0x3F00, &[0x27]);
+    set_vram_addr(&mut ppu, 0x0000);
+    ppu.write_register(0x2001, 0b0000_0001);
+    render_frame(&mut ppu);
+
+    assert_eq!(ppu.frame_buffer().pixel(100, 100), 0x20);
+}
+
+#[test]
+fn test_greyscale_applies_to_palette_reads() {
+    let mut ppu = new_ppu();
+    write_vram(&mut ppu, 0x3F01, &[0x16]);
+    ppu.write_register(0x2001, 0b0000_0001);
+    set_vram_addr(&mut ppu, 0x3F01);
+
+    assert_eq!(ppu.read_register(0x2007) & 0x3F, 0x10);
+}
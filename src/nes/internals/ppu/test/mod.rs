@@ -0,0 +1,259 @@
+use crate::nes::internals::{
+    cartridge::{Chr, Mirroring},
+    ppu::{registers::StatusRegister, PPU},
+    ram_pattern::RamPattern,
+};
+
+mod background;
+mod frame;
+mod region;
+mod sprites;
+mod timing;
+mod viewer;
+mod vram;
+
+pub fn new_ppu() -> PPU {
+    PPU::new(Chr::Ram(vec![0; 0x2000]), Mirroring::Horizontal)
+}
+
+pub fn set_vram_addr(ppu: &mut PPU, addr: u16) {
+    ppu.write_register(0x2006, (addr >> 8) as u8);
+    ppu.write_register(0x2006, addr as u8);
+}
+
+pub fn is_in_vblank(ppu: &PPU) -> bool {
+    ppu.status().contains(StatusRegister::VBLANK_STARTED)
+}
+
+#[test]
+fn test_ppudata_read_is_delayed_by_the_buffer() {
+    let mut ppu = new_ppu();
+    set_vram_addr(&mut ppu, 0x2305);
+    ppu.write_register(0x2007, 0x66);
+    ppu.write_register(0x2007, 0x77);
+
+    set_vram_addr(&mut ppu, 0x2305);
+    assert_eq!(ppu.read_register(0x2007), 0x00);
+    assert_eq!(ppu.read_register(0x2007), 0x66);
+    assert_eq!(ppu.read_register(0x2007), 0x77);
+}
+
+#[test]
+fn test_ppudata_increment_by_32() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2000, 0b0000_0100);
+    set_vram_addr(&mut ppu, 0x2000);
+    ppu.write_register(0x2007, 0x11);
+    ppu.write_register(0x2007, 0x22);
+
+    assert_eq!(ppu.vram_addr(), 0x2040);
+    set_vram_addr(&mut ppu, 0x2020);
+    ppu.read_register(0x2007);
+    assert_eq!(ppu.read_register(0x2007), 0x22);
+}
+
+#[test]
+fn test_ppuaddr_is_14_bits_wide() {
+    let mut ppu = new_ppu();
+    set_vram_addr(&mut ppu, 0xFF05);
+    assert_eq!(ppu.vram_addr(), 0x3F05);
+
+    ppu.write_register(0x2007, 0x00);
+    set_vram_addr(&mut ppu, 0x3FFF);
+    ppu.write_register(0x2007, 0x00);
+    assert_eq!(ppu.vram_addr() & 0x3FFF, 0x0000);
+}
+
+#[test]
+fn test_palette_read_bypasses_the_buffer() {
+    let mut ppu = new_ppu();
+    set_vram_addr(&mut ppu, 0x2F01);
+    ppu.write_register(0x2007, 0x99);
+    set_vram_addr(&mut ppu, 0x3F01);
+    ppu.write_register(0x2007, 0x2A);
+
+    set_vram_addr(&mut ppu, 0x3F01);
+    assert_eq!(ppu.read_register(0x2007) & 0x3F, 0x2A);
+    // the buffer was filled with the nametable byte under the palette
+    assert_eq!(ppu.read_buffer(), 0x99);
+}
+
+#[test]
+fn test_palette_read_upper_bits_come_from_open_bus() {
+    let mut ppu = new_ppu();
+    set_vram_addr(&mut ppu, 0x3F00);
+    ppu.write_register(0x2007, 0xFF);
+    set_vram_addr(&mut ppu, 0x3F00);
+    ppu.write_register(0x2000, 0x80);
+
+    assert_eq!(ppu.read_register(0x2007), 0xBF);
+}
+
+#[test]
+fn test_status_read_clears_vblank_and_write_toggle() {
+    let mut ppu = new_ppu();
+    ppu.status.insert(StatusRegister::VBLANK_STARTED);
+    ppu.write_register(0x2006, 0x21);
+    assert!(ppu.write_toggle());
+
+    assert_eq!(ppu.read_register(0x2002) & 0x80, 0x80);
+    assert!(!ppu.write_toggle());
+    assert_eq!(ppu.read_register(0x2002) & 0x80, 0x00);
+
+    // the toggle restarted,so this is the high byte again
+    set_vram_addr(&mut ppu, 0x2400);
+    assert_eq!(ppu.vram_addr(), 0x2400);
+}
+
+#[test]
+fn test_status_low_bits_come_from_open_bus() {
+    let mut ppu = new_ppu();
+    ppu.status.insert(StatusRegister::SPRITE_ZERO_HIT);
+    ppu.write_register(0x2000, 0x1F);
+
+    assert_eq!(ppu.read_register(0x2002), 0x5F);
+}
+
+#[test]
+fn test_scroll_double_write() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2000, 0b10);
+    // x = 0b01111_101,y = 0b01011_110
+    ppu.write_register(0x2005, 0x7D);
+    ppu.write_register(0x2005, 0x5E);
+
+    // fine Y 6,nametable 2,coarse Y 11 and coarse X 15
+    assert_eq!(ppu.temp_vram_addr(), 0x696F);
+    assert_eq!(ppu.fine_x(), 0b101);
+    assert!(!ppu.write_toggle());
+}
+
+#[test]
+fn test_ppuaddr_second_write_copies_t_to_v() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2005, 0x7D);
+    ppu.write_register(0x2005, 0x5E);
+    // PPUSCROLL and PPUADDR share the toggle and t
+    ppu.write_register(0x2006, 0x3D);
+    assert_eq!(ppu.temp_vram_addr(), 0x3D6F);
+    assert_eq!(ppu.vram_addr(), 0x0000);
+
+    ppu.write_register(0x2006, 0xF0);
+    assert_eq!(ppu.vram_addr(), 0x3DF0);
+    // the first write after PPUADDR is the X scroll again
+    ppu.write_register(0x2005, 0x08);
+    assert_eq!(ppu.temp_vram_addr(), 0x3DE1);
+}
+
+#[test]
+fn test_oamdata_write_increments_oamaddr() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2003, 0x10);
+    ppu.write_register(0x2004, 0x66);
+    ppu.write_register(0x2004, 0x77);
+
+    assert_eq!(ppu.oam_addr(), 0x12);
+    assert_eq!(ppu.oam_data[0x10], 0x66);
+    assert_eq!(ppu.oam_data[0x11], 0x77);
+
+    // reads don't increment
+    ppu.write_register(0x2003, 0x10);
+    assert_eq!(ppu.read_register(0x2004), 0x66);
+    assert_eq!(ppu.read_register(0x2004), 0x66);
+}
+
+#[test]
+fn test_oam_attribute_unused_bits_read_as_zero() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2003, 0x02);
+    ppu.write_register(0x2004, 0xFF);
+    ppu.write_register(0x2003, 0x02);
+
+    assert_eq!(ppu.read_register(0x2004), 0xE3);
+}
+
+#[test]
+fn test_write_only_registers_read_open_bus() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2001, 0x5A);
+
+    assert_eq!(ppu.read_register(0x2000), 0x5A);
+    assert_eq!(ppu.read_register(0x2005), 0x5A);
+    assert_eq!(ppu.peek_register(0x2006), 0x5A);
+}
+
+#[test]
+fn test_open_bus_decays() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2000, 0xF0);
+    ppu.tick(341 * 262 * 20);
+    ppu.status = StatusRegister::all();
+    // reading $2002 refreshes only the top 3 bits
+    ppu.read_register(0x2002);
+    ppu.tick(341 * 262 * 20);
+
+    assert_eq!(ppu.read_register(0x2000), 0xE0);
+}
+
+#[test]
+fn test_peek_has_no_side_effects() {
+    let mut ppu = new_ppu();
+    ppu.status.insert(StatusRegister::VBLANK_STARTED);
+    ppu.write_register(0x2006, 0x20);
+
+    assert_eq!(ppu.peek_register(0x2002) & 0x80, 0x80);
+    ppu.peek_register(0x2007);
+    assert!(ppu.status().contains(StatusRegister::VBLANK_STARTED));
+    assert!(ppu.write_toggle());
+    assert_eq!(ppu.temp_vram_addr(), 0x2000);
+}
+
+#[test]
+fn test_reset_keeps_vram() {
+    let mut ppu = new_ppu();
+    set_vram_addr(&mut ppu, 0x2305);
+    ppu.write_register(0x2007, 0x66);
+    ppu.write_register(0x2000, 0b1000_0100);
+    ppu.write_register(0x2001, 0b0001_1000);
+    ppu.write_register(0x2005, 0x12);
+
+    ppu.reset();
+    assert!(ppu.ctrl.is_empty());
+    assert!(ppu.mask.is_empty());
+    assert!(!ppu.write_toggle);
+    set_vram_addr(&mut ppu, 0x2305);
+    ppu.read_register(0x2007);
+    assert_eq!(ppu.read_register(0x2007), 0x66);
+}
+
+#[test]
+fn test_power_on_clears_vram_and_chr_ram() {
+    let mut ppu = new_ppu();
+    ppu.set_unlimited_sprites(true);
+    set_vram_addr(&mut ppu, 0x0010);
+    ppu.write_register(0x2007, 0x55);
+    set_vram_addr(&mut ppu, 0x2305);
+    ppu.write_register(0x2007, 0x66);
+    ppu.tick(400);
+    let frame = ppu.frame;
+
+    ppu.power_on(&mut RamPattern::Zeros.filler());
+    assert!(ppu.unlimited_sprites());
+    assert_eq!(ppu.frame, frame);
+    set_vram_addr(&mut ppu, 0x0010);
+    ppu.read_register(0x2007);
+    assert_eq!(ppu.read_register(0x2007), 0x00);
+    set_vram_addr(&mut ppu, 0x2305);
+    ppu.read_register(0x2007);
+    assert_eq!(ppu.read_register(0x2007), 0x00);
+}
+
+#[test]
+fn test_power_on_fills_chr_ram_and_oam_with_the_pattern() {
+    let mut ppu = new_ppu();
+    ppu.power_on(&mut RamPattern::Ones.filler());
+    assert!(ppu.oam_data.iter().all(|&byte| byte == 0xFF));
+    set_vram_addr(&mut ppu, 0x0010);
+    ppu.read_register(0x2007);
+    assert_eq!(ppu.read_register(0x2007), 0xFF);
+}
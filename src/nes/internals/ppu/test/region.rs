@@ -0,0 +1,72 @@
+use crate::nes::internals::{bus::Bus, ppu::PPU, region::Region};
+
+use super::{background::run_until, is_in_vblank, new_ppu};
+
+fn new_ppu_for(region: Region) -> PPU {
+    let mut ppu = new_ppu();
+    ppu.set_region(region);
+    ppu
+}
+
+#[test]
+fn test_pal_frame_has_312_scanlines() {
+    let mut ppu = new_ppu_for(Region::Pal);
+    // rendering on,PAL never skips the dot of odd frames
+    ppu.write_register(0x2001, 0b0000_1000);
+    ppu.tick(341 * 312 * 2);
+
+    assert_eq!(ppu.position(), (0, 0));
+    assert_eq!(ppu.frame_count(), 2);
+}
+
+#[test]
+fn test_pal_vblank() {
+    let mut ppu = new_ppu_for(Region::Pal);
+    ppu.write_register(0x2000, 0x80);
+    run_until(&mut ppu, 241, 2);
+    assert!(is_in_vblank(&ppu));
+    assert!(ppu.poll_nmi());
+
+    // it lasts 70 scanlines,until the pre-render one
+    run_until(&mut ppu, 311, 1);
+    assert!(is_in_vblank(&ppu));
+    ppu.tick(1);
+    assert!(!is_in_vblank(&ppu));
+}
+
+#[test]
+fn test_dendy_vblank_starts_at_scanline_291() {
+    let mut ppu = new_ppu_for(Region::Dendy);
+    ppu.write_register(0x2000, 0x80);
+    run_until(&mut ppu, 241, 2);
+    assert!(!is_in_vblank(&ppu));
+    assert!(!ppu.poll_nmi());
+
+    run_until(&mut ppu, 291, 2);
+    assert!(is_in_vblank(&ppu));
+    assert!(ppu.poll_nmi());
+}
+
+#[test]
+fn test_pal_ppu_runs_16_dots_every_5_cycles() {
+    let mut bus = Bus::new();
+    bus.set_region(Region::Pal);
+    for _ in 0..5 {
+        bus.tick(1);
+    }
+    assert_eq!(bus.ppu_position(), (0, 16));
+
+    bus.tick(7);
+    // 22.4 dots,the fraction is kept for the next cycles
+    assert_eq!(bus.ppu_position(), (0, 38));
+    bus.tick(3);
+    assert_eq!(bus.ppu_position(), (0, 48));
+}
+
+#[test]
+fn test_dendy_ppu_runs_3_dots_per_cycle() {
+    let mut bus = Bus::new();
+    bus.set_region(Region::Dendy);
+    bus.tick(5);
+    assert_eq!(bus.ppu_position(), (0, 15));
+}
@@ -0,0 +1,265 @@
+use crate::nes::internals::{
+    cartridge::{Chr, Mirroring},
+    ppu::{registers::StatusRegister, PPU},
+};
+
+use super::{
+    background::{pixel, render_frame, run_until, write_vram},
+    set_vram_addr,
+};
+
+const BACKDROP: u8 = 0x0F;
+const BACKGROUND: u8 = 0x11;
+const SPRITE_COLOR_1: u8 = 0x21;
+const SPRITE_COLOR_2: u8 = 0x22;
+
+/*
+  tile 1 is filled with color 1,tile 2 with color 2,tile 3 only has its left half filled
+  and tile 4 only its first row.
+  tiles 2 and 3 of the second table are used by the 8x16 tests
+*/
+fn new_ppu(opaque_background: bool) -> PPU {
+    let mut chr = vec![0; 0x2000];
+    chr[0x10..0x18].copy_from_slice(&[0xFF; 8]);
+    chr[0x28..0x30].copy_from_slice(&[0xFF; 8]);
+    chr[0x30..0x38].copy_from_slice(&[0xF0; 8]);
+    chr[0x40] = 0xFF;
+    chr[0x1020..0x1028].copy_from_slice(&[0xFF; 8]);
+    chr[0x1038..0x1040].copy_from_slice(&[0xFF; 8]);
+    let mut ppu = PPU::new(Chr::Rom(chr), Mirroring::Vertical);
+
+    if opaque_background {
+        write_vram(&mut ppu, 0x2000, &[1; 0x3C0]);
+    }
+    write_vram(&mut ppu, 0x3F00, &[BACKDROP, BACKGROUND]);
+    write_vram(&mut ppu, 0x3F11, &[SPRITE_COLOR_1, SPRITE_COLOR_2]);
+    set_vram_addr(&mut ppu, 0x0000);
+    // hides every sprite below the screen
+    ppu.write_register(0x2003, 0);
+    for _ in 0..256 {
+        ppu.write_register(0x2004, 0xFF);
+    }
+    ppu
+}
+
+fn set_sprite(ppu: &mut PPU, index: u8, y: u8, tile: u8, attributes: u8, x: u8) {
+    ppu.write_register(0x2003, index * 4);
+    for byte in [y, tile, attributes, x] {
+        ppu.write_register(0x2004, byte);
+    }
+}
+
+// background and sprites,including the leftmost 8 pixels
+fn enable_rendering(ppu: &mut PPU) {
+    ppu.write_register(0x2001, 0b0001_1110);
+}
+
+#[test]
+fn test_render_sprite() {
+    let mut ppu = new_ppu(false);
+    // the sprite shows up one scanline below its Y
+    set_sprite(&mut ppu, 0, 9, 1, 0, 20);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 20, 10), SPRITE_COLOR_1);
+    assert_eq!(pixel(&ppu, 27, 17), SPRITE_COLOR_1);
+    assert_eq!(pixel(&ppu, 28, 10), BACKDROP);
+    assert_eq!(pixel(&ppu, 20, 9), BACKDROP);
+    assert_eq!(pixel(&ppu, 20, 18), BACKDROP);
+}
+
+#[test]
+fn test_sprite_palette() {
+    let mut ppu = new_ppu(false);
+    set_sprite(&mut ppu, 0, 9, 2, 0, 20);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 20, 10), SPRITE_COLOR_2);
+}
+
+#[test]
+fn test_sprite_priority() {
+    let mut ppu = new_ppu(true);
+    set_sprite(&mut ppu, 0, 9, 1, 0b0010_0000, 20);
+    set_sprite(&mut ppu, 1, 9, 1, 0, 40);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 20, 10), BACKGROUND);
+    assert_eq!(pixel(&ppu, 40, 10), SPRITE_COLOR_1);
+}
+
+#[test]
+fn test_first_sprite_in_oam_wins() {
+    let mut ppu = new_ppu(false);
+    set_sprite(&mut ppu, 0, 9, 2, 0, 20);
+    set_sprite(&mut ppu, 1, 9, 1, 0, 24);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 27, 10), SPRITE_COLOR_2);
+    assert_eq!(pixel(&ppu, 28, 10), SPRITE_COLOR_1);
+}
+
+#[test]
+fn test_sprite_flips() {
+    let mut ppu = new_ppu(false);
+    set_sprite(&mut ppu, 0, 9, 3, 0b0100_0000, 20);
+    set_sprite(&mut ppu, 1, 9, 4, 0b1000_0000, 40);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 23, 10), BACKDROP);
+    assert_eq!(pixel(&ppu, 24, 10), SPRITE_COLOR_1);
+    assert_eq!(pixel(&ppu, 40, 10), BACKDROP);
+    assert_eq!(pixel(&ppu, 40, 17), SPRITE_COLOR_1);
+}
+
+#[test]
+fn test_8x16_sprites() {
+    let mut ppu = new_ppu(false);
+    ppu.write_register(0x2000, 0b0010_0000);
+    // bit 0 of the tile selects the $1000 table,the sprite uses tiles 2 and 3
+    set_sprite(&mut ppu, 0, 9, 0x03, 0, 20);
+    set_sprite(&mut ppu, 1, 9, 0x03, 0b1000_0000, 40);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 20, 10), SPRITE_COLOR_1);
+    assert_eq!(pixel(&ppu, 20, 18), SPRITE_COLOR_2);
+    assert_eq!(pixel(&ppu, 20, 25), SPRITE_COLOR_2);
+    assert_eq!(pixel(&ppu, 20, 26), BACKDROP);
+    // flipped vertically the bottom tile comes first
+    assert_eq!(pixel(&ppu, 40, 10), SPRITE_COLOR_2);
+    assert_eq!(pixel(&ppu, 40, 25), SPRITE_COLOR_1);
+}
+
+#[test]
+fn test_8_sprites_per_scanline() {
+    let mut ppu = new_ppu(false);
+    for index in 0..9 {
+        set_sprite(&mut ppu, index, 9, 1, 0, index * 10);
+    }
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 70, 10), SPRITE_COLOR_1);
+    assert_eq!(pixel(&ppu, 80, 10), BACKDROP);
+    assert!(ppu.status().contains(StatusRegister::SPRITE_OVERFLOW));
+}
+
+#[test]
+fn test_unlimited_sprites() {
+    let mut ppu = new_ppu(false);
+    ppu.set_unlimited_sprites(true);
+    for index in 0..12 {
+        set_sprite(&mut ppu, index, 9, 1, 0, index * 10);
+    }
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert_eq!(pixel(&ppu, 80, 10), SPRITE_COLOR_1);
+    assert_eq!(pixel(&ppu, 110, 10), SPRITE_COLOR_1);
+    assert!(ppu.status().contains(StatusRegister::SPRITE_OVERFLOW));
+}
+
+#[test]
+fn test_sprite_overflow_false_positive() {
+    let mut ppu = new_ppu(false);
+    for index in 0..8 {
+        set_sprite(&mut ppu, index, 9, 1, 0, index * 10);
+    }
+    // sprite 9 is not in range,but the buggy evaluation reads its tile as the Y position
+    set_sprite(&mut ppu, 9, 0xF0, 10, 0, 200);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert!(ppu.status().contains(StatusRegister::SPRITE_OVERFLOW));
+}
+
+#[test]
+fn test_sprite_overflow_false_negative() {
+    let mut ppu = new_ppu(false);
+    for index in 0..8 {
+        set_sprite(&mut ppu, index, 9, 1, 0, index * 10);
+    }
+    // sprite 8 is in range,but after sprite 8 is skipped the evaluation checks the tile of sprite 9
+    set_sprite(&mut ppu, 8, 0xF0, 1, 0, 200);
+    set_sprite(&mut ppu, 9, 9, 0xF0, 0, 200);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert!(!ppu.status().contains(StatusRegister::SPRITE_OVERFLOW));
+}
+
+#[test]
+fn test_sprite_zero_hit() {
+    let mut ppu = new_ppu(true);
+    set_sprite(&mut ppu, 0, 49, 1, 0b0010_0000, 50);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    // priority doesn't matter for the hit
+    assert!(ppu.status().contains(StatusRegister::SPRITE_ZERO_HIT));
+    // the event log gets the dot of the first pixel that overlaps
+    assert_eq!(ppu.take_sprite_zero_hit(), Some((50, 51)));
+    assert_eq!(ppu.take_sprite_zero_hit(), None);
+    run_until(&mut ppu, 261, 2);
+    assert!(!ppu.status().contains(StatusRegister::SPRITE_ZERO_HIT));
+}
+
+#[test]
+fn test_no_sprite_zero_hit_on_transparent_background() {
+    let mut ppu = new_ppu(false);
+    set_sprite(&mut ppu, 0, 49, 1, 0, 50);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert!(!ppu.status().contains(StatusRegister::SPRITE_ZERO_HIT));
+}
+
+#[test]
+fn test_only_sprite_zero_hits() {
+    let mut ppu = new_ppu(true);
+    set_sprite(&mut ppu, 1, 49, 1, 0, 50);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert!(!ppu.status().contains(StatusRegister::SPRITE_ZERO_HIT));
+}
+
+#[test]
+fn test_no_sprite_zero_hit_at_x_255() {
+    let mut ppu = new_ppu(true);
+    set_sprite(&mut ppu, 0, 49, 1, 0, 255);
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+
+    assert!(!ppu.status().contains(StatusRegister::SPRITE_ZERO_HIT));
+}
+
+#[test]
+fn test_no_sprite_zero_hit_in_clipped_left_column() {
+    let mut ppu = new_ppu(true);
+    // only the first 4 pixels of the sprite overlap,all of them inside the left column
+    set_sprite(&mut ppu, 0, 49, 3, 0, 0);
+    ppu.write_register(0x2001, 0b0001_1000);
+    render_frame(&mut ppu);
+    assert!(!ppu.status().contains(StatusRegister::SPRITE_ZERO_HIT));
+
+    enable_rendering(&mut ppu);
+    render_frame(&mut ppu);
+    assert!(ppu.status().contains(StatusRegister::SPRITE_ZERO_HIT));
+}
+
+#[test]
+fn test_oamaddr_is_reset_while_fetching_sprites() {
+    let mut ppu = new_ppu(false);
+    enable_rendering(&mut ppu);
+    ppu.write_register(0x2003, 0x20);
+    run_until(&mut ppu, 10, 300);
+
+    assert_eq!(ppu.oam_addr(), 0);
+}
@@ -0,0 +1,153 @@
+use crate::nes::internals::{bus::Bus, cpu::CPU, memory::Memory, ppu::PPU};
+
+use super::{background::run_until, is_in_vblank, new_ppu};
+
+// amount of dots until the next frame starts
+fn frame_length(ppu: &mut PPU) -> usize {
+    let mut dots = 0;
+    loop {
+        ppu.tick(1);
+        dots += 1;
+        if ppu.position() == (0, 0) {
+            return dots;
+        }
+    }
+}
+
+#[test]
+fn test_vblank_starts_at_scanline_241_dot_1() {
+    let mut ppu = new_ppu();
+    run_until(&mut ppu, 241, 1);
+    assert!(!is_in_vblank(&ppu));
+
+    ppu.tick(1);
+    assert!(is_in_vblank(&ppu));
+}
+
+#[test]
+fn test_vblank_ends_at_pre_render_scanline() {
+    let mut ppu = new_ppu();
+    run_until(&mut ppu, 261, 1);
+    assert!(is_in_vblank(&ppu));
+
+    ppu.tick(1);
+    assert!(!is_in_vblank(&ppu));
+}
+
+#[test]
+fn test_nmi_at_vblank() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2000, 0x80);
+    run_until(&mut ppu, 241, 2);
+
+    assert!(ppu.poll_nmi());
+    assert!(!ppu.poll_nmi());
+}
+
+#[test]
+fn test_no_nmi_when_disabled() {
+    let mut ppu = new_ppu();
+    run_until(&mut ppu, 241, 2);
+    assert!(!ppu.poll_nmi());
+}
+
+#[test]
+fn test_enabling_nmi_during_vblank() {
+    let mut ppu = new_ppu();
+    run_until(&mut ppu, 250, 0);
+    ppu.write_register(0x2000, 0x80);
+    assert!(ppu.poll_nmi());
+
+    // the line only goes low again after a disable
+    ppu.write_register(0x2000, 0x80);
+    assert!(!ppu.poll_nmi());
+    ppu.write_register(0x2000, 0x00);
+    ppu.write_register(0x2000, 0x80);
+    assert!(ppu.poll_nmi());
+}
+
+#[test]
+fn test_status_read_before_vblank_suppresses_it() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2000, 0x80);
+    run_until(&mut ppu, 241, 1);
+
+    assert_eq!(ppu.read_register(0x2002) & 0x80, 0);
+    run_until(&mut ppu, 250, 0);
+    assert!(!is_in_vblank(&ppu));
+    assert!(!ppu.poll_nmi());
+}
+
+#[test]
+fn test_status_read_right_after_vblank_suppresses_nmi() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2000, 0x80);
+    run_until(&mut ppu, 241, 3);
+
+    assert_eq!(ppu.read_register(0x2002) & 0x80, 0x80);
+    assert!(!ppu.poll_nmi());
+}
+
+#[test]
+fn test_status_read_late_in_vblank_keeps_nmi() {
+    let mut ppu = new_ppu();
+    ppu.write_register(0x2000, 0x80);
+    run_until(&mut ppu, 241, 4);
+
+    assert_eq!(ppu.read_register(0x2002) & 0x80, 0x80);
+    assert!(ppu.poll_nmi());
+}
+
+#[test]
+fn test_odd_frames_skip_a_dot_when_rendering() {
+    let mut ppu = new_ppu();
+    assert_eq!(frame_length(&mut ppu), 341 * 262);
+    assert_eq!(frame_length(&mut ppu), 341 * 262);
+
+    ppu.write_register(0x2001, 0b0000_1000);
+    assert_eq!(frame_length(&mut ppu), 341 * 262);
+    assert_eq!(frame_length(&mut ppu), 341 * 262 - 1);
+    assert_eq!(frame_length(&mut ppu), 341 * 262);
+}
+
+#[test]
+fn test_status_is_read_in_the_middle_of_the_instruction() {
+    let mut bus = Bus::new();
+    // (241,1) is 27394 cycles away,the instruction starts 2 cycles before it
+    for _ in 0..27392 {
+        bus.tick(1);
+    }
+    let mut cpu = CPU::new(bus);
+    /*
+    LDA $2002
+    STA $10
+    BRK
+     */
+    cpu.load(vec![0xad, 0x02, 0x20, 0x85, 0x10, 0x00]);
+    cpu.run();
+
+    // the read happens on the 4th cycle,after vblank started
+    assert_eq!(cpu.read_from_memory(0x10) & 0x80, 0x80);
+}
+
+#[test]
+fn test_cpu_takes_the_nmi() {
+    let mut bus = Bus::new();
+    bus.poke_memory(0xFFFA, 0x00);
+    bus.poke_memory(0xFFFB, 0x07);
+    let mut cpu = CPU::new(bus);
+    /*
+    0600 LDA #$80
+    0602 STA $2000
+    0605 JMP $0605
+    0700 INX
+    0701 BRK
+     */
+    cpu.load(vec![0xa9, 0x80, 0x8d, 0x00, 0x20, 0x4c, 0x05, 0x06]);
+    cpu.poke_memory(0x0700, 0xe8);
+    cpu.poke_memory(0x0701, 0x00);
+    cpu.run();
+
+    assert_eq!(cpu.registers().x, 1);
+    assert_eq!(cpu.bus().ppu_position().0, 241);
+}
@@ -0,0 +1,113 @@
+use crate::nes::internals::{
+    image::pack,
+    palette::Palette,
+    ppu::{test::background::write_vram, PPU},
+};
+
+use super::new_ppu;
+
+const SCROLL_COLOR: u32 = 0xFF00FF;
+
+fn color(palette: &Palette, index: u16) -> u32 {
+    let (r, g, b) = palette.color(index);
+    pack(r, g, b)
+}
+
+// tile 1 has a single pixel of value 1 in its top left corner and one of value 3 in its bottom right
+fn ppu_with_tiles() -> PPU {
+    let mut ppu = new_ppu();
+    write_vram(&mut ppu, 0x0010, &[0x80, 0, 0, 0, 0, 0, 0, 0x01]);
+    write_vram(&mut ppu, 0x0018, &[0, 0, 0, 0, 0, 0, 0, 0x01]);
+    write_vram(
+        &mut ppu,
+        0x3F00,
+        &[0x0F, 0x16, 0x27, 0x30, 0x0F, 0x1A, 0x2A, 0x3A],
+    );
+    write_vram(&mut ppu, 0x3F14, &[0x0F, 0x12, 0x22, 0x32]);
+    ppu
+}
+
+#[test]
+fn test_pattern_table_image() {
+    let ppu = ppu_with_tiles();
+    let palette = Palette::default();
+    let image = ppu.pattern_table_image(0, 0, &palette);
+
+    assert_eq!((image.width, image.height), (128, 128));
+    assert_eq!(image.get(8, 0), color(&palette, 0x16));
+    assert_eq!(image.get(15, 7), color(&palette, 0x30));
+    assert_eq!(image.get(9, 0), color(&palette, 0x0F));
+
+    // the palette is selectable
+    let image = ppu.pattern_table_image(0, 5, &palette);
+    assert_eq!(image.get(8, 0), color(&palette, 0x12));
+}
+
+#[test]
+fn test_nametables_image_with_scroll() {
+    let mut ppu = ppu_with_tiles();
+    // tile 1 in the first tile of the second nametable,with palette 1
+    write_vram(&mut ppu, 0x2800, &[0x01]);
+    write_vram(&mut ppu, 0x2BC0, &[0x01]);
+    // scroll to (16,8),PPUADDR also wrote the nametable bits of t
+    ppu.write_register(0x2000, 0);
+    ppu.write_register(0x2005, 16);
+    ppu.write_register(0x2005, 8);
+    let palette = Palette::default();
+    let image = ppu.nametables_image(&palette);
+
+    assert_eq!((image.width, image.height), (512, 480));
+    // horizontal mirroring,$2800 is the bottom half
+    assert_eq!(image.get(0, 240), color(&palette, 0x1A));
+    assert_eq!(image.get(7, 247), color(&palette, 0x3A));
+    // the scroll rectangle
+    assert_eq!(image.get(16, 8), SCROLL_COLOR);
+    assert_eq!(image.get(271, 247), SCROLL_COLOR);
+    assert_ne!(image.get(17, 9), SCROLL_COLOR);
+}
+
+#[test]
+fn test_scroll_rectangle_wraps() {
+    let mut ppu = new_ppu();
+    // 8 pixels into the last nametable,the right edge wraps to the left ones
+    ppu.write_register(0x2000, 0b11);
+    ppu.write_register(0x2005, 8);
+    ppu.write_register(0x2005, 0);
+    let image = ppu.nametables_image(&Palette::default());
+
+    assert_eq!(image.get(264, 240), SCROLL_COLOR);
+    assert_eq!(image.get(511, 479), SCROLL_COLOR);
+    assert_eq!(image.get(0, 240), SCROLL_COLOR);
+    assert_eq!(image.get(7, 300), SCROLL_COLOR);
+    assert_ne!(image.get(8, 300), SCROLL_COLOR);
+}
+
+#[test]
+fn test_oam_image() {
+    let mut ppu = ppu_with_tiles();
+    // sprite 9 uses tile 1,palette 5 and is flipped horizontally
+    ppu.write_register(0x2003, 9 * 4);
+    for value in [0x10, 0x01, 0b0100_0001, 0x20] {
+        ppu.write_register(0x2004, value);
+    }
+    let palette = Palette::default();
+    let image = ppu.oam_image(&palette);
+
+    assert_eq!((image.width, image.height), (80, 144));
+    // second row,second column,the cell starts 1 pixel in
+    let (x, y) = (10 + 1, 18 + 1);
+    assert_eq!(image.get(x + 7, y), color(&palette, 0x12));
+    assert_eq!(image.get(x, y + 7), color(&palette, 0x32));
+    assert_eq!(image.get(x, y), color(&palette, 0x0F));
+}
+
+#[test]
+fn test_palette_image() {
+    let ppu = ppu_with_tiles();
+    let palette = Palette::default();
+    let image = ppu.palette_image(&palette);
+
+    assert_eq!((image.width, image.height), (256, 32));
+    assert_eq!(image.get(16, 0), color(&palette, 0x16));
+    assert_eq!(image.get(5 * 16 + 15, 16 + 15), color(&palette, 0x12));
+}
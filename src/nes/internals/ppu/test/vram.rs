@@ -0,0 +1,90 @@
+use crate::nes::internals::{
+    cartridge::{Chr, Mirroring},
+    ppu::PPU,
+};
+
+use super::{background::write_vram, set_vram_addr};
+
+fn read_vram(ppu: &mut PPU, addr: u16) -> u8 {
+    set_vram_addr(ppu, addr);
+    ppu.read_register(0x2007);
+    ppu.read_register(0x2007)
+}
+
+// palette reads don't go through the read buffer
+fn read_palette(ppu: &mut PPU, addr: u16) -> u8 {
+    set_vram_addr(ppu, addr);
+    ppu.read_register(0x2007) & 0x3F
+}
+
+// writes a different value at the start of each nametable and returns what is read back from all of them
+fn nametables_after_writes(mirroring: Mirroring) -> [u8; 4] {
+    let mut ppu = PPU::new(Chr::Ram(vec![0; 0x2000]), mirroring);
+    for (i, addr) in [0x2000, 0x2400, 0x2800, 0x2C00].iter().enumerate() {
+        write_vram(&mut ppu, *addr, &[i as u8 + 1]);
+    }
+    [0x2000, 0x2400, 0x2800, 0x2C00].map(|addr| read_vram(&mut ppu, addr))
+}
+
+#[test]
+fn test_horizontal_mirroring() {
+    assert_eq!(nametables_after_writes(Mirroring::Horizontal), [2, 2, 4, 4]);
+}
+
+#[test]
+fn test_vertical_mirroring() {
+    assert_eq!(nametables_after_writes(Mirroring::Vertical), [3, 4, 3, 4]);
+}
+
+#[test]
+fn test_single_screen_mirroring() {
+    assert_eq!(
+        nametables_after_writes(Mirroring::SingleScreenLower),
+        [4, 4, 4, 4]
+    );
+
+    let mut ppu = PPU::new(Chr::Ram(vec![0; 0x2000]), Mirroring::SingleScreenUpper);
+    write_vram(&mut ppu, 0x2C05, &[0x42]);
+    assert_eq!(read_vram(&mut ppu, 0x2005), 0x42);
+}
+
+#[test]
+fn test_four_screen_mirroring() {
+    assert_eq!(nametables_after_writes(Mirroring::FourScreen), [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_nametables_are_mirrored_at_3000() {
+    let mut ppu = PPU::new(Chr::Ram(vec![0; 0x2000]), Mirroring::Vertical);
+    write_vram(&mut ppu, 0x3123, &[0x42]);
+    assert_eq!(read_vram(&mut ppu, 0x2123), 0x42);
+}
+
+#[test]
+fn test_pattern_tables_come_from_the_cartridge() {
+    let mut chr = vec![0; 0x2000];
+    chr[0x1234] = 0x42;
+    let mut ppu = PPU::new(Chr::Rom(chr), Mirroring::Horizontal);
+
+    assert_eq!(read_vram(&mut ppu, 0x1234), 0x42);
+    write_vram(&mut ppu, 0x1234, &[0x99]);
+    assert_eq!(read_vram(&mut ppu, 0x1234), 0x42);
+}
+
+#[test]
+fn test_palette_background_mirrors() {
+    let mut ppu = PPU::new(Chr::Ram(vec![0; 0x2000]), Mirroring::Horizontal);
+    for (i, addr) in [0x3F10, 0x3F14, 0x3F18, 0x3F1C].iter().enumerate() {
+        write_vram(&mut ppu, *addr, &[i as u8 + 1]);
+    }
+    write_vram(&mut ppu, 0x3F11, &[0x20]);
+
+    assert_eq!(read_palette(&mut ppu, 0x3F00), 1);
+    assert_eq!(read_palette(&mut ppu, 0x3F04), 2);
+    assert_eq!(read_palette(&mut ppu, 0x3F08), 3);
+    assert_eq!(read_palette(&mut ppu, 0x3F0C), 4);
+    // the sprite entries in between are not mirrored
+    assert_eq!(read_palette(&mut ppu, 0x3F01), 0);
+    // $3F20-$3FFF repeats the 32 entries
+    assert_eq!(read_palette(&mut ppu, 0x3F31), 0x20);
+}
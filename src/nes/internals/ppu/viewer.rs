@@ -0,0 +1,269 @@
+use std::fmt;
+
+use super::{
+    super::{
+        image::{pack, Image},
+        palette::Palette,
+    },
+    registers::ControlRegister,
+    sprites::{
+        ATTRIBUTE_BEHIND_BACKGROUND, ATTRIBUTE_FLIP_HORIZONTAL, ATTRIBUTE_FLIP_VERTICAL,
+        ATTRIBUTE_PALETTE, OAM_SPRITES,
+    },
+    NAMETABLES, NAMETABLE_SIZE, PALETTE_TABLE, PPU, SCREEN_HEIGHT, SCREEN_WIDTH,
+};
+
+// a pattern table is 16x16 tiles of 8x8 pixels
+pub const PATTERN_TABLE_SIZE: usize = 128;
+const TILES_PER_ROW: usize = 16;
+const ATTRIBUTE_TABLE: u16 = 0x3C0;
+// the OAM viewer draws the 64 sprites in 8 rows,every cell fits a 8x16 sprite with a border
+const OAM_COLUMNS: usize = 8;
+const OAM_CELL_WIDTH: usize = 10;
+const OAM_CELL_HEIGHT: usize = 18;
+const SWATCH_SIZE: usize = 16;
+// the scroll rectangle drawn over the nametables
+const SCROLL_COLOR: u32 = 0xFF00FF;
+
+// a sprite as the OAM has it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OamSprite {
+    pub index: usize,
+    pub x: u8,
+    pub y: u8,
+    pub tile: u8,
+    pub attributes: u8,
+}
+
+impl OamSprite {
+    // palette 4-7,the sprite ones
+    pub fn palette(&self) -> u8 {
+        4 + (self.attributes & ATTRIBUTE_PALETTE)
+    }
+
+    pub fn behind_background(&self) -> bool {
+        self.attributes & ATTRIBUTE_BEHIND_BACKGROUND != 0
+    }
+
+    pub fn flip_horizontal(&self) -> bool {
+        self.attributes & ATTRIBUTE_FLIP_HORIZONTAL != 0
+    }
+
+    pub fn flip_vertical(&self) -> bool {
+        self.attributes & ATTRIBUTE_FLIP_VERTICAL != 0
+    }
+}
+
+// "#05 x:120 y:64 tile:3A palette:5 front flip:H-"
+impl fmt::Display for OamSprite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "#{:02} x:{:3} y:{:3} tile:{:02X} palette:{} {} flip:{}{}",
+            self.index,
+            self.x,
+            self.y,
+            self.tile,
+            self.palette(),
+            if self.behind_background() {
+                "back "
+            } else {
+                "front"
+            },
+            if self.flip_horizontal() { 'H' } else { '-' },
+            if self.flip_vertical() { 'V' } else { '-' },
+        )
+    }
+}
+
+/*
+  pictures of the PPU memory for the debug viewers,they are drawn with the current palette RAM.
+  none of them change the state of the PPU
+*/
+impl PPU {
+    pub fn oam_sprites(&self) -> Vec<OamSprite> {
+        (0..OAM_SPRITES)
+            .map(|index| {
+                let entry = &self.oam_data[index * 4..index * 4 + 4];
+                OamSprite {
+                    index,
+                    y: entry[0],
+                    tile: entry[1],
+                    attributes: entry[2],
+                    x: entry[3],
+                }
+            })
+            .collect()
+    }
+
+    // the 256 tiles of pattern table 0 or 1,with one of the 8 palettes
+    pub fn pattern_table_image(&self, table: u16, palette: u8, colors: &Palette) -> Image {
+        let mut image = Image::new(PATTERN_TABLE_SIZE, PATTERN_TABLE_SIZE);
+        image.fill(0, 0, image.width, image.height, self.color(0, colors));
+        for tile in 0..256 {
+            let x = (tile % TILES_PER_ROW) * 8;
+            let y = (tile / TILES_PER_ROW) * 8;
+            let addr = (table & 1) * 0x1000 + tile as u16 * 16;
+            self.draw_tile(&mut image, x, y, addr, palette, false, false, colors);
+        }
+        image
+    }
+
+    // the 4 nametables in a 2x2 grid,with the rectangle the screen scrolls to
+    pub fn nametables_image(&self, colors: &Palette) -> Image {
+        let mut image = Image::new(SCREEN_WIDTH * 2, SCREEN_HEIGHT * 2);
+        let table = if self.ctrl.contains(ControlRegister::BACKGROUND_PATTERN_ADDR) {
+            0x1000
+        } else {
+            0
+        };
+        for nametable in 0..4 {
+            let base = NAMETABLES + nametable * NAMETABLE_SIZE;
+            let origin_x = (nametable as usize % 2) * SCREEN_WIDTH;
+            let origin_y = (nametable as usize / 2) * SCREEN_HEIGHT;
+            for row in 0..30 {
+                for column in 0..32 {
+                    let tile = self.read_vram(base + row * 32 + column);
+                    let attribute =
+                        self.read_vram(base + ATTRIBUTE_TABLE + (row / 4) * 8 + column / 4);
+                    let shift = ((row % 4) / 2) * 4 + ((column % 4) / 2) * 2;
+                    let palette = (attribute >> shift) & 0b11;
+                    self.draw_tile(
+                        &mut image,
+                        origin_x + column as usize * 8,
+                        origin_y + row as usize * 8,
+                        table + tile as u16 * 16,
+                        palette,
+                        false,
+                        false,
+                        colors,
+                    );
+                }
+            }
+        }
+
+        // the scroll the next frame starts from is in t and fine x
+        let (scroll_x, scroll_y) = self.scroll();
+        let (width, height) = (image.width, image.height);
+        for i in 0..SCREEN_WIDTH {
+            let x = (scroll_x + i) % width;
+            image.set(x, scroll_y, SCROLL_COLOR);
+            image.set(x, (scroll_y + SCREEN_HEIGHT - 1) % height, SCROLL_COLOR);
+        }
+        for i in 0..SCREEN_HEIGHT {
+            let y = (scroll_y + i) % height;
+            image.set(scroll_x, y, SCROLL_COLOR);
+            image.set((scroll_x + SCREEN_WIDTH - 1) % width, y, SCROLL_COLOR);
+        }
+        image
+    }
+
+    // the 64 sprites in a grid,in OAM order
+    pub fn oam_image(&self, colors: &Palette) -> Image {
+        let rows = OAM_SPRITES / OAM_COLUMNS;
+        let mut image = Image::new(OAM_COLUMNS * OAM_CELL_WIDTH, rows * OAM_CELL_HEIGHT);
+        let backdrop = self.color(0, colors);
+        image.fill(0, 0, image.width, image.height, backdrop);
+        let tall = self.ctrl.contains(ControlRegister::SPRITE_SIZE);
+
+        for sprite in self.oam_sprites() {
+            let x = (sprite.index % OAM_COLUMNS) * OAM_CELL_WIDTH + 1;
+            let y = (sprite.index / OAM_COLUMNS) * OAM_CELL_HEIGHT + 1;
+            let palette = sprite.palette();
+            let (flip_h, flip_v) = (sprite.flip_horizontal(), sprite.flip_vertical());
+            if tall {
+                // 8x16 sprites take the table from bit 0 of the tile,flipping also swaps the halves
+                let top = (sprite.tile as u16 & 1) * 0x1000 + (sprite.tile & 0xFE) as u16 * 16;
+                let (first, second) = if flip_v {
+                    (top + 16, top)
+                } else {
+                    (top, top + 16)
+                };
+                self.draw_tile(&mut image, x, y, first, palette, flip_h, flip_v, colors);
+                self.draw_tile(
+                    &mut image,
+                    x,
+                    y + 8,
+                    second,
+                    palette,
+                    flip_h,
+                    flip_v,
+                    colors,
+                );
+            } else {
+                let table = if self.ctrl.contains(ControlRegister::SPRITE_PATTERN_ADDR) {
+                    0x1000
+                } else {
+                    0
+                };
+                let addr = table + sprite.tile as u16 * 16;
+                self.draw_tile(&mut image, x, y, addr, palette, flip_h, flip_v, colors);
+            }
+        }
+        image
+    }
+
+    // the 32 entries of the palette RAM,the background palettes on top and the sprite ones below
+    pub fn palette_image(&self, colors: &Palette) -> Image {
+        let mut image = Image::new(16 * SWATCH_SIZE, 2 * SWATCH_SIZE);
+        for entry in 0..32 {
+            let color = self.read_vram(PALETTE_TABLE + entry as u16);
+            let (r, g, b) = colors.color(color as u16);
+            image.fill(
+                (entry % 16) * SWATCH_SIZE,
+                (entry / 16) * SWATCH_SIZE,
+                SWATCH_SIZE,
+                SWATCH_SIZE,
+                pack(r, g, b),
+            );
+        }
+        image
+    }
+
+    // (x,y) of the top left corner of the screen inside the 512x480 nametables
+    fn scroll(&self) -> (usize, usize) {
+        let coarse_x = (self.t & 0x1F) as usize;
+        let coarse_y = ((self.t >> 5) & 0x1F) as usize;
+        let nametable = ((self.t >> 10) & 0b11) as usize;
+        let fine_y = ((self.t >> 12) & 0b111) as usize;
+        let x = (nametable & 1) * SCREEN_WIDTH + coarse_x * 8 + self.x as usize;
+        let y = (nametable >> 1) * SCREEN_HEIGHT + (coarse_y * 8 + fine_y) % SCREEN_HEIGHT;
+        (x, y)
+    }
+
+    // color of pixel value 0-3 of one of the 8 palettes,0 is always the backdrop
+    fn color(&self, index: u8, colors: &Palette) -> u32 {
+        let entry = if index & 0b11 == 0 { 0 } else { index };
+        let (r, g, b) = colors.color(self.read_vram(PALETTE_TABLE + entry as u16) as u16);
+        pack(r, g, b)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_tile(
+        &self,
+        image: &mut Image,
+        x: usize,
+        y: usize,
+        addr: u16,
+        palette: u8,
+        flip_h: bool,
+        flip_v: bool,
+        colors: &Palette,
+    ) {
+        for row in 0..8 {
+            let source_row = if flip_v { 7 - row } else { row };
+            let lo = self.read_vram(addr + source_row as u16);
+            let hi = self.read_vram(addr + source_row as u16 + 8);
+            for column in 0..8 {
+                let bit = if flip_h { column } else { 7 - column };
+                let pixel = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+                if pixel == 0 && palette >= 4 {
+                    // transparent sprite pixels leave the backdrop of the cell
+                    continue;
+                }
+                let color = self.color(palette * 4 + pixel, colors);
+                image.set(x + column, y + row, color);
+            }
+        }
+    }
+}
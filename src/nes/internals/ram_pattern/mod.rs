@@ -0,0 +1,97 @@
+use std::{fmt, str::FromStr};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[cfg(test)]
+mod test;
+
+// bytes in a row with the same value in the alternating pattern
+const ALTERNATING_RUN: usize = 4;
+
+/*
+  what the RAM chips hold when the console is switched on.
+  the real chips come up with garbage,some games read it by mistake and some seed their random numbers with it.
+  the alternating pattern is the one most emulators use,4 bytes of $00 then 4 bytes of $FF
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum RamPattern {
+    #[default]
+    Zeros,
+    Ones,
+    Alternating,
+    // the same seed fills the memories with the same bytes,so a run can be reproduced
+    Random(u64),
+}
+
+impl RamPattern {
+    // the memories must be filled in the same order every time for a seed to give the same state
+    pub fn filler(&self) -> RamFiller {
+        RamFiller {
+            pattern: *self,
+            rng: match self {
+                RamPattern::Random(seed) => Some(StdRng::seed_from_u64(*seed)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl FromStr for RamPattern {
+    type Err = String;
+
+    // random without a seed picks one,the seed is printed by Display
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "zeros" | "00" => Ok(RamPattern::Zeros),
+            "ones" | "ff" => Ok(RamPattern::Ones),
+            "alternating" => Ok(RamPattern::Alternating),
+            "random" => Ok(RamPattern::Random(rand::thread_rng().gen())),
+            name => match name.strip_prefix("random:") {
+                Some(seed) => seed
+                    .parse()
+                    .map(RamPattern::Random)
+                    .map_err(|_| format!("Invalid RAM seed {}", seed)),
+                None => Err(format!(
+                    "Unknown RAM pattern {},expected zeros,ff,alternating,random or random:SEED",
+                    name
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for RamPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RamPattern::Zeros => write!(f, "zeros"),
+            RamPattern::Ones => write!(f, "ff"),
+            RamPattern::Alternating => write!(f, "alternating"),
+            RamPattern::Random(seed) => write!(f, "random:{}", seed),
+        }
+    }
+}
+
+// fills the memories of a power on one after the other,the random pattern keeps going from one to the next
+pub struct RamFiller {
+    pattern: RamPattern,
+    rng: Option<StdRng>,
+}
+
+impl RamFiller {
+    pub fn fill(&mut self, memory: &mut [u8]) {
+        match (self.pattern, &mut self.rng) {
+            (RamPattern::Random(_), Some(rng)) => rng.fill(memory),
+            (RamPattern::Ones, _) => memory.fill(0xFF),
+            (RamPattern::Alternating, _) => {
+                for (i, byte) in memory.iter_mut().enumerate() {
+                    *byte = if (i / ALTERNATING_RUN).is_multiple_of(2) {
+                        0x00
+                    } else {
+                        0xFF
+                    };
+                }
+            }
+            _ => memory.fill(0x00),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::nes::internals::ram_pattern::RamPattern;
+
+fn filled(pattern: RamPattern) -> Vec<u8> {
+    let mut memory = vec![0x42; 16];
+    pattern.filler().fill(&mut memory);
+    memory
+}
+
+#[test]
+fn test_fixed_patterns() {
+    assert_eq!(filled(RamPattern::Zeros), vec![0x00; 16]);
+    assert_eq!(filled(RamPattern::Ones), vec![0xFF; 16]);
+    assert_eq!(
+        filled(RamPattern::Alternating)[..10],
+        [0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00]
+    );
+}
+
+#[test]
+fn test_random_pattern_is_reproducible() {
+    assert_eq!(filled(RamPattern::Random(7)), filled(RamPattern::Random(7)));
+    assert_ne!(filled(RamPattern::Random(7)), filled(RamPattern::Random(8)));
+}
+
+#[test]
+fn test_random_pattern_goes_on_between_memories() {
+    let mut filler = RamPattern::Random(7).filler();
+    let mut first = [0; 16];
+    let mut second = [0; 16];
+    filler.fill(&mut first);
+    filler.fill(&mut second);
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_parse_ram_pattern() {
+    assert_eq!("zeros".parse(), Ok(RamPattern::Zeros));
+    assert_eq!("FF".parse(), Ok(RamPattern::Ones));
+    assert_eq!("alternating".parse(), Ok(RamPattern::Alternating));
+    assert_eq!("random:1234".parse(), Ok(RamPattern::Random(1234)));
+    assert!(matches!("random".parse(), Ok(RamPattern::Random(_))));
+    assert!("random:x".parse::<RamPattern>().is_err());
+    assert!("garbage".parse::<RamPattern>().is_err());
+}
+
+#[test]
+fn test_display_gives_back_the_seed() {
+    let pattern: RamPattern = "random".parse().unwrap();
+    assert_eq!(pattern.to_string().parse(), Ok(pattern));
+    assert_eq!(RamPattern::Alternating.to_string(), "alternating");
+}
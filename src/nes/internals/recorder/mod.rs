@@ -0,0 +1,153 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{palette::Palette, ppu::frame::Frame, region::Region};
+
+#[cfg(test)]
+mod test;
+
+const SAMPLE_RATE: u32 = 44_100;
+const BITS_PER_SAMPLE: u16 = 16;
+const CHANNELS: u16 = 1;
+const WAV_HEADER_SIZE: u32 = 44;
+// the frame rate goes in the Y4M header as a fraction with this denominator
+const FRAME_RATE_DENOMINATOR: u64 = 1_000_000;
+
+/*
+  writes the frames into a Y4M file and the sound into a WAV file next to it,so they can be muxed without losing anything.
+  the clock is the emulated frame rate,after every frame the audio has exactly the samples that fit in the
+  time the frames lasted,rounded down.
+  there is no APU yet,so those samples are silence
+*/
+pub struct Recorder {
+    video: BufWriter<File>,
+    audio: BufWriter<File>,
+    audio_path: PathBuf,
+    frame_rate: f64,
+    frames: u64,
+    samples: u64,
+    // the WAV header is complete,nothing else can be written
+    stopped: bool,
+}
+
+impl Recorder {
+    // the audio goes to the same path with the extension changed to .wav
+    pub fn start<P: AsRef<Path>>(path: P, region: Region) -> Result<Recorder, String> {
+        let path = path.as_ref();
+        let audio_path = path.with_extension("wav");
+        let create = |path: &Path| {
+            File::create(path)
+                .map(BufWriter::new)
+                .map_err(|err| format!("Could not create {}: {}", path.display(), err))
+        };
+        let mut video = create(path)?;
+        let mut audio = create(&audio_path)?;
+
+        let frame_rate = region.frame_rate();
+        let numerator = (frame_rate * FRAME_RATE_DENOMINATOR as f64).round() as u64;
+        writeln!(
+            video,
+            "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C444",
+            Frame::new().width(),
+            Frame::new().height(),
+            numerator,
+            FRAME_RATE_DENOMINATOR
+        )
+        .map_err(|err| format!("Could not write {}: {}", path.display(), err))?;
+        // the sizes are filled when the recording stops
+        write_wav_header(&mut audio, 0)
+            .map_err(|err| format!("Could not write {}: {}", audio_path.display(), err))?;
+
+        Ok(Recorder {
+            video,
+            audio,
+            audio_path,
+            frame_rate,
+            frames: 0,
+            samples: 0,
+            stopped: false,
+        })
+    }
+
+    pub fn record_frame(&mut self, frame: &Frame, palette: &Palette) -> Result<(), String> {
+        let size = frame.width() * frame.height();
+        let mut planes = vec![0; size * 3];
+        for (i, pixel) in frame.pixels().iter().enumerate() {
+            let (y, u, v) = rgb_to_yuv(palette.color(*pixel));
+            planes[i] = y;
+            planes[size + i] = u;
+            planes[size * 2 + i] = v;
+        }
+        self.video
+            .write_all(b"FRAME\n")
+            .and_then(|_| self.video.write_all(&planes))
+            .map_err(|err| format!("Could not write the video: {}", err))?;
+        self.frames += 1;
+
+        let total = (self.frames as f64 * SAMPLE_RATE as f64 / self.frame_rate) as u64;
+        let silence = vec![0; (total - self.samples) as usize * (BITS_PER_SAMPLE / 8) as usize];
+        self.audio
+            .write_all(&silence)
+            .map_err(|err| format!("Could not write {}: {}", self.audio_path.display(), err))?;
+        self.samples = total;
+        Ok(())
+    }
+
+    pub fn stop(mut self) -> Result<(), String> {
+        self.finish()
+    }
+
+    // flushes both files and writes the size of the audio in the WAV header
+    fn finish(&mut self) -> Result<(), String> {
+        if std::mem::replace(&mut self.stopped, true) {
+            return Ok(());
+        }
+        let data_size = (self.samples * (BITS_PER_SAMPLE / 8) as u64) as u32;
+        self.video
+            .flush()
+            .map_err(|err| format!("Could not write the video: {}", err))?;
+        self.audio
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| write_wav_header(&mut self.audio, data_size))
+            .and_then(|_| self.audio.flush())
+            .map_err(|err| format!("Could not write {}: {}", self.audio_path.display(), err))
+    }
+}
+
+// a recording that is dropped without stopping it is still playable
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish() {
+            eprintln!("Recording failed: {}", err);
+        }
+    }
+}
+
+// 16 bit PCM,mono
+fn write_wav_header<W: Write>(output: &mut W, data_size: u32) -> std::io::Result<()> {
+    let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+    output.write_all(b"RIFF")?;
+    output.write_all(&(WAV_HEADER_SIZE - 8 + data_size).to_le_bytes())?;
+    output.write_all(b"WAVEfmt ")?;
+    output.write_all(&16u32.to_le_bytes())?;
+    output.write_all(&1u16.to_le_bytes())?;
+    output.write_all(&CHANNELS.to_le_bytes())?;
+    output.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    output.write_all(&(SAMPLE_RATE * block_align as u32).to_le_bytes())?;
+    output.write_all(&block_align.to_le_bytes())?;
+    output.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+    output.write_all(b"data")?;
+    output.write_all(&data_size.to_le_bytes())
+}
+
+// BT.601 with the studio range Y4M players expect,Y in 16-235 and U V in 16-240
+pub fn rgb_to_yuv((r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let y = 16.0 + (65.738 * r + 129.057 * g + 25.064 * b) / 256.0;
+    let u = 128.0 + (-37.945 * r - 74.494 * g + 112.439 * b) / 256.0;
+    let v = 128.0 + (112.439 * r - 94.154 * g - 18.285 * b) / 256.0;
+    (y.round() as u8, u.round() as u8, v.round() as u8)
+}
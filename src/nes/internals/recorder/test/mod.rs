@@ -0,0 +1,69 @@
+use crate::nes::internals::{
+    palette::Palette,
+    ppu::frame::Frame,
+    recorder::{rgb_to_yuv, Recorder},
+    region::Region,
+};
+
+#[test]
+fn test_rgb_to_yuv() {
+    assert_eq!(rgb_to_yuv((0, 0, 0)), (16, 128, 128));
+    assert_eq!(rgb_to_yuv((255, 255, 255)), (235, 128, 128));
+    let (_, u, v) = rgb_to_yuv((255, 0, 0));
+    assert!(u < 128 && v == 240);
+}
+
+#[test]
+fn test_record() {
+    let dir = std::env::temp_dir().join("recorder-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("capture.y4m");
+
+    let mut recorder = Recorder::start(&path, Region::Ntsc).unwrap();
+    let frame = Frame::new();
+    for _ in 0..3 {
+        recorder.record_frame(&frame, &Palette::default()).unwrap();
+    }
+    recorder.stop().unwrap();
+
+    let video = std::fs::read(&path).unwrap();
+    let header = b"YUV4MPEG2 W256 H240 F60098812:1000000 Ip A1:1 C444\n";
+    assert!(video.starts_with(header));
+    assert_eq!(video.len(), header.len() + 3 * (6 + 256 * 240 * 3));
+
+    // 44100 samples per second at 60.0988 frames per second
+    let audio = std::fs::read(dir.join("capture.wav")).unwrap();
+    assert_eq!(&audio[0..4], b"RIFF");
+    assert_eq!(audio.len(), 44 + 2201 * 2);
+    assert_eq!(
+        u32::from_le_bytes(audio[40..44].try_into().unwrap()),
+        2201 * 2
+    );
+    assert_eq!(
+        u32::from_le_bytes(audio[4..8].try_into().unwrap()) as usize,
+        audio.len() - 8
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_audio_follows_the_emulated_frame_rate() {
+    let dir = std::env::temp_dir().join("recorder-test-pal");
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut recorder = Recorder::start(dir.join("capture.y4m"), Region::Pal).unwrap();
+    for _ in 0..50 {
+        recorder
+            .record_frame(&Frame::new(), &Palette::default())
+            .unwrap();
+    }
+    // dropping the recorder stops it too
+    drop(recorder);
+
+    // 50 PAL frames last a bit less than a second
+    let audio = std::fs::read(dir.join("capture.wav")).unwrap();
+    assert_eq!(
+        u32::from_le_bytes(audio[40..44].try_into().unwrap()),
+        44093 * 2
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,106 @@
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
+
+use super::ppu::DOTS_PER_SCANLINE;
+
+// master clocks of the consoles,the CPU and the PPU divide them
+const NTSC_MASTER_CLOCK: f64 = 21_477_272.0;
+const PAL_MASTER_CLOCK: f64 = 26_601_712.0;
+
+/*
+  the console models,they run the same games with different clocks.
+    NTSC: 2A03 CPU and 2C02 PPU,262 scanlines at 60Hz
+    PAL: 2A07 CPU and 2C07 PPU,312 scanlines at 50Hz,the PPU runs 3.2 dots per CPU cycle
+    Dendy: a famiclone with the PAL frame,but the CPU is clocked so the PPU runs 3 dots per CPU cycle
+    and vblank starts 51 scanlines after the picture,so the NMI handler gets the same time as on NTSC
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+    Dendy,
+}
+
+impl Region {
+    pub fn scanlines_per_frame(&self) -> usize {
+        match self {
+            Region::Ntsc => 262,
+            Region::Pal | Region::Dendy => 312,
+        }
+    }
+
+    // scanline where the vblank flag is set and the NMI fires
+    pub fn vblank_scanline(&self) -> usize {
+        match self {
+            Region::Ntsc | Region::Pal => 241,
+            Region::Dendy => 291,
+        }
+    }
+
+    // the last scanline of a frame,it prepares the first visible one
+    pub fn pre_render_scanline(&self) -> usize {
+        self.scanlines_per_frame() - 1
+    }
+
+    // PPU dots run for each CPU cycle,as a fraction (dots,cycles)
+    pub fn ppu_dots_per_cpu_cycle(&self) -> (usize, usize) {
+        match self {
+            Region::Ntsc | Region::Dendy => (3, 1),
+            Region::Pal => (16, 5),
+        }
+    }
+
+    // only the NTSC PPU skips a dot of the pre-render scanline in odd frames
+    pub fn skips_odd_frame_dot(&self) -> bool {
+        *self == Region::Ntsc
+    }
+
+    // the 2C07 has the red and green emphasis bits of PPUMASK swapped,the Dendy PPUs copy it
+    pub fn swaps_red_green_emphasis(&self) -> bool {
+        *self != Region::Ntsc
+    }
+
+    /*
+      CPU cycles per second.
+      the NTSC CPU divides the master clock by 12,the PAL one by 16 and the Dendy one by 15.
+      the APU is clocked by the CPU,so its rates come from this one
+    */
+    pub fn cpu_clock_rate(&self) -> f64 {
+        match self {
+            Region::Ntsc => NTSC_MASTER_CLOCK / 12.0,
+            Region::Pal => PAL_MASTER_CLOCK / 16.0,
+            Region::Dendy => PAL_MASTER_CLOCK / 15.0,
+        }
+    }
+
+    // frames per second,60.0988 on NTSC and 50.007 on PAL and Dendy
+    pub fn frame_rate(&self) -> f64 {
+        let (dots, cycles) = self.ppu_dots_per_cpu_cycle();
+        let dots_per_second = self.cpu_clock_rate() * dots as f64 / cycles as f64;
+        let mut dots_per_frame = (DOTS_PER_SCANLINE * self.scanlines_per_frame()) as f64;
+        if self.skips_odd_frame_dot() {
+            // one every two frames is a dot shorter
+            dots_per_frame -= 0.5;
+        }
+        dots_per_second / dots_per_frame
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "ntsc" => Ok(Region::Ntsc),
+            "pal" => Ok(Region::Pal),
+            "dendy" => Ok(Region::Dendy),
+            _ => Err(format!(
+                "Unknown region {},expected ntsc,pal or dendy",
+                name
+            )),
+        }
+    }
+}
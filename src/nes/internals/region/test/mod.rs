@@ -0,0 +1,33 @@
+use crate::nes::internals::region::Region;
+
+#[test]
+fn test_frame_rates() {
+    assert!((Region::Ntsc.frame_rate() - 60.0988).abs() < 0.0001);
+    assert!((Region::Pal.frame_rate() - 50.007).abs() < 0.0001);
+    assert!((Region::Dendy.frame_rate() - 50.007).abs() < 0.0001);
+}
+
+#[test]
+fn test_cpu_clock_rates() {
+    assert_eq!(Region::Ntsc.cpu_clock_rate().round(), 1_789_773.0);
+    assert_eq!(Region::Pal.cpu_clock_rate().round(), 1_662_607.0);
+    assert_eq!(Region::Dendy.cpu_clock_rate().round(), 1_773_447.0);
+}
+
+#[test]
+fn test_frame_layout() {
+    assert_eq!(Region::Ntsc.scanlines_per_frame(), 262);
+    assert_eq!(Region::Ntsc.pre_render_scanline(), 261);
+    assert_eq!(Region::Pal.scanlines_per_frame(), 312);
+    assert_eq!(Region::Pal.vblank_scanline(), 241);
+    assert_eq!(Region::Dendy.vblank_scanline(), 291);
+    assert_eq!(Region::Dendy.pre_render_scanline(), 311);
+}
+
+#[test]
+fn test_parse_region() {
+    assert_eq!("ntsc".parse(), Ok(Region::Ntsc));
+    assert_eq!("PAL".parse(), Ok(Region::Pal));
+    assert_eq!("Dendy".parse(), Ok(Region::Dendy));
+    assert!("secam".parse::<Region>().is_err());
+}
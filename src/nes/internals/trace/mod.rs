@@ -0,0 +1,230 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use super::{cpu::CPU, disassembler::disassemble};
+
+#[cfg(test)]
+mod test;
+
+pub enum TraceFormat {
+    // same layout as nestest.log,so traces can be diffed against Nintendulator logs
+    Nintendulator,
+    Mesen,
+}
+
+pub enum TraceCondition {
+    ProgramCounter(RangeInclusive<u16>),
+    Frame(usize),
+}
+
+impl TraceCondition {
+    fn matches(&self, cpu: &CPU) -> bool {
+        match self {
+            TraceCondition::ProgramCounter(range) => {
+                range.contains(&cpu.registers().program_counter)
+            }
+            TraceCondition::Frame(frame) => cpu.bus().frame_count() >= *frame,
+        }
+    }
+}
+
+enum TraceOutput {
+    File(BufWriter<File>),
+    /*
+      keeps only the last `capacity` lines in memory.
+      they are written to `dump_path` when asked to or when the emulator crashes
+    */
+    RingBuffer {
+        lines: VecDeque<String>,
+        capacity: usize,
+        dump_path: PathBuf,
+    },
+}
+
+pub struct Tracer {
+    output: TraceOutput,
+    format: TraceFormat,
+    start: Option<TraceCondition>,
+    stop: Option<TraceCondition>,
+    tracing: bool,
+    stopped: bool,
+}
+
+impl Tracer {
+    pub fn to_file<P: AsRef<Path>>(path: P) -> io::Result<Tracer> {
+        let file = File::create(path)?;
+        Ok(Tracer::new(TraceOutput::File(BufWriter::new(file))))
+    }
+
+    pub fn ring_buffer<P: AsRef<Path>>(capacity: usize, dump_path: P) -> Tracer {
+        Tracer::new(TraceOutput::RingBuffer {
+            lines: VecDeque::with_capacity(capacity),
+            capacity,
+            dump_path: dump_path.as_ref().to_path_buf(),
+        })
+    }
+
+    fn new(output: TraceOutput) -> Tracer {
+        Tracer {
+            output,
+            format: TraceFormat::Nintendulator,
+            start: None,
+            stop: None,
+            tracing: true,
+            stopped: false,
+        }
+    }
+
+    pub fn with_format(mut self, format: TraceFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    // nothing is traced until the condition is met for the first time
+    pub fn start_when(mut self, condition: TraceCondition) -> Self {
+        self.start = Some(condition);
+        self.tracing = false;
+        self
+    }
+
+    // once the condition is met tracing stops for good
+    pub fn stop_when(mut self, condition: TraceCondition) -> Self {
+        self.stop = Some(condition);
+        self
+    }
+
+    // called by the cpu before executing each instruction
+    pub fn trace(&mut self, cpu: &CPU) {
+        if self.stopped {
+            return;
+        }
+        if let Some(stop) = &self.stop {
+            if stop.matches(cpu) {
+                self.stopped = true;
+                self.tracing = false;
+                return;
+            }
+        }
+        if !self.tracing {
+            match &self.start {
+                Some(start) if start.matches(cpu) => self.tracing = true,
+                _ => return,
+            }
+        }
+
+        let line = format_line(&self.format, cpu);
+        match &mut self.output {
+            TraceOutput::File(writer) => {
+                if let Err(err) = writeln!(writer, "{}", line) {
+                    eprintln!("Warning: could not write trace: {}", err);
+                    self.stopped = true;
+                }
+            }
+            TraceOutput::RingBuffer {
+                lines, capacity, ..
+            } => {
+                if lines.len() == *capacity {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+        }
+    }
+
+    // writes the lines kept by the ring buffer,for file traces it only flushes what is pending
+    pub fn dump(&mut self) -> io::Result<()> {
+        match &mut self.output {
+            TraceOutput::File(writer) => writer.flush(),
+            TraceOutput::RingBuffer {
+                lines, dump_path, ..
+            } => {
+                let mut writer = BufWriter::new(File::create(dump_path)?);
+                for line in lines.iter() {
+                    writeln!(writer, "{}", line)?;
+                }
+                writer.flush()
+            }
+        }
+    }
+}
+
+impl Drop for Tracer {
+    fn drop(&mut self) {
+        // a panic unwinds through the cpu,this is the last chance to see what led to the crash
+        let crashed = std::thread::panicking();
+        if crashed || matches!(self.output, TraceOutput::File(_)) {
+            if let Err(err) = self.dump() {
+                eprintln!("Warning: could not dump trace: {}", err);
+            }
+        }
+    }
+}
+
+/*
+  status flags as NV-BDIZC,upper case letters are set flags and lower case ones are unset.
+  bit 5 is not a real flag so it is always shown as '-'
+*/
+pub fn status_flags(status: u8) -> String {
+    "NV-BDIZC"
+        .chars()
+        .enumerate()
+        .map(|(i, flag)| {
+            let set = status & (0b1000_0000 >> i) != 0;
+            match flag {
+                '-' => '-',
+                _ if set => flag,
+                _ => flag.to_ascii_lowercase(),
+            }
+        })
+        .collect()
+}
+
+pub fn format_line(format: &TraceFormat, cpu: &CPU) -> String {
+    let registers = cpu.registers();
+    let instruction = disassemble(cpu, registers.program_counter);
+    let bytes = instruction
+        .bytes
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let (scanline, dot) = cpu.bus().ppu_position();
+    let cycles = cpu.bus().cycles();
+
+    match format {
+        TraceFormat::Nintendulator => format!(
+            "{:04X}  {:<8}  {:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:>3},{:>3} CYC:{}",
+            registers.program_counter,
+            bytes,
+            instruction.text,
+            registers.a,
+            registers.x,
+            registers.y,
+            registers.status,
+            registers.stack_pointer,
+            scanline,
+            dot,
+            cycles
+        ),
+        TraceFormat::Mesen => format!(
+            "{:04X}  {:<8}  {:<32}A:{:02X} X:{:02X} Y:{:02X} S:{:02X} P:{} V:{:<3} H:{:<3} Fr:{} Cycle:{}",
+            registers.program_counter,
+            bytes,
+            instruction.text,
+            registers.a,
+            registers.x,
+            registers.y,
+            registers.stack_pointer,
+            status_flags(registers.status),
+            scanline,
+            dot,
+            cpu.bus().frame_count(),
+            cycles
+        ),
+    }
+}
@@ -0,0 +1,102 @@
+use crate::nes::internals::{
+    bus::test::factory::cpu_with_program,
+    trace::{format_line, status_flags, TraceCondition, TraceFormat, TraceOutput, Tracer},
+};
+
+fn ring_buffer_lines(tracer: &Tracer) -> Vec<String> {
+    match &tracer.output {
+        TraceOutput::RingBuffer { lines, .. } => lines.iter().cloned().collect(),
+        TraceOutput::File(_) => panic!("Not a ring buffer"),
+    }
+}
+
+fn run_traced(program: Vec<u8>, tracer: Tracer) -> Tracer {
+    let mut cpu = cpu_with_program(program);
+    cpu.set_tracer(Some(tracer));
+    cpu.run();
+    cpu.take_tracer().expect("Tracer")
+}
+
+#[test]
+fn test_status_flags() {
+    assert_eq!(status_flags(0x24), "nv-bdIzc");
+    assert_eq!(status_flags(0xC3), "NV-bdiZC");
+}
+
+#[test]
+fn test_format_line_nintendulator() {
+    let cpu = cpu_with_program(vec![0x4c, 0xf5, 0xc5]);
+
+    assert_eq!(
+        format_line(&TraceFormat::Nintendulator, &cpu),
+        "0600  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FF PPU:  0,  0 CYC:0"
+    );
+}
+
+#[test]
+fn test_format_line_mesen() {
+    let cpu = cpu_with_program(vec![0xe8]);
+
+    assert_eq!(
+        format_line(&TraceFormat::Mesen, &cpu),
+        "0600  E8        INX                             A:00 X:00 Y:00 S:FF P:nv-bdIzc V:0   H:0   Fr:0 Cycle:0"
+    );
+}
+
+#[test]
+fn test_trace_counts_cycles() {
+    /*
+    LDA #$01
+    INX
+    BRK
+     */
+    let tracer = run_traced(
+        vec![0xa9, 0x01, 0xe8, 0x00],
+        Tracer::ring_buffer(10, "unused.log"),
+    );
+    let lines = ring_buffer_lines(&tracer);
+
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("PPU:  0,  0 CYC:0"));
+    assert!(lines[1].ends_with("PPU:  0,  6 CYC:2"));
+    assert!(lines[2].ends_with("PPU:  0, 12 CYC:4"));
+}
+
+#[test]
+fn test_ring_buffer_keeps_last_lines() {
+    /*
+    INX
+    INX
+    INX
+    BRK
+     */
+    let tracer = run_traced(
+        vec![0xe8, 0xe8, 0xe8, 0x00],
+        Tracer::ring_buffer(2, "unused.log"),
+    );
+    let lines = ring_buffer_lines(&tracer);
+
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("0602  E8"));
+    assert!(lines[1].starts_with("0603  00"));
+}
+
+#[test]
+fn test_trace_start_and_stop_on_program_counter() {
+    /*
+    INX
+    INY
+    DEX
+    DEY
+    BRK
+     */
+    let tracer = Tracer::ring_buffer(10, "unused.log")
+        .start_when(TraceCondition::ProgramCounter(0x0601..=0x0601))
+        .stop_when(TraceCondition::ProgramCounter(0x0603..=0x0604));
+    let tracer = run_traced(vec![0xe8, 0xc8, 0xca, 0x88, 0x00], tracer);
+    let lines = ring_buffer_lines(&tracer);
+
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("0601  C8"));
+    assert!(lines[1].starts_with("0602  CA"));
+}
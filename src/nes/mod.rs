@@ -1,14 +1,57 @@
+use std::{
+    io::{self, BufRead, Write},
+    net::SocketAddr,
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
 use rand::Rng;
 use sdl2::{
-    event::Event,
+    event::{Event, WindowEvent},
     keyboard::Keycode,
     pixels::{Color, PixelFormatEnum},
-    EventPump, Sdl,
+    rect::Rect,
+    render::{Texture, WindowCanvas},
+    EventPump,
 };
 
-use self::internals::{bus::Bus, cpu::CPU, memory::Memory};
+use self::{
+    controls::Controls,
+    internals::{
+        bus::Bus,
+        cartridge::Cartridge,
+        cpu::{CPU, RESET_VECTOR},
+        debugger::{gdb::GdbServer, StopReason},
+        image::Image,
+        memory::Memory,
+        ppu::{SCREEN_HEIGHT, SCREEN_WIDTH},
+        recorder::Recorder,
+    },
+    monitor::{catch_interrupt, run_until_interrupted, Monitor, Resume},
+    pacer::Pacer,
+    viewers::Viewers,
+};
 
+pub use self::internals::debugger::{parse_addr_range, Breakpoint};
+pub use self::internals::filter::{Filter, NtscFilter, MAX_OUTPUT_SIZE};
+pub use self::internals::palette::{NtscSettings, Palette};
+pub use self::internals::ppu::frame::Frame;
+pub use self::internals::ram_pattern::RamPattern;
+pub use self::internals::region::Region;
+pub use self::internals::trace::{TraceCondition, TraceFormat, Tracer};
+pub use self::pacer::SyncMode;
+
+mod controls;
 mod internals;
+mod monitor;
+mod pacer;
+mod viewers;
+
+#[cfg(test)]
+mod test;
+
+// the built-in snake game draws a 32x32 screen from $0200-$05FF instead of using the PPU
+const RAM_SCREEN_SIZE: usize = 32;
 
 fn color(byte: u8) -> Color {
     match byte {
@@ -24,7 +67,7 @@ fn color(byte: u8) -> Color {
     }
 }
 
-fn read_screen_state(cpu: &CPU, frame: &mut [u8; 32 * 3 * 32]) -> bool {
+fn read_screen_state(cpu: &mut CPU, frame: &mut [u8]) -> bool {
     let mut frame_idx = 0;
     let mut update = false;
     for i in 0x0200..0x600 {
@@ -41,89 +84,816 @@ fn read_screen_state(cpu: &CPU, frame: &mut [u8; 32 * 3 * 32]) -> bool {
     update
 }
 
-fn handle_user_input(cpu: &mut CPU, event_pump: &mut EventPump) {
-    for event in event_pump.poll_iter() {
+// true once for every frame,when the PPU finishes its picture
+fn is_new_frame(cpu: &CPU, last_frame: &mut usize) -> bool {
+    let (scanline, _) = cpu.bus().ppu_position();
+    let frame_count = cpu.bus().frame_count();
+    if scanline < SCREEN_HEIGHT || frame_count == *last_frame {
+        return false;
+    }
+    *last_frame = frame_count;
+    true
+}
+
+// number of the frame in the frame buffer,the previous one while a frame is being rendered
+fn completed_frame(cpu: &CPU) -> usize {
+    let (scanline, _) = cpu.bus().ppu_position();
+    let frame_count = cpu.bus().frame_count();
+    if scanline >= SCREEN_HEIGHT {
+        frame_count
+    } else {
+        frame_count.saturating_sub(1)
+    }
+}
+
+// saves the frame buffer as PNG,with the game and the frame number in text chunks
+fn save_screenshot(
+    frame: &Frame,
+    number: usize,
+    palette: &Palette,
+    rom_text: &[(&'static str, String)],
+    path: &Path,
+) -> Result<(), String> {
+    let mut text = rom_text.to_vec();
+    text.push(("Frame", number.to_string()));
+    Image::from_frame(frame, palette).save_png_with_text(path, &text)
+}
+
+// the screenshots and recordings started from the window are named after the time they were taken
+fn timestamped_path(name: &str, extension: &str) -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or_default();
+    format!("{}-{}.{}", name, millis, extension)
+}
+
+// starts recording into a new file,or stops the recording in progress
+fn toggle_recording(recorder: &mut Option<Recorder>, region: Region) {
+    match recorder.take() {
+        Some(active) => match active.stop() {
+            Ok(()) => println!("Recording stopped"),
+            Err(err) => eprintln!("Recording failed: {}", err),
+        },
+        None => {
+            let path = timestamped_path("capture", "y4m");
+            match Recorder::start(&path, region) {
+                Ok(active) => {
+                    println!("Recording to {}", path);
+                    *recorder = Some(active);
+                }
+                Err(err) => eprintln!("Recording failed: {}", err),
+            }
+        }
+    }
+}
+
+// adds the frame to the recording,a recording that fails is stopped
+fn record_frame(recorder: &mut Option<Recorder>, cpu: &CPU, palette: &Palette) {
+    if let Some(active) = recorder {
+        if let Err(err) = active.record_frame(cpu.bus().ppu().frame_buffer(), palette) {
+            eprintln!("Recording failed: {}", err);
+            *recorder = None;
+        }
+    }
+}
+
+// the snake game is loaded in RAM,so it has to be loaded again
+fn power_cycle(cpu: &mut CPU, program: Option<&[u8]>) {
+    cpu.power_on();
+    if let Some(program) = program {
+        cpu.load(program.to_vec());
+    }
+}
+
+// what the hotkeys of the window ask the emulator to do
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Hotkey {
+    // closes the emulator
+    Quit,
+    // opens the monitor
+    Pause,
+    // stops or resumes the emulation at the end of a frame
+    TogglePause,
+    // runs a single frame and pauses
+    FrameAdvance,
+    // fast forward while the key is held
+    FastForward(bool),
+    // goes through 50%,25% and 100% of the speed
+    SlowMotion,
+    // switches to the next video filter
+    NextFilter,
+    // shows or hides the PPU viewers
+    ToggleViewers,
+    // hides the PPU viewers when one of their windows is closed
+    CloseViewers,
+    // switches the palette of the pattern table viewer
+    NextViewerPalette,
+    // saves the frame on the screen as PNG
+    Screenshot,
+    // starts or stops recording the video and the audio
+    Record,
+    // presses the reset button of the console
+    Reset,
+    // switches the console off and on
+    PowerCycle,
+}
+
+/*
+  returns the hotkeys pressed since the last call.
+  the snake game reads the last key pressed from $FF
+*/
+fn handle_user_input<I: Iterator<Item = Event>>(
+    cpu: &mut CPU,
+    events: I,
+    main_window: u32,
+    ram_screen: bool,
+) -> Vec<Hotkey> {
+    let mut hotkeys = Vec::new();
+    for event in events {
         match event {
+            // closing a viewer only hides the viewers,closing the main window quits
+            Event::Window {
+                window_id,
+                win_event: WindowEvent::Close,
+                ..
+            } if window_id != main_window => hotkeys.push(Hotkey::CloseViewers),
             Event::Quit { .. }
             | Event::KeyDown {
                 keycode: Some(Keycode::Escape),
                 ..
-            } => std::process::exit(0),
+            } => hotkeys.push(Hotkey::Quit),
+            Event::KeyDown {
+                keycode: Some(Keycode::F9),
+                ..
+            } => hotkeys.push(Hotkey::Pause),
+            Event::KeyDown {
+                keycode: Some(Keycode::F7),
+                ..
+            } => hotkeys.push(Hotkey::NextFilter),
+            Event::KeyDown {
+                keycode: Some(Keycode::P),
+                ..
+            } => hotkeys.push(Hotkey::TogglePause),
+            Event::KeyDown {
+                keycode: Some(Keycode::Backslash),
+                ..
+            } => hotkeys.push(Hotkey::FrameAdvance),
+            Event::KeyDown {
+                keycode: Some(Keycode::Tab),
+                ..
+            } => hotkeys.push(Hotkey::FastForward(true)),
+            Event::KeyUp {
+                keycode: Some(Keycode::Tab),
+                ..
+            } => hotkeys.push(Hotkey::FastForward(false)),
+            Event::KeyDown {
+                keycode: Some(Keycode::Minus),
+                ..
+            } => hotkeys.push(Hotkey::SlowMotion),
+            Event::KeyDown {
+                keycode: Some(Keycode::F12),
+                ..
+            } => hotkeys.push(Hotkey::Screenshot),
+            Event::KeyDown {
+                keycode: Some(Keycode::F10),
+                ..
+            } => hotkeys.push(Hotkey::Record),
+            Event::KeyDown {
+                keycode: Some(Keycode::F5),
+                ..
+            } => hotkeys.push(Hotkey::Reset),
+            Event::KeyDown {
+                keycode: Some(Keycode::F6),
+                ..
+            } => hotkeys.push(Hotkey::PowerCycle),
+            Event::KeyDown {
+                keycode: Some(Keycode::F2),
+                ..
+            } => hotkeys.push(Hotkey::ToggleViewers),
+            Event::KeyDown {
+                keycode: Some(Keycode::F3),
+                ..
+            } => hotkeys.push(Hotkey::NextViewerPalette),
             Event::KeyDown {
                 keycode: Some(Keycode::W),
                 ..
-            } => {
+            } if ram_screen => {
                 cpu.write_to_memory(0xff, 0x77);
             }
             Event::KeyDown {
                 keycode: Some(Keycode::S),
                 ..
-            } => {
+            } if ram_screen => {
                 cpu.write_to_memory(0xff, 0x73);
             }
             Event::KeyDown {
                 keycode: Some(Keycode::A),
                 ..
-            } => {
+            } if ram_screen => {
                 cpu.write_to_memory(0xff, 0x61);
             }
             Event::KeyDown {
                 keycode: Some(Keycode::D),
                 ..
-            } => {
+            } if ram_screen => {
                 cpu.write_to_memory(0xff, 0x64);
             }
             _ => { /* do nothing */ }
         }
     }
+    hotkeys
+}
+
+// the frame whose picture the PPU is drawing,during vblank it's the next one
+fn frame_being_drawn(cpu: &CPU) -> usize {
+    let (scanline, _) = cpu.bus().ppu_position();
+    let frame_count = cpu.bus().frame_count();
+    if scanline >= SCREEN_HEIGHT {
+        frame_count + 1
+    } else {
+        frame_count
+    }
+}
+
+fn is_frame_complete(cpu: &CPU, frame: usize) -> bool {
+    let (scanline, _) = cpu.bus().ppu_position();
+    (cpu.bus().frame_count(), scanline) >= (frame, SCREEN_HEIGHT)
+}
+
+// what happens every time the PPU finishes a picture,whatever runs the CPU
+fn finish_frame(
+    cpu: &CPU,
+    controls: &mut Controls,
+    recorder: &mut Option<Recorder>,
+    palette: &Palette,
+) {
+    controls.end_frame();
+    // skipped frames are still recorded
+    record_frame(recorder, cpu, palette);
+}
+
+// one move of the snake game,true when it changed the screen
+fn snake_move<R: Rng>(cpu: &mut CPU, rng: &mut R, screen_state: &mut [u8]) -> bool {
+    cpu.write_to_memory(0xfe, rng.gen_range(1..16));
+    read_screen_state(cpu, screen_state)
+}
+
+/*
+  the SDL window,it shows the frames at the speed of the console and turns the keys into hotkeys.
+  it never runs the emulation,the run loop decides when a frame is emulated
+*/
+struct Screen<'a> {
+    canvas: WindowCanvas,
+    texture: Texture<'a>,
+    event_pump: EventPump,
+    main_window: u32,
+    title: &'static str,
+    // true when running the built-in snake game
+    ram_screen: bool,
+    // RGB24 picture shown in the window
+    screen_state: Vec<u8>,
+    filter: Filter,
+    pacer: Pacer,
+    viewers: Viewers,
+    palette: Palette,
+    rom_text: Vec<(&'static str, String)>,
+}
+
+impl Screen<'_> {
+    // waits for the time the frame would be shown on the console,unless it is skipped to catch up
+    fn show_frame(&mut self, cpu: &CPU) {
+        if let Some(speed) = self.pacer.measure_speed(Instant::now()) {
+            let title = format!("{} - {:.0}%", self.title, speed);
+            self.canvas.window_mut().set_title(&title).unwrap();
+        }
+        if !self.pacer.next_frame(Instant::now()) {
+            return;
+        }
+        self.screen_state = self
+            .filter
+            .apply(cpu.bus().ppu().frame_buffer(), &self.palette);
+        std::thread::sleep(self.pacer.time_left(Instant::now()));
+        self.present(self.filter.output_size());
+        self.viewers.update(cpu.bus(), &self.palette).unwrap();
+    }
+
+    // the snake game has no frames,it is paced by sleeping after every move
+    fn show_snake(&mut self) {
+        self.present((RAM_SCREEN_SIZE, RAM_SCREEN_SIZE));
+        std::thread::sleep(Duration::from_millis(18));
+    }
+
+    // the picture only fills the top left of the texture
+    fn present(&mut self, (width, height): (usize, usize)) {
+        let area = Rect::new(0, 0, width as u32, height as u32);
+        self.texture
+            .update(area, &self.screen_state, width * 3)
+            .unwrap();
+        self.canvas.copy(&self.texture, area, None).unwrap();
+        self.canvas.present();
+    }
+
+    // the hotkeys pressed since the last call,a paused emulation waits for the user to press something
+    fn read_hotkeys(&mut self, cpu: &mut CPU, wait: bool) -> Vec<Hotkey> {
+        let events: Vec<Event> = if wait {
+            let event = self.event_pump.wait_event();
+            std::iter::once(event)
+                .chain(self.event_pump.poll_iter())
+                .collect()
+        } else {
+            self.event_pump.poll_iter().collect()
+        };
+        handle_user_input(cpu, events.into_iter(), self.main_window, self.ram_screen)
+    }
+}
+
+/*
+  applies the hotkeys that only change the window,the controls or the recording.
+  the ones that stop the emulation are given back to the run loop
+*/
+fn apply_hotkey(
+    hotkey: Hotkey,
+    cpu: &mut CPU,
+    controls: &mut Controls,
+    recorder: &mut Option<Recorder>,
+    screen: &mut Screen,
+) -> Option<Hotkey> {
+    let ram_screen = screen.ram_screen;
+    match hotkey {
+        Hotkey::TogglePause if !ram_screen => {
+            controls.toggle_pause();
+            println!(
+                "{}",
+                if controls.is_paused() {
+                    "Paused"
+                } else {
+                    "Resumed"
+                }
+            );
+        }
+        Hotkey::FrameAdvance if !ram_screen => controls.advance_frame(),
+        Hotkey::FastForward(on) if !ram_screen => controls.set_fast_forward(on),
+        Hotkey::SlowMotion if !ram_screen => {
+            println!("Speed: {}%", controls.next_slow_motion() * 100.0);
+        }
+        Hotkey::NextFilter if !ram_screen => {
+            screen.filter = screen.filter.next();
+            println!("Filter: {}", screen.filter.name());
+        }
+        Hotkey::ToggleViewers if !ram_screen => {
+            screen.viewers.toggle();
+            if screen.viewers.is_visible() {
+                cpu.bus_mut().events_mut().set_enabled(true);
+            }
+        }
+        Hotkey::CloseViewers => screen.viewers.set_visible(false),
+        Hotkey::NextViewerPalette => screen.viewers.next_palette(),
+        // the input is read at the end of a frame,so the frame buffer is complete
+        Hotkey::Screenshot if !ram_screen => {
+            let path = timestamped_path("screenshot", "png");
+            let frame = cpu.bus().ppu().frame_buffer();
+            let number = completed_frame(cpu);
+            match save_screenshot(
+                frame,
+                number,
+                &screen.palette,
+                &screen.rom_text,
+                Path::new(&path),
+            ) {
+                Ok(()) => println!("Screenshot saved to {}", path),
+                Err(err) => eprintln!("Screenshot failed: {}", err),
+            }
+        }
+        Hotkey::Record if !ram_screen => toggle_recording(recorder, cpu.bus().ppu().region()),
+        Hotkey::Quit | Hotkey::Pause | Hotkey::Reset | Hotkey::PowerCycle => return Some(hotkey),
+        Hotkey::TogglePause
+        | Hotkey::FrameAdvance
+        | Hotkey::FastForward(_)
+        | Hotkey::SlowMotion
+        | Hotkey::NextFilter
+        | Hotkey::ToggleViewers
+        | Hotkey::Screenshot
+        | Hotkey::Record => {}
+    }
+    None
+}
+
+// false when the user quits from the monitor
+fn enter_monitor<R: BufRead, W: Write>(
+    monitor: &mut Monitor<R, W>,
+    cpu: &mut CPU,
+    reason: Option<&StopReason>,
+) -> bool {
+    match monitor.enter(cpu, reason) {
+        Ok(Resume::Continue) => true,
+        Ok(Resume::Quit) => false,
+        Err(err) => {
+            eprintln!("Monitor stopped: {}", err);
+            false
+        }
+    }
 }
 
 pub struct NES {
     cpu: CPU,
-    sdl: Sdl,
+    gdb: Option<GdbServer>,
+    palette: Palette,
+    filter: Filter,
+    // true when running the built-in snake game
+    ram_screen: bool,
+    // the snake game lives in RAM,it is loaded again after a power cycle
+    program: Option<Vec<u8>>,
+    // file name and CRC32 of the game,they are written in the screenshots
+    rom_name: Option<String>,
+    rom_crc: Option<u32>,
+    recorder: Option<Recorder>,
+    sync: SyncMode,
+    controls: Controls,
 }
 
 impl NES {
-    pub fn new(program: Vec<u8>, sdl: Sdl) -> NES {
+    pub fn new(program: Vec<u8>) -> NES {
         let bus = Bus::new();
         let mut cpu = CPU::new(bus);
-        cpu.load(program);
-        NES { cpu, sdl }
+        // the reset button starts the game over
+        cpu.poke_memory(RESET_VECTOR, 0x00);
+        cpu.poke_memory(RESET_VECTOR + 1, 0x06);
+        cpu.load(program.clone());
+        NES {
+            cpu,
+            gdb: None,
+            palette: Palette::default(),
+            filter: Filter::None,
+            ram_screen: true,
+            program: Some(program),
+            rom_name: None,
+            rom_crc: None,
+            recorder: None,
+            sync: SyncMode::default(),
+            controls: Controls::default(),
+        }
+    }
+
+    // builds the console with an iNES file plugged in,the game starts from its reset vector
+    pub fn from_rom(raw: &[u8]) -> Result<NES, String> {
+        let cartridge = Cartridge::new(raw)?;
+        let rom_crc = cartridge.crc32();
+        let bus = Bus::with_cartridge(cartridge)?;
+        let mut cpu = CPU::new(bus);
+        cpu.set_stop_on_brk(false);
+        cpu.power_on();
+        Ok(NES {
+            cpu,
+            gdb: None,
+            palette: Palette::default(),
+            filter: Filter::None,
+            ram_screen: false,
+            program: None,
+            rom_name: None,
+            rom_crc: Some(rom_crc),
+            recorder: None,
+            sync: SyncMode::default(),
+            controls: Controls::default(),
+        })
+    }
+
+    // the last picture made by the PPU,it is complete while the PPU is in vblank
+    pub fn frame(&self) -> &Frame {
+        self.cpu.bus().ppu().frame_buffer()
+    }
+
+    pub fn set_rom_name(&mut self, name: &str) {
+        self.rom_name = Some(name.to_string());
+    }
+
+    // records every frame from now on,the audio goes next to the video with the .wav extension
+    pub fn start_recording(&mut self, path: &str) -> Result<(), String> {
+        let region = self.cpu.bus().ppu().region();
+        self.recorder = Some(Recorder::start(path, region)?);
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) -> Result<(), String> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.stop(),
+            None => Ok(()),
+        }
+    }
+
+    /*
+      runs until the picture of the next frame is complete,or of the current one after a breakpoint.
+      the frame is recorded and counted by the controls,so frame advance stops after it
+    */
+    pub fn run_frame(&mut self) -> Option<StopReason> {
+        let frame = frame_being_drawn(&self.cpu);
+        let reason = self.cpu.run_while(|cpu| !is_frame_complete(cpu, frame));
+        if reason.is_none() {
+            finish_frame(
+                &self.cpu,
+                &mut self.controls,
+                &mut self.recorder,
+                &self.palette,
+            );
+        }
+        reason
+    }
+
+    // runs without a window until the picture of the frame (counting from 0) is complete
+    pub fn run_until_frame(&mut self, frame: usize) -> Result<(), String> {
+        while !is_frame_complete(&self.cpu, frame) {
+            if let Some(reason) = self.run_frame() {
+                return Err(format!(
+                    "Emulation stopped before frame {}: {}",
+                    frame, reason
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // the reset button,the game starts over but the RAM is kept
+    pub fn reset(&mut self) {
+        self.cpu.reset();
+    }
+
+    // switches the console off and on,only the cartridge is kept
+    pub fn power_cycle(&mut self) {
+        power_cycle(&mut self.cpu, self.program.as_deref());
+    }
+
+    // what the memories hold at power on,the console is switched off and on again to apply it
+    pub fn set_ram_pattern(&mut self, pattern: RamPattern) {
+        self.cpu.bus_mut().set_ram_pattern(pattern);
+        self.power_cycle();
+    }
+
+    // pause,frame advance and speed,the run loop follows them and the hotkeys of the window change them
+    pub fn controls_mut(&mut self) -> &mut Controls {
+        &mut self.controls
+    }
+
+    pub fn screenshot<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        save_screenshot(
+            self.frame(),
+            completed_frame(&self.cpu),
+            &self.palette,
+            &self.rom_text(),
+            path.as_ref(),
+        )
+    }
+
+    // what the screenshots say about the game
+    fn rom_text(&self) -> Vec<(&'static str, String)> {
+        let mut text = vec![("Software", String::from("nes-emulator"))];
+        if let Some(name) = &self.rom_name {
+            text.push(("ROM", name.clone()));
+        }
+        if let Some(crc) = self.rom_crc {
+            text.push(("ROM CRC32", format!("{:08X}", crc)));
+        }
+        // a random pattern needs its seed to reproduce the frame
+        text.push(("RAM pattern", self.cpu.bus().ram_pattern().to_string()));
+        text
+    }
+
+    // the palette used to turn frames into RGB
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    // overrides the region taken from the ROM header
+    pub fn set_region(&mut self, region: Region) {
+        self.cpu.bus_mut().set_region(region);
+    }
+
+    // post-processing of the frames before they are shown
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+    }
+
+    // what keeps the window at the speed of the console
+    pub fn set_sync(&mut self, sync: SyncMode) {
+        self.sync = sync;
+    }
+
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.cpu.set_tracer(tracer);
+    }
+
+    // draws every sprite of a scanline,games that flicker sprites to get around the 8 sprites limit stop flickering
+    pub fn set_unlimited_sprites(&mut self, unlimited: bool) {
+        self.cpu
+            .bus_mut()
+            .ppu_mut()
+            .set_unlimited_sprites(unlimited);
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.cpu.debugger_mut().add(breakpoint);
+    }
+
+    // the emulation only starts once a gdb client is attached,and it is driven by it
+    pub fn listen_gdb(&mut self, port: u16) -> std::io::Result<SocketAddr> {
+        let gdb = GdbServer::bind(("127.0.0.1", port))?;
+        let addr = gdb.local_addr()?;
+        self.gdb = Some(gdb);
+        Ok(addr)
+    }
+
+    // runs without a window,the emulation is driven by the monitor commands
+    pub fn run_monitor<R: BufRead, W: Write>(&mut self, input: R, output: W) -> io::Result<()> {
+        let mut monitor = Monitor::new(input, output);
+        monitor.set_palette(self.palette.clone());
+        let interrupted = catch_interrupt();
+        let mut reason = None;
+        loop {
+            match monitor.enter(&mut self.cpu, reason.as_ref())? {
+                Resume::Continue => reason = run_until_interrupted(&mut self.cpu, interrupted),
+                Resume::Quit => return Ok(()),
+            }
+        }
     }
 
     pub fn run(&mut self) {
-        let video_subsystem = self.sdl.video().unwrap();
+        let ram_screen = self.ram_screen;
+        let (title, width, height, scale) = if ram_screen {
+            ("Snake game", RAM_SCREEN_SIZE, RAM_SCREEN_SIZE, 10.0)
+        } else {
+            ("NES", SCREEN_WIDTH, SCREEN_HEIGHT, 3.0)
+        };
+        // the window keeps the size of the screen,the filter output is stretched into it
+        let (texture_width, texture_height) = if ram_screen {
+            (width, height)
+        } else {
+            MAX_OUTPUT_SIZE
+        };
+
+        // init sdl2
+        let sdl = sdl2::init().unwrap();
+        let video_subsystem = sdl.video().unwrap();
         let window = video_subsystem
-            .window("Snake game", (32.0 * 10.0) as u32, (32.0 * 10.0) as u32)
+            .window(
+                title,
+                (width as f32 * scale) as u32,
+                (height as f32 * scale) as u32,
+            )
             .position_centered()
+            .resizable()
             .build()
             .unwrap();
 
-        let mut canvas = window.into_canvas().present_vsync().build().unwrap();
-        let mut event_pump = self.sdl.event_pump().unwrap();
-        canvas.set_scale(10.0, 10.0).unwrap();
+        let main_window = window.id();
+        let frame_rate = self.cpu.bus().ppu().region().frame_rate();
+        let pacer = Pacer::new(self.sync, frame_rate);
+        // the snake game is paced by sleeping after every move
+        let mut canvas = if ram_screen || pacer.mode() == SyncMode::Display {
+            window.into_canvas().present_vsync().build().unwrap()
+        } else {
+            window.into_canvas().build().unwrap()
+        };
+        let event_pump = sdl.event_pump().unwrap();
+        canvas.set_scale(scale, scale).unwrap();
 
         let creator = canvas.texture_creator();
-        let mut texture = creator
-            .create_texture_target(PixelFormatEnum::RGB24, 32, 32)
+        let texture = creator
+            .create_texture_target(
+                PixelFormatEnum::RGB24,
+                texture_width as u32,
+                texture_height as u32,
+            )
             .unwrap();
 
-        let mut screen_state = [0 as u8; 32 * 3 * 32];
+        let mut screen = Screen {
+            canvas,
+            texture,
+            event_pump,
+            main_window,
+            title,
+            ram_screen,
+            screen_state: vec![0; width * 3 * height],
+            filter: std::mem::take(&mut self.filter),
+            pacer,
+            viewers: Viewers::new(&video_subsystem).unwrap(),
+            palette: self.palette.clone(),
+            rom_text: self.rom_text(),
+        };
         let mut rng = rand::thread_rng();
 
-        self.cpu.run_with_callback(move |cpu| {
-            handle_user_input(cpu, &mut event_pump);
-
-            cpu.write_to_memory(0xfe, rng.gen_range(1..16));
-
-            if read_screen_state(cpu, &mut screen_state) {
-                texture.update(None, &screen_state, 32 * 3).unwrap();
+        /*
+          gdb runs the CPU,so the window is updated from its callback at the end of every frame.
+          pausing and stepping are done from gdb,the window only shows the emulation
+        */
+        if let Some(gdb) = &self.gdb {
+            let program = self.program.clone();
+            let controls = &mut self.controls;
+            let recorder = &mut self.recorder;
+            let mut last_frame = 0;
+            let result = gdb.serve(&mut self.cpu, |cpu| {
+                if ram_screen {
+                    if !snake_move(cpu, &mut rng, &mut screen.screen_state) {
+                        return;
+                    }
+                    screen.show_snake();
+                } else {
+                    if !is_new_frame(cpu, &mut last_frame) {
+                        return;
+                    }
+                    finish_frame(cpu, controls, recorder, &screen.palette);
+                    screen.show_frame(cpu);
+                }
+                for hotkey in screen.read_hotkeys(cpu, false) {
+                    if let Hotkey::Pause | Hotkey::TogglePause | Hotkey::FrameAdvance = hotkey {
+                        continue;
+                    }
+                    match apply_hotkey(hotkey, cpu, controls, recorder, &mut screen) {
+                        Some(Hotkey::Quit) => {
+                            // exiting doesn't run the destructors,dropping them finishes their files
+                            *recorder = None;
+                            cpu.set_tracer(None);
+                            std::process::exit(0)
+                        }
+                        Some(Hotkey::Reset) => cpu.reset(),
+                        Some(Hotkey::PowerCycle) => power_cycle(cpu, program.as_deref()),
+                        _ => {}
+                    }
+                }
+                screen.pacer.set_speed(controls.speed());
+            });
+            if let Err(err) = result {
+                eprintln!("Gdb session ended: {}", err);
+            }
+            return;
+        }
 
-                canvas.copy(&texture, None, None).unwrap();
+        // breakpoints and the pause hotkey open the monitor on the terminal
+        let stdin = io::stdin();
+        let mut monitor = Monitor::new(stdin.lock(), io::stdout());
+        monitor.set_palette(self.palette.clone());
+        loop {
+            let running = self.controls.is_running();
+            if running {
+                let reason = if ram_screen {
+                    let screen_state = &mut screen.screen_state;
+                    self.cpu
+                        .run_while(|cpu| !snake_move(cpu, &mut rng, screen_state))
+                } else {
+                    self.run_frame()
+                };
+                match reason {
+                    Some(StopReason::Brk) => {
+                        println!("Emulation stopped: {}", StopReason::Brk);
+                        return;
+                    }
+                    Some(reason) => {
+                        if !enter_monitor(&mut monitor, &mut self.cpu, Some(&reason)) {
+                            return;
+                        }
+                        screen.pacer.restart();
+                        continue;
+                    }
+                    None if ram_screen => screen.show_snake(),
+                    None => screen.show_frame(&self.cpu),
+                }
+            }
 
-                canvas.present();
-                std::thread::sleep(std::time::Duration::from_millis(18))
+            // the input is read once per frame,the snake game reads it after every move
+            let hotkeys = screen.read_hotkeys(&mut self.cpu, !running);
+            if !running {
+                // the time spent paused doesn't count
+                screen.pacer.restart();
+            }
+            let mut pause = false;
+            for hotkey in hotkeys {
+                let hotkey = apply_hotkey(
+                    hotkey,
+                    &mut self.cpu,
+                    &mut self.controls,
+                    &mut self.recorder,
+                    &mut screen,
+                );
+                match hotkey {
+                    // the recorder and the tracer finish their files when the NES is dropped
+                    Some(Hotkey::Quit) => return,
+                    Some(Hotkey::Pause) => pause = true,
+                    Some(Hotkey::Reset) => {
+                        self.reset();
+                        println!("Reset");
+                    }
+                    Some(Hotkey::PowerCycle) => {
+                        self.power_cycle();
+                        println!("Power cycle");
+                    }
+                    _ => {}
+                }
             }
-        });
+            screen.pacer.set_speed(self.controls.speed());
+            if pause {
+                if !enter_monitor(&mut monitor, &mut self.cpu, None) {
+                    return;
+                }
+                screen.pacer.restart();
+            }
+        }
     }
 }
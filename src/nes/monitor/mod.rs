@@ -0,0 +1,573 @@
+use std::{
+    io::{self, BufRead, Write},
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use super::internals::{
+    cpu::{Registers, CPU},
+    debugger::{Breakpoint, BreakpointId, StopReason},
+    disassembler::disassemble,
+    events::EventKind,
+    palette::Palette,
+    trace::status_flags,
+};
+
+#[cfg(test)]
+mod test;
+
+const JSR: u8 = 0x20;
+const RTS: u8 = 0x60;
+const STACK_BASE: u16 = 0x0100;
+
+// set by Ctrl+C,it stops the emulation of the headless monitor
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+const DEFAULT_DISASSEMBLY_LINES: u16 = 10;
+const DEFAULT_DUMP_LEN: u16 = 0x80;
+const DUMP_BYTES_PER_LINE: u16 = 16;
+
+const HELP: &str = "\
+r                      show registers
+r <reg> <value>        set A X Y P SP or PC
+d [addr] [count]       disassemble
+m [addr] [len]         hex dump of memory
+e <addr> <byte>...     edit memory
+s [count]              step instructions
+n                      step over JSR
+f                      run until the current subroutine returns
+c                      continue,Ctrl+C pauses again without a window
+b <breakpoint>         add breakpoint (C000, read:0200-02FF, write:4014, opcode:00, nmi, irq, if:A == $10)
+bl                     list breakpoints
+bc <id>                clear breakpoint
+be <id> / bd <id>      enable / disable breakpoint
+bt                     backtrace from the stack
+ppu                    PPU registers
+oam                    the 64 sprites of OAM
+dump <dir> [palette]   write pattern tables (with palette 0-7),nametables,OAM,palettes and events as PNG
+ev on / ev off         log the register writes,interrupts and sprite 0 hit of every frame
+ev [kind] [from-to]    events of the last frame (ppu apu mapper nmi irq sprite0) in a scanline range
+apu                    APU and I/O registers
+q                      quit
+empty line repeats the last command";
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+// Ctrl+C goes back to the prompt instead of killing the emulator
+pub fn catch_interrupt() -> &'static AtomicBool {
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+    &INTERRUPTED
+}
+
+// runs until a stop reason or until the flag is set,a Ctrl+C pressed at the prompt doesn't count
+pub fn run_until_interrupted(cpu: &mut CPU, interrupted: &AtomicBool) -> Option<StopReason> {
+    interrupted.store(false, Ordering::Relaxed);
+    let reason = cpu.run_while(|_| !interrupted.load(Ordering::Relaxed));
+    interrupted.store(false, Ordering::Relaxed);
+    reason
+}
+
+pub enum Resume {
+    Continue,
+    Quit,
+}
+
+/*
+  text mode debugger,it only talks through the given input and output so it can be driven by a terminal or a script.
+  the emulation is paused while the monitor is reading commands
+*/
+pub struct Monitor<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    last_command: String,
+    // where "d" and "m" continue from when no address is given
+    next_disassembly: Option<u16>,
+    next_dump: u16,
+    // colors of the PNG dumps
+    palette: Palette,
+}
+
+impl<R: BufRead, W: Write> Monitor<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Monitor {
+            input,
+            output,
+            last_command: String::new(),
+            next_disassembly: None,
+            next_dump: 0,
+            palette: Palette::default(),
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    // reads commands until the user continues the emulation or quits
+    pub fn enter(&mut self, cpu: &mut CPU, reason: Option<&StopReason>) -> io::Result<Resume> {
+        match reason {
+            Some(reason) => writeln!(self.output, "Stopped: {}", reason)?,
+            None => writeln!(self.output, "Paused")?,
+        }
+        self.next_disassembly = None;
+        self.print_current(cpu)?;
+
+        loop {
+            write!(self.output, "> ")?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(Resume::Quit);
+            }
+            let line = match line.trim() {
+                "" => self.last_command.clone(),
+                line => line.to_string(),
+            };
+            self.last_command = line.clone();
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let result = match words.as_slice() {
+                [] => Ok(()),
+                ["c"] => return Ok(Resume::Continue),
+                ["q"] => return Ok(Resume::Quit),
+                ["h"] | ["help"] => {
+                    writeln!(self.output, "{}", HELP).map_err(|err| err.to_string())
+                }
+                ["r"] => self.print_registers(cpu).map_err(|err| err.to_string()),
+                ["r", register, value] => self.set_register(cpu, register, value),
+                ["d", args @ ..] => self.disassemble(cpu, args),
+                ["m", args @ ..] => self.dump(cpu, args),
+                ["e", addr, bytes @ ..] if !bytes.is_empty() => self.edit(cpu, addr, bytes),
+                ["s"] => self.step(cpu, 1),
+                ["s", count] => parse_number(count).and_then(|count| self.step(cpu, count)),
+                ["n"] => self.next(cpu),
+                ["f"] => self.finish(cpu),
+                ["b", ..] => {
+                    let spec = line[1..].trim();
+                    spec.parse::<Breakpoint>().and_then(|breakpoint| {
+                        let id = cpu.debugger_mut().add(breakpoint);
+                        writeln!(self.output, "Breakpoint #{} added", id)
+                            .map_err(|err| err.to_string())
+                    })
+                }
+                ["bl"] => self.list_breakpoints(cpu).map_err(|err| err.to_string()),
+                ["bc", id] => parse_id(id).and_then(|id| found(cpu.debugger_mut().remove(id), id)),
+                ["be", id] => {
+                    parse_id(id).and_then(|id| found(cpu.debugger_mut().set_enabled(id, true), id))
+                }
+                ["bd", id] => {
+                    parse_id(id).and_then(|id| found(cpu.debugger_mut().set_enabled(id, false), id))
+                }
+                ["bt"] => self.backtrace(cpu).map_err(|err| err.to_string()),
+                ["ppu"] => self.print_ppu(cpu).map_err(|err| err.to_string()),
+                ["apu"] => self.print_apu(cpu).map_err(|err| err.to_string()),
+                ["oam"] => self.print_oam(cpu).map_err(|err| err.to_string()),
+                ["dump", dir] => self.dump_ppu(cpu, dir, "0"),
+                ["dump", dir, palette] => self.dump_ppu(cpu, dir, palette),
+                ["ev", "on"] => {
+                    cpu.bus_mut().events_mut().set_enabled(true);
+                    Ok(())
+                }
+                ["ev", "off"] => {
+                    cpu.bus_mut().events_mut().set_enabled(false);
+                    Ok(())
+                }
+                ["ev", args @ ..] => self.print_events(cpu, args),
+                _ => Err(format!("Unknown command {}, type h for help", line)),
+            };
+            if let Err(err) = result {
+                writeln!(self.output, "Error: {}", err)?;
+            }
+        }
+    }
+
+    fn print_current(&mut self, cpu: &CPU) -> io::Result<()> {
+        let pc = cpu.registers().program_counter;
+        let line = format_instruction(cpu, pc).0;
+        writeln!(self.output, "{}", line)
+    }
+
+    fn print_registers(&mut self, cpu: &CPU) -> io::Result<()> {
+        let Registers {
+            a,
+            x,
+            y,
+            status,
+            stack_pointer,
+            program_counter,
+        } = cpu.registers();
+        writeln!(
+            self.output,
+            "A:{:02X} X:{:02X} Y:{:02X} P:{:02X} {} SP:{:02X} PC:{:04X} CYC:{}",
+            a,
+            x,
+            y,
+            status,
+            status_flags(status),
+            stack_pointer,
+            program_counter,
+            cpu.bus().cycles()
+        )
+    }
+
+    fn set_register(&mut self, cpu: &mut CPU, register: &str, value: &str) -> Result<(), String> {
+        let value = parse_hex(value)?;
+        let byte =
+            || u8::try_from(value).map_err(|_| format!("{:X} doesn't fit in {}", value, register));
+        let mut registers = cpu.registers();
+        match register.to_ascii_uppercase().as_str() {
+            "A" => registers.a = byte()?,
+            "X" => registers.x = byte()?,
+            "Y" => registers.y = byte()?,
+            "P" => registers.status = byte()?,
+            "SP" => registers.stack_pointer = byte()?,
+            "PC" => registers.program_counter = value,
+            _ => return Err(format!("Unknown register {}", register)),
+        }
+        cpu.set_registers(registers);
+        self.print_registers(cpu).map_err(|err| err.to_string())
+    }
+
+    fn disassemble(&mut self, cpu: &CPU, args: &[&str]) -> Result<(), String> {
+        let default_addr = self
+            .next_disassembly
+            .unwrap_or(cpu.registers().program_counter);
+        let mut addr = match args.first() {
+            Some(addr) => parse_hex(addr)?,
+            None => default_addr,
+        };
+        let count = match args.get(1) {
+            Some(count) => parse_number(count)?,
+            None => DEFAULT_DISASSEMBLY_LINES,
+        };
+        for _ in 0..count {
+            let (line, len) = format_instruction(cpu, addr);
+            writeln!(self.output, "{}", line).map_err(|err| err.to_string())?;
+            addr = addr.wrapping_add(len);
+        }
+        self.next_disassembly = Some(addr);
+        Ok(())
+    }
+
+    fn dump(&mut self, cpu: &CPU, args: &[&str]) -> Result<(), String> {
+        let start = match args.first() {
+            Some(addr) => parse_hex(addr)?,
+            None => self.next_dump,
+        };
+        let len = match args.get(1) {
+            Some(len) => parse_hex(len)?,
+            None => DEFAULT_DUMP_LEN,
+        };
+
+        let mut offset = 0;
+        while offset < len {
+            let line_addr = start.wrapping_add(offset);
+            let line_len = DUMP_BYTES_PER_LINE.min(len - offset);
+            let bytes: Vec<u8> = (0..line_len)
+                .map(|i| cpu.peek_memory(line_addr.wrapping_add(i)))
+                .collect();
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let ascii: String = bytes
+                .iter()
+                .map(|byte| match byte.is_ascii_graphic() {
+                    true => *byte as char,
+                    false => '.',
+                })
+                .collect();
+            writeln!(
+                self.output,
+                "{:04X}  {:<47}  |{}|",
+                line_addr,
+                hex.join(" "),
+                ascii
+            )
+            .map_err(|err| err.to_string())?;
+            offset += line_len;
+        }
+        self.next_dump = start.wrapping_add(len);
+        Ok(())
+    }
+
+    fn edit(&mut self, cpu: &mut CPU, addr: &str, bytes: &[&str]) -> Result<(), String> {
+        let addr = parse_hex(addr)?;
+        let bytes = bytes
+            .iter()
+            .map(|byte| {
+                u8::from_str_radix(byte.trim_start_matches('$'), 16)
+                    .map_err(|_| format!("Invalid byte {}", byte))
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+        for (i, byte) in bytes.into_iter().enumerate() {
+            cpu.poke_memory(addr.wrapping_add(i as u16), byte);
+        }
+        Ok(())
+    }
+
+    fn step(&mut self, cpu: &mut CPU, count: u16) -> Result<(), String> {
+        for _ in 0..count {
+            if let Some(reason) = cpu.step() {
+                writeln!(self.output, "Stopped: {}", reason).map_err(|err| err.to_string())?;
+                break;
+            }
+        }
+        self.print_current(cpu).map_err(|err| err.to_string())
+    }
+
+    // a JSR runs until it returns to the next instruction,anything else is a single step
+    fn next(&mut self, cpu: &mut CPU) -> Result<(), String> {
+        let pc = cpu.registers().program_counter;
+        if cpu.peek_memory(pc) != JSR {
+            return self.step(cpu, 1);
+        }
+        let return_addr = pc.wrapping_add(3);
+        let reason = cpu.run_while(|cpu| cpu.registers().program_counter != return_addr);
+        self.print_stop(cpu, reason)
+    }
+
+    /*
+      runs until the RTS of the current subroutine.
+      an RTS belongs to this subroutine when the stack is back to the depth it had when we started
+    */
+    fn finish(&mut self, cpu: &mut CPU) -> Result<(), String> {
+        let depth = cpu.registers().stack_pointer;
+        let at_return = |cpu: &CPU| {
+            let registers = cpu.registers();
+            cpu.peek_memory(registers.program_counter) == RTS && registers.stack_pointer >= depth
+        };
+        let reason = match at_return(cpu) {
+            true => None,
+            false => cpu.run_while(|cpu| !at_return(cpu)),
+        };
+        match reason {
+            Some(reason) => self.print_stop(cpu, Some(reason)),
+            None => self.step(cpu, 1),
+        }
+    }
+
+    fn print_stop(&mut self, cpu: &CPU, reason: Option<StopReason>) -> Result<(), String> {
+        if let Some(reason) = reason {
+            writeln!(self.output, "Stopped: {}", reason).map_err(|err| err.to_string())?;
+        }
+        self.print_current(cpu).map_err(|err| err.to_string())
+    }
+
+    fn list_breakpoints(&mut self, cpu: &CPU) -> io::Result<()> {
+        let mut empty = true;
+        for (id, breakpoint, enabled) in cpu.debugger().breakpoints() {
+            let state = if enabled { "" } else { " (disabled)" };
+            writeln!(self.output, "#{} {}{}", id, breakpoint, state)?;
+            empty = false;
+        }
+        if empty {
+            writeln!(self.output, "No breakpoints")?;
+        }
+        Ok(())
+    }
+
+    /*
+      the 6502 has no frame pointers,so the stack is scanned for return addresses.
+      JSR pushes the address of its own last byte,so a value is taken as a return address when it points
+      to the last byte of a JSR instruction
+    */
+    fn backtrace(&mut self, cpu: &CPU) -> io::Result<()> {
+        let registers = cpu.registers();
+        writeln!(self.output, "#0 {:04X}", registers.program_counter)?;
+
+        let mut frame = 1;
+        let mut addr = registers.stack_pointer as u16 + 1;
+        while addr < 0xFF {
+            let lo = cpu.peek_memory(STACK_BASE + addr) as u16;
+            let hi = cpu.peek_memory(STACK_BASE + addr + 1) as u16;
+            let jsr_addr = ((hi << 8) | lo).wrapping_sub(2);
+            if cpu.peek_memory(jsr_addr) == JSR {
+                let target = (cpu.peek_memory(jsr_addr.wrapping_add(2)) as u16) << 8
+                    | cpu.peek_memory(jsr_addr.wrapping_add(1)) as u16;
+                writeln!(
+                    self.output,
+                    "#{} {:04X} JSR ${:04X} (stack {:04X})",
+                    frame,
+                    jsr_addr,
+                    target,
+                    STACK_BASE + addr
+                )?;
+                frame += 1;
+                addr += 2;
+            } else {
+                addr += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn print_ppu(&mut self, cpu: &CPU) -> io::Result<()> {
+        let ppu = cpu.bus().ppu();
+        let (scanline, dot) = ppu.position();
+        writeln!(
+            self.output,
+            "2000  PPUCTRL    {:02X}\n2001  PPUMASK    {:02X}\n2002  PPUSTATUS  {:02X}\n2003  OAMADDR    {:02X}",
+            ppu.ctrl().bits(),
+            ppu.mask().bits(),
+            ppu.status().bits(),
+            ppu.oam_addr()
+        )?;
+        writeln!(
+            self.output,
+            "v:{:04X} t:{:04X} x:{} w:{} buffer:{:02X} bus:{:02X}",
+            ppu.vram_addr(),
+            ppu.temp_vram_addr(),
+            ppu.fine_x(),
+            ppu.write_toggle() as u8,
+            ppu.read_buffer(),
+            ppu.io_latch()
+        )?;
+        writeln!(
+            self.output,
+            "scanline:{} dot:{} frame:{} mirroring:{:?} region:{:?}",
+            scanline,
+            dot,
+            ppu.frame_count(),
+            ppu.mirroring(),
+            ppu.region()
+        )
+    }
+
+    fn print_oam(&mut self, cpu: &CPU) -> io::Result<()> {
+        for sprite in cpu.bus().ppu().oam_sprites() {
+            writeln!(self.output, "{}", sprite)?;
+        }
+        Ok(())
+    }
+
+    // writes the images of the debug viewers into a directory
+    fn dump_ppu(&mut self, cpu: &CPU, dir: &str, palette: &str) -> Result<(), String> {
+        let palette = match palette.parse::<u8>() {
+            Ok(palette) if palette < 8 => palette,
+            _ => return Err(format!("Invalid palette {},expected 0-7", palette)),
+        };
+        let dir = Path::new(dir);
+        std::fs::create_dir_all(dir)
+            .map_err(|err| format!("Could not create {}: {}", dir.display(), err))?;
+
+        let ppu = cpu.bus().ppu();
+        let colors = &self.palette;
+        let images = [
+            ("pattern0.png", ppu.pattern_table_image(0, palette, colors)),
+            ("pattern1.png", ppu.pattern_table_image(1, palette, colors)),
+            ("nametables.png", ppu.nametables_image(colors)),
+            ("oam.png", ppu.oam_image(colors)),
+            ("palettes.png", ppu.palette_image(colors)),
+            ("events.png", cpu.bus().events().image(ppu.region())),
+        ];
+        for (name, image) in images {
+            image.save_png(dir.join(name))?;
+        }
+        writeln!(self.output, "PPU dumped to {}", dir.display()).map_err(|err| err.to_string())
+    }
+
+    // the arguments are an event kind and a range of scanlines like 0-239,in any order
+    fn print_events(&mut self, cpu: &CPU, args: &[&str]) -> Result<(), String> {
+        let events = cpu.bus().events();
+        if !events.is_enabled() {
+            return Err("The event log is off,turn it on with ev on".to_string());
+        }
+        let mut kind = None;
+        let mut scanlines = 0..=usize::MAX;
+        for arg in args {
+            match arg.split_once('-') {
+                Some((from, to)) => {
+                    let from = parse_number(from)? as usize;
+                    let to = parse_number(to)? as usize;
+                    scanlines = from..=to;
+                }
+                None => kind = Some(arg.parse::<EventKind>()?),
+            }
+        }
+        for event in events.query(kind, scanlines) {
+            writeln!(self.output, "{}", event).map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    // the APU registers are write only,so the monitor shows the last value written to each of them
+    fn print_apu(&mut self, cpu: &CPU) -> io::Result<()> {
+        let registers = cpu.bus().apu_io_registers();
+        let names = [
+            "SQ1",
+            "SQ2",
+            "TRI",
+            "NOISE",
+            "DMC",
+            "OAMDMA/SND_CHN",
+            "JOY1/JOY2",
+        ];
+        let rows: [(u16, usize); 7] = [
+            (0x4000, 4),
+            (0x4004, 4),
+            (0x4008, 4),
+            (0x400C, 4),
+            (0x4010, 4),
+            (0x4014, 2),
+            (0x4016, 2),
+        ];
+        for (name, (addr, len)) in names.iter().zip(rows) {
+            let start = (addr - 0x4000) as usize;
+            let values: Vec<String> = registers[start..start + len]
+                .iter()
+                .map(|value| format!("{:02X}", value))
+                .collect();
+            writeln!(
+                self.output,
+                "{:04X}  {:<14} {}",
+                addr,
+                name,
+                values.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// returns the formatted line and the length of the instruction
+fn format_instruction(cpu: &CPU, addr: u16) -> (String, u16) {
+    let instruction = disassemble(cpu, addr);
+    let bytes: Vec<String> = instruction
+        .bytes
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+    let line = format!("{:04X}  {:<8}  {}", addr, bytes.join(" "), instruction.text);
+    (line, instruction.bytes.len() as u16)
+}
+
+fn parse_hex(value: &str) -> Result<u16, String> {
+    u16::from_str_radix(value.trim_start_matches('$'), 16)
+        .map_err(|_| format!("Invalid value {}", value))
+}
+
+fn parse_number(value: &str) -> Result<u16, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid number {}", value))
+}
+
+fn parse_id(value: &str) -> Result<BreakpointId, String> {
+    value
+        .trim_start_matches('#')
+        .parse()
+        .map_err(|_| format!("Invalid breakpoint {}", value))
+}
+
+fn found(found: bool, id: BreakpointId) -> Result<(), String> {
+    match found {
+        true => Ok(()),
+        false => Err(format!("No breakpoint #{}", id)),
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    io::Cursor,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use crate::nes::{
+    internals::{
+        bus::test::factory::cpu_with_program, cpu::CPU, debugger::StopReason, memory::Memory,
+    },
+    monitor::{run_until_interrupted, Monitor, Resume},
+};
+
+fn run_script(cpu: &mut CPU, script: &str) -> (String, Resume) {
+    let mut output = Vec::new();
+    let resume = {
+        let mut monitor = Monitor::new(Cursor::new(script), &mut output);
+        monitor.enter(cpu, None).unwrap()
+    };
+    (String::from_utf8(output).unwrap(), resume)
+}
+
+/*
+0600 JSR $0606
+0603 INX
+0604 BRK
+0605 NOP
+0606 INY
+0607 JSR $060B
+060A RTS
+060B LDA #$01
+060D RTS
+ */
+fn subroutines() -> Vec<u8> {
+    vec![
+        0x20, 0x06, 0x06, 0xe8, 0x00, 0xea, 0xc8, 0x20, 0x0b, 0x06, 0x60, 0xa9, 0x01, 0x60,
+    ]
+}
+
+#[test]
+fn test_registers_and_quit() {
+    let mut cpu = cpu_with_program(vec![0xe8, 0x00]);
+    let (output, resume) = run_script(&mut cpu, "r\nq\n");
+
+    assert!(matches!(resume, Resume::Quit));
+    assert!(output.contains("0600  E8        INX"));
+    assert!(output.contains("A:00 X:00 Y:00 P:24 nv-bdIzc SP:FF PC:0600 CYC:0"));
+}
+
+#[test]
+fn test_end_of_input_quits() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    let (_, resume) = run_script(&mut cpu, "");
+    assert!(matches!(resume, Resume::Quit));
+}
+
+#[test]
+fn test_continue() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    let (_, resume) = run_script(&mut cpu, "c\n");
+    assert!(matches!(resume, Resume::Continue));
+}
+
+#[test]
+fn test_set_register() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    let (output, _) = run_script(&mut cpu, "r x 42\nr pc 0700\nr a 100\nq\n");
+
+    assert_eq!(cpu.registers().x, 0x42);
+    assert_eq!(cpu.registers().program_counter, 0x0700);
+    assert!(output.contains("Error: 100 doesn't fit in a"));
+}
+
+#[test]
+fn test_step_and_repeat_last_command() {
+    let mut cpu = cpu_with_program(vec![0xe8, 0xe8, 0xe8, 0x00]);
+    let (output, _) = run_script(&mut cpu, "s\n\ns\nq\n");
+
+    assert_eq!(cpu.registers().x, 3);
+    assert!(output.contains("0603  00        BRK"));
+}
+
+#[test]
+fn test_disassemble_continues_from_last_address() {
+    let mut cpu = cpu_with_program(subroutines());
+    let (output, _) = run_script(&mut cpu, "d 0600 2\nd\nq\n");
+
+    assert!(output.contains("0600  20 06 06  JSR $0606\n0603  E8        INX\n"));
+    assert!(output.contains("0604  00        BRK\n0605  EA        NOP\n"));
+}
+
+#[test]
+fn test_memory_dump_and_edit() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    let (output, _) = run_script(&mut cpu, "e 10 48 49 $ff\nm 10 4\nq\n");
+
+    assert_eq!(cpu.read_from_memory(0x12), 0xff);
+    assert!(output.contains("0010  48 49 FF 00"));
+    assert!(output.contains("|HI..|"));
+}
+
+#[test]
+fn test_next_steps_over_jsr() {
+    let mut cpu = cpu_with_program(subroutines());
+    let (output, _) = run_script(&mut cpu, "n\nq\n");
+
+    assert_eq!(cpu.registers().program_counter, 0x0603);
+    assert_eq!(cpu.registers().y, 1);
+    assert!(output.contains("0603  E8        INX"));
+}
+
+#[test]
+fn test_finish_runs_until_return() {
+    let mut cpu = cpu_with_program(subroutines());
+    // step into the first subroutine,then finish it skipping the nested one
+    let (_, _) = run_script(&mut cpu, "s\nf\nq\n");
+
+    assert_eq!(cpu.registers().program_counter, 0x0603);
+    assert_eq!(cpu.registers().a, 1);
+}
+
+#[test]
+fn test_backtrace() {
+    let mut cpu = cpu_with_program(subroutines());
+    let (output, _) = run_script(&mut cpu, "b 060B\nc\n");
+    assert!(output.contains("Breakpoint #1 added"));
+
+    assert!(matches!(cpu.run(), StopReason::Breakpoint(1, _)));
+    let (output, _) = run_script(&mut cpu, "bt\nq\n");
+    assert!(output
+        .contains("#0 060B\n#1 0607 JSR $060B (stack 01FC)\n#2 0600 JSR $0606 (stack 01FE)\n"));
+}
+
+#[test]
+fn test_breakpoint_management() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    let (output, _) = run_script(
+        &mut cpu,
+        "b write:0200-02FF\nb nmi\nbd 1\nbl\nbc 2\nbc 7\nbl\nq\n",
+    );
+
+    assert!(output.contains("#1 write:0200-02FF (disabled)\n#2 NMI\n"));
+    assert!(output.contains("Error: No breakpoint #7"));
+    assert_eq!(cpu.debugger().breakpoints().count(), 1);
+}
+
+#[test]
+fn test_apu_registers() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    cpu.write_to_memory(0x4000, 0x3f);
+    cpu.write_to_memory(0x4003, 0x08);
+    let (output, _) = run_script(&mut cpu, "apu\nq\n");
+
+    assert!(output.contains("4000  SQ1            3F 00 00 08"));
+}
+
+#[test]
+fn test_unknown_command() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    let (output, _) = run_script(&mut cpu, "xyz\nq\n");
+    assert!(output.contains("Error: Unknown command xyz"));
+}
+
+#[test]
+fn test_ppu_registers() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    cpu.write_to_memory(0x2000, 0x80);
+    cpu.write_to_memory(0x2006, 0x23);
+    let (output, _) = run_script(&mut cpu, "ppu\nq\n");
+
+    assert!(output.contains("2000  PPUCTRL    80"));
+    assert!(output.contains("v:0000 t:2300 x:0 w:1 buffer:00 bus:23"));
+}
+
+#[test]
+fn test_oam() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    // sprite 1 at (0x30,0x20) with tile $3A,palette 2,behind the background and flipped horizontally
+    for value in [0x04, 0x20, 0x3A, 0b0110_0010, 0x30] {
+        let addr = if value == 0x04 { 0x2003 } else { 0x2004 };
+        cpu.write_to_memory(addr, value);
+    }
+    let (output, _) = run_script(&mut cpu, "oam\nq\n");
+
+    assert!(output.contains("#00 x:  0 y:  0 tile:00 palette:4 front flip:--"));
+    assert!(output.contains("#01 x: 48 y: 32 tile:3A palette:6 back  flip:H-"));
+}
+
+#[test]
+fn test_dump_ppu() {
+    let mut cpu = cpu_with_program(vec![0x00]);
+    let dir = std::env::temp_dir().join("monitor-test-dump");
+    let script = format!("dump {} 5\ndump {} 8\nq\n", dir.display(), dir.display());
+    let (output, _) = run_script(&mut cpu, &script);
+
+    assert!(output.contains("PPU dumped to"));
+    assert!(output.contains("Error: Invalid palette 8,expected 0-7"));
+    for name in [
+        "pattern0",
+        "pattern1",
+        "nametables",
+        "oam",
+        "palettes",
+        "events",
+    ] {
+        assert!(dir.join(format!("{}.png", name)).exists());
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_events() {
+    /*
+    0600 LDA #$1E
+    0602 STA $2001
+    0605 STA $4015
+    0608 JMP $0608
+     */
+    let mut cpu = cpu_with_program(vec![
+        0xa9, 0x1e, 0x8d, 0x01, 0x20, 0x8d, 0x15, 0x40, 0x4c, 0x08, 0x06,
+    ]);
+    let (output, _) = run_script(&mut cpu, "ev\nev on\nq\n");
+    assert!(output.contains("Error: The event log is off,turn it on with ev on"));
+
+    cpu.run_while(|cpu| cpu.bus().frame_count() < 1);
+    let (output, _) = run_script(&mut cpu, "ev\nev apu\nev 1-2\nev nope\nq\n");
+
+    assert!(output.contains("ppu     $2001 = $1E"));
+    assert!(output.contains("apu     $4015 = $1E"));
+    assert_eq!(output.matches("$2001").count(), 1);
+    assert_eq!(output.matches("$4015").count(), 2);
+    assert!(output.contains("Error: Invalid event nope"));
+}
+
+#[test]
+fn test_interrupt_stops_the_emulation() {
+    // JMP $0600
+    let mut cpu = cpu_with_program(vec![0x4c, 0x00, 0x06]);
+    let interrupted = AtomicBool::new(false);
+    let reason = thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(10));
+            interrupted.store(true, Ordering::Relaxed);
+        });
+        run_until_interrupted(&mut cpu, &interrupted)
+    });
+
+    assert_eq!(reason, None);
+    assert!(!interrupted.load(Ordering::Relaxed));
+}
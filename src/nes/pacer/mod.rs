@@ -0,0 +1,162 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+#[cfg(test)]
+mod test;
+
+// frames in a row that can be skipped to catch up,after that the pacer gives up and starts counting from now
+const MAX_FRAME_SKIP: usize = 4;
+// how often the speed is measured
+const SPEED_INTERVAL: Duration = Duration::from_secs(1);
+
+// what keeps the emulation at the speed of the console.
+// there's no syncing to the audio buffer,the APU isn't emulated so there's no audio to fill it with
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SyncMode {
+    // sleeps until the time the frame would be shown on the console
+    #[default]
+    Timer,
+    // waits for the vsync of the display,smoother but it runs at the refresh rate of the monitor
+    Display,
+}
+
+impl FromStr for SyncMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "timer" => Ok(SyncMode::Timer),
+            "display" => Ok(SyncMode::Display),
+            _ => Err(format!("Unknown sync {},expected timer or display", name)),
+        }
+    }
+}
+
+/*
+  keeps the frames on the emulated clock,60.0988 per second on NTSC and 50.007 on PAL.
+  every frame has a deadline one frame after the previous one,so the error of a sleep doesn't add up.
+  when the host can't keep up the frames are emulated but not shown until it catches up.
+  fast forward only shows some of the frames,so the window isn't asked for more than the console would show
+*/
+pub struct Pacer {
+    mode: SyncMode,
+    frame_duration: Duration,
+    // times the speed of the console,None is uncapped
+    speed: Option<f64>,
+    deadline: Option<Instant>,
+    skipped: usize,
+    // frames emulated since the deadlines started
+    frames: usize,
+    // frames emulated since the speed was last measured
+    speed_frames: usize,
+    speed_start: Option<Instant>,
+}
+
+impl Pacer {
+    pub fn new(mode: SyncMode, frame_rate: f64) -> Self {
+        Pacer {
+            mode,
+            frame_duration: Duration::from_secs_f64(1.0 / frame_rate),
+            speed: Some(1.0),
+            deadline: None,
+            skipped: 0,
+            frames: 0,
+            speed_frames: 0,
+            speed_start: None,
+        }
+    }
+
+    pub fn mode(&self) -> SyncMode {
+        self.mode
+    }
+
+    pub fn set_speed(&mut self, speed: Option<f64>) {
+        if speed != self.speed {
+            self.speed = speed;
+            self.restart();
+        }
+    }
+
+    // forgets the deadlines and the speed measure,after a pause the frames start again from now
+    pub fn restart(&mut self) {
+        self.deadline = None;
+        self.skipped = 0;
+        self.frames = 0;
+        self.speed_frames = 0;
+        self.speed_start = None;
+    }
+
+    // called once a frame is emulated,returns false when it shouldn't be shown
+    pub fn next_frame(&mut self, now: Instant) -> bool {
+        let speed = match self.speed {
+            Some(speed) => speed,
+            // uncapped,a frame is shown every console frame of real time and the rest are dropped
+            None => {
+                let show = self.deadline.is_none_or(|deadline| now >= deadline);
+                if show {
+                    self.deadline = Some(now + self.frame_duration);
+                }
+                return show;
+            }
+        };
+
+        // at 3x only 1 out of 3 frames is shown,each of them lasting a frame of the console
+        let shown_every = speed.ceil().max(1.0) as usize;
+        self.frames += 1;
+        if !(self.frames - 1).is_multiple_of(shown_every) {
+            return false;
+        }
+        let step = self.frame_duration.mul_f64(shown_every as f64 / speed);
+        let deadline = match self.deadline {
+            Some(deadline) => deadline + step,
+            None => now,
+        };
+        self.deadline = Some(deadline);
+        if self.waits_for_display() {
+            return true;
+        }
+
+        if now.saturating_duration_since(deadline) > step {
+            if self.skipped < MAX_FRAME_SKIP {
+                self.skipped += 1;
+                return false;
+            }
+            // too far behind,catching up would run the game fast for a while
+            self.deadline = Some(now);
+        }
+        self.skipped = 0;
+        true
+    }
+
+    // how long to sleep before showing the frame
+    pub fn time_left(&self, now: Instant) -> Duration {
+        match self.deadline {
+            Some(deadline) if self.speed.is_some() && !self.waits_for_display() => {
+                deadline.saturating_duration_since(now)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    // vsync only keeps the speed of the console,other speeds need the timer
+    fn waits_for_display(&self) -> bool {
+        self.mode == SyncMode::Display && self.speed == Some(1.0)
+    }
+
+    // the speed in percent of the console,once every second
+    pub fn measure_speed(&mut self, now: Instant) -> Option<f64> {
+        let start = *self.speed_start.get_or_insert(now);
+        self.speed_frames += 1;
+        let elapsed = now.duration_since(start);
+        if elapsed < SPEED_INTERVAL {
+            return None;
+        }
+        // the first frame only starts the measure
+        let emulated = self.frame_duration * (self.speed_frames - 1) as u32;
+        self.speed_frames = 1;
+        self.speed_start = Some(now);
+        Some(emulated.as_secs_f64() / elapsed.as_secs_f64() * 100.0)
+    }
+}
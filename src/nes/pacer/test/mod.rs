@@ -0,0 +1,126 @@
+use std::time::{Duration, Instant};
+
+use crate::nes::pacer::{Pacer, SyncMode};
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn test_deadlines_follow_the_frame_rate() {
+    let mut pacer = Pacer::new(SyncMode::Timer, 50.0);
+    let start = Instant::now();
+    assert!(pacer.next_frame(start));
+    assert_eq!(pacer.time_left(start), Duration::ZERO);
+
+    // the frame took 5ms to emulate,so there are 15ms left
+    assert!(pacer.next_frame(start + ms(5)));
+    assert_eq!(pacer.time_left(start + ms(5)), ms(15));
+
+    // a late sleep doesn't push the next deadlines
+    assert!(pacer.next_frame(start + ms(22)));
+    assert_eq!(pacer.time_left(start + ms(22)), ms(18));
+}
+
+#[test]
+fn test_frames_are_skipped_when_behind() {
+    let mut pacer = Pacer::new(SyncMode::Timer, 50.0);
+    let start = Instant::now();
+    assert!(pacer.next_frame(start));
+    // the host stalled for 70ms,the frames due at 20 and 40 are skipped
+    assert!(!pacer.next_frame(start + ms(70)));
+    assert!(!pacer.next_frame(start + ms(71)));
+    assert!(pacer.next_frame(start + ms(72)));
+    assert_eq!(pacer.time_left(start + ms(72)), Duration::ZERO);
+}
+
+#[test]
+fn test_pacer_gives_up_catching_up() {
+    let mut pacer = Pacer::new(SyncMode::Timer, 50.0);
+    let start = Instant::now();
+    assert!(pacer.next_frame(start));
+    let late = start + ms(1000);
+    for _ in 0..4 {
+        assert!(!pacer.next_frame(late));
+    }
+    assert!(pacer.next_frame(late));
+    // the deadlines start again from there
+    assert!(pacer.next_frame(late + ms(1)));
+    assert_eq!(pacer.time_left(late + ms(1)), ms(19));
+}
+
+#[test]
+fn test_display_sync_never_waits_or_skips() {
+    let mut pacer = Pacer::new(SyncMode::Display, 50.0);
+    let start = Instant::now();
+    assert!(pacer.next_frame(start));
+    assert!(pacer.next_frame(start + ms(500)));
+    assert!(pacer.next_frame(start + ms(501)));
+    assert_eq!(pacer.time_left(start), Duration::ZERO);
+}
+
+#[test]
+fn test_speed() {
+    let mut pacer = Pacer::new(SyncMode::Timer, 50.0);
+    let start = Instant::now();
+    assert_eq!(pacer.measure_speed(start), None);
+    for frame in 1..25 {
+        assert_eq!(pacer.measure_speed(start + ms(frame * 40)), None);
+    }
+    // 25 frames of 20ms in a second
+    let speed = pacer.measure_speed(start + ms(1000)).unwrap();
+    assert!((speed - 50.0).abs() < 0.001);
+}
+
+#[test]
+fn test_parse_sync_mode() {
+    assert_eq!("timer".parse(), Ok(SyncMode::Timer));
+    assert_eq!("display".parse(), Ok(SyncMode::Display));
+    assert!("vsync".parse::<SyncMode>().is_err());
+}
+
+#[test]
+fn test_fast_forward_shows_some_frames() {
+    let mut pacer = Pacer::new(SyncMode::Timer, 50.0);
+    pacer.set_speed(Some(2.0));
+    let start = Instant::now();
+    assert!(pacer.next_frame(start));
+    assert!(!pacer.next_frame(start + ms(1)));
+    // 2 frames of the console in 20ms
+    assert!(pacer.next_frame(start + ms(2)));
+    assert_eq!(pacer.time_left(start + ms(2)), ms(18));
+}
+
+#[test]
+fn test_slow_motion_stretches_the_deadlines() {
+    let mut pacer = Pacer::new(SyncMode::Display, 50.0);
+    pacer.set_speed(Some(0.25));
+    let start = Instant::now();
+    assert!(pacer.next_frame(start));
+    assert!(pacer.next_frame(start + ms(5)));
+    assert_eq!(pacer.time_left(start + ms(5)), ms(75));
+}
+
+#[test]
+fn test_uncapped_shows_a_frame_per_console_frame() {
+    let mut pacer = Pacer::new(SyncMode::Timer, 50.0);
+    pacer.set_speed(None);
+    let start = Instant::now();
+    assert!(pacer.next_frame(start));
+    assert!(!pacer.next_frame(start + ms(5)));
+    assert!(!pacer.next_frame(start + ms(19)));
+    assert!(pacer.next_frame(start + ms(20)));
+    assert_eq!(pacer.time_left(start + ms(20)), Duration::ZERO);
+}
+
+#[test]
+fn test_restart_forgets_the_deadlines() {
+    let mut pacer = Pacer::new(SyncMode::Timer, 50.0);
+    let start = Instant::now();
+    assert!(pacer.next_frame(start));
+    // paused for a second
+    pacer.restart();
+    assert!(pacer.next_frame(start + ms(1000)));
+    assert!(pacer.next_frame(start + ms(1001)));
+    assert_eq!(pacer.time_left(start + ms(1001)), ms(19));
+}
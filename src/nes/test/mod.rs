@@ -0,0 +1,57 @@
+use crate::nes::{completed_frame, internals::memory::Memory, NES, SCREEN_HEIGHT};
+
+// INX forever
+const PROGRAM: [u8; 4] = [0xe8, 0x4c, 0x00, 0x06];
+
+#[test]
+fn test_reset_keeps_ram() {
+    let mut nes = NES::new(PROGRAM.to_vec());
+    nes.cpu.write_to_memory(0x0010, 0x99);
+
+    nes.reset();
+    let registers = nes.cpu.registers();
+    assert_eq!(registers.program_counter, 0x0600);
+    assert_eq!(registers.stack_pointer, 0xFC);
+    assert_eq!(nes.cpu.read_from_memory(0x0010), 0x99);
+}
+
+#[test]
+fn test_power_cycle_loads_the_snake_game_again() {
+    let mut nes = NES::new(PROGRAM.to_vec());
+    nes.cpu.write_to_memory(0x0010, 0x99);
+    nes.cpu.write_to_memory(0x0600, 0x00);
+
+    nes.power_cycle();
+    let registers = nes.cpu.registers();
+    assert_eq!(registers.program_counter, 0x0600);
+    assert_eq!(registers.stack_pointer, 0xFD);
+    assert_eq!(nes.cpu.read_from_memory(0x0010), 0x00);
+    assert_eq!(nes.cpu.read_from_memory(0x0600), 0xe8);
+}
+
+#[test]
+fn test_run_frame_stops_when_the_picture_is_complete() {
+    let mut nes = NES::new(PROGRAM.to_vec());
+    assert_eq!(nes.run_frame(), None);
+    assert_eq!(completed_frame(&nes.cpu), 0);
+    assert!(nes.cpu.bus().ppu_position().0 >= SCREEN_HEIGHT);
+
+    assert_eq!(nes.run_frame(), None);
+    assert_eq!(completed_frame(&nes.cpu), 1);
+    nes.run_until_frame(4).unwrap();
+    assert_eq!(completed_frame(&nes.cpu), 4);
+}
+
+#[test]
+fn test_frame_advance_runs_one_frame_of_the_core() {
+    let mut nes = NES::new(PROGRAM.to_vec());
+    nes.controls_mut().pause();
+    nes.run_frame();
+    assert!(!nes.controls.is_running());
+
+    nes.controls_mut().advance_frame();
+    assert!(nes.controls.is_running());
+    nes.run_frame();
+    assert!(!nes.controls.is_running());
+    assert_eq!(completed_frame(&nes.cpu), 1);
+}
@@ -0,0 +1,117 @@
+use sdl2::{pixels::PixelFormatEnum, render::Canvas, video::Window, VideoSubsystem};
+
+use super::internals::{bus::Bus, image::Image, palette::Palette, ppu::viewer::PATTERN_TABLE_SIZE};
+
+const SCALE: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    PatternTables,
+    Nametables,
+    Oam,
+    Palettes,
+    Events,
+}
+
+/*
+  the PPU debug viewers,each one in its own window.
+  they start hidden,F2 shows them and F3 changes the palette of the pattern tables.
+  the events window needs the event log of the bus to be on
+*/
+pub struct Viewers {
+    windows: Vec<(View, Canvas<Window>)>,
+    visible: bool,
+    pattern_palette: u8,
+}
+
+impl Viewers {
+    pub fn new(video: &VideoSubsystem) -> Result<Self, String> {
+        let views = [
+            (View::PatternTables, "Pattern tables", 256, 128),
+            (View::Nametables, "Nametables", 512, 480),
+            (View::Oam, "OAM", 80, 144),
+            (View::Palettes, "Palettes", 256, 32),
+            (View::Events, "Events", 341, 262),
+        ];
+        let mut windows = Vec::new();
+        for (view, title, width, height) in views {
+            let window = video
+                .window(title, width * SCALE, height * SCALE)
+                .hidden()
+                .build()
+                .map_err(|err| err.to_string())?;
+            let canvas = window
+                .into_canvas()
+                .build()
+                .map_err(|err| err.to_string())?;
+            windows.push((view, canvas));
+        }
+        Ok(Viewers {
+            windows,
+            visible: false,
+            pattern_palette: 0,
+        })
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_visible(!self.visible);
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        for (_, canvas) in self.windows.iter_mut() {
+            if visible {
+                canvas.window_mut().show();
+            } else {
+                canvas.window_mut().hide();
+            }
+        }
+    }
+
+    // the pattern tables go through the 8 palettes
+    pub fn next_palette(&mut self) {
+        self.pattern_palette = (self.pattern_palette + 1) % 8;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn update(&mut self, bus: &Bus, colors: &Palette) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        let ppu = bus.ppu();
+        for (view, canvas) in self.windows.iter_mut() {
+            let image = match view {
+                View::PatternTables => {
+                    let mut image = Image::new(PATTERN_TABLE_SIZE * 2, PATTERN_TABLE_SIZE);
+                    for table in 0..2 {
+                        let pattern = ppu.pattern_table_image(table, self.pattern_palette, colors);
+                        image.blit(table as usize * PATTERN_TABLE_SIZE, 0, &pattern);
+                    }
+                    image
+                }
+                View::Nametables => ppu.nametables_image(colors),
+                View::Oam => ppu.oam_image(colors),
+                View::Palettes => ppu.palette_image(colors),
+                View::Events => bus.events().image(ppu.region()),
+            };
+
+            let creator = canvas.texture_creator();
+            let mut texture = creator
+                .create_texture_static(
+                    PixelFormatEnum::RGB24,
+                    image.width as u32,
+                    image.height as u32,
+                )
+                .map_err(|err| err.to_string())?;
+            texture
+                .update(None, &image.to_rgb24(), image.width * 3)
+                .map_err(|err| err.to_string())?;
+            canvas.copy(&texture, None, None)?;
+            canvas.present();
+        }
+        Ok(())
+    }
+}